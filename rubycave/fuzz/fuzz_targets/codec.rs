@@ -0,0 +1,26 @@
+//! A stream of arbitrary bytes from a peer, decoded and validated like the
+//! server does
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rubycave::{
+    protocol::{codec::PacketCodec, Packet, PacketValidator},
+    tokio_util::{bytes::BytesMut, codec::Decoder},
+};
+
+fuzz_target!(|data: &[u8]| {
+    let validator = PacketValidator::new().unwrap();
+    let mut codec = PacketCodec::new(1 << 16);
+    let mut buf = BytesMut::from(data);
+
+    while let Ok(Some(packet)) = codec.decode(&mut buf) {
+        match packet {
+            Packet::Client(packet) => {
+                let _ = validator.check_client(&packet);
+            }
+            Packet::Server(packet) => {
+                let _ = validator.check_server(&packet);
+            }
+        }
+    }
+});
@@ -0,0 +1,132 @@
+//! Arbitrary bytes as a single whole frame, so every input reaches the
+//! archive checks. Whatever passes validation gets used the way the client
+//! and server would, which must not panic.
+#![no_main]
+
+use std::{sync::OnceLock, time::Instant};
+
+use libfuzzer_sys::fuzz_target;
+use rubycave::{
+    glam::Vec3,
+    protocol::{client, codec::PacketCodec, compress, server, Packet, PacketValidator},
+    tokio_util::{bytes::BytesMut, codec::Decoder},
+    world::{light, BlockRegistry, Chunk, ChunkPos, LightKind, LocalPos, World},
+};
+use rubycave_server::player::Player;
+
+const SPAWN: Vec3 = Vec3::new(8.5, 1.0, 8.5);
+
+/// A chunk with a stone floor for the player to act in, built once
+fn world() -> &'static (World, BlockRegistry) {
+    static WORLD: OnceLock<(World, BlockRegistry)> = OnceLock::new();
+
+    WORLD.get_or_init(|| {
+        let registry = BlockRegistry::builtin().unwrap();
+        let stone = registry.default_state_by_name("stone").unwrap();
+        let mut chunk = Chunk::new(ChunkPos::new(0, 0, 0));
+
+        for local in LocalPos::iter().filter(|local| local.y() < 1) {
+            chunk.set(local, stone);
+        }
+
+        let mut world = World::new();
+        world.insert_chunk(Box::new(chunk));
+        light::init_chunk(&mut world, &registry, ChunkPos::new(0, 0, 0));
+
+        (world, registry)
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut buf = BytesMut::new();
+    let mut length = data.len();
+
+    loop {
+        let byte = (length & 0x7f) as u8;
+        length >>= 7;
+
+        if length == 0 {
+            buf.extend_from_slice(&[byte]);
+            break;
+        }
+
+        buf.extend_from_slice(&[byte | 0x80]);
+    }
+
+    buf.extend_from_slice(data);
+
+    let Ok(Some(packet)) = PacketCodec::default().decode(&mut buf) else {
+        return;
+    };
+
+    let validator = PacketValidator::new().unwrap();
+
+    match packet {
+        Packet::Client(packet) => {
+            if validator.check_client(&packet).is_err() {
+                return;
+            }
+
+            let (world, registry) = world();
+            let mut player = Player::new("fuzz".to_owned(), SPAWN);
+            let now = Instant::now();
+
+            match packet {
+                client::Packet::Move {
+                    x,
+                    y,
+                    z,
+                    yaw,
+                    pitch,
+                    on_ground,
+                } => {
+                    let position = Vec3::new(x, y, z);
+
+                    player.handle_move(world, registry, SPAWN, 0.0, 0.0, true, now);
+                    player.handle_move(world, registry, position, yaw, pitch, on_ground, now);
+                }
+                client::Packet::Dig { pos, .. } => {
+                    player.start_dig(pos, now);
+                    player.finish_dig(world, registry, pos, now);
+                }
+                client::Packet::Place { pos, face, state } => {
+                    player.check_place(world, registry, pos, face, state);
+                }
+                _ => {}
+            }
+        }
+        Packet::Server(packet) => {
+            if validator.check_server(&packet).is_err() {
+                return;
+            }
+
+            let Ok(packet) = compress::decompress_packet(packet) else {
+                return;
+            };
+
+            if validator.check_server(&packet).is_err() {
+                return;
+            }
+
+            match packet {
+                server::Packet::Chunk(chunk) => {
+                    for pos in LocalPos::iter() {
+                        chunk.pos.block(pos);
+                        chunk.get(pos);
+                        chunk.get_light(LightKind::Block, pos);
+                        chunk.get_light(LightKind::Sky, pos);
+                    }
+                }
+                server::Packet::BlockChange { pos, .. } => {
+                    pos.split();
+                }
+                server::Packet::MultiBlockChange { chunk, changes } => {
+                    for change in changes {
+                        chunk.block(LocalPos::from_index(change.index as usize));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+});
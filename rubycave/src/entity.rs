@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use glam::Vec3;
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::{
+    physics::{self, Aabb},
+    world::{BlockRegistry, BlockState, World},
+    TICK_RATE,
+};
+
+/// Downward acceleration, in blocks per second squared
+pub const GRAVITY: f32 = 16.0;
+
+/// Fraction of velocity kept every twentieth of a second
+const DRAG: f32 = 0.98;
+
+/// Ticks a falling block can fall for before it gives up and drops
+pub const MAX_FALL_AGE: u32 = 30 * TICK_RATE;
+
+/// Ticks a dropped item stays in the world
+pub const ITEM_LIFETIME: u32 = 300 * TICK_RATE;
+
+#[derive(
+    Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug,
+)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct EntityId(pub u32);
+
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum EntityKind {
+    /// A block falling until it lands and is placed back
+    FallingBlock(BlockState),
+    /// A dropped block lying around
+    Item(BlockState),
+    /// Moved by its client rather than by physics
+    Player,
+}
+
+impl EntityKind {
+    pub fn width(self) -> f32 {
+        match self {
+            EntityKind::FallingBlock(_) => 0.98,
+            EntityKind::Item(_) => 0.25,
+            EntityKind::Player => 0.6,
+        }
+    }
+
+    pub fn height(self) -> f32 {
+        match self {
+            EntityKind::Player => 1.8,
+            _ => self.width(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entity {
+    pub kind: EntityKind,
+    pub name: Option<String>,
+    /// Bottom center of the entity
+    pub position: Vec3,
+    /// In blocks per second
+    pub velocity: Vec3,
+    /// In radians
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+    /// Ticks since it spawned
+    pub age: u32,
+}
+
+impl Entity {
+    pub fn new(kind: EntityKind, position: Vec3, velocity: Vec3) -> Self {
+        Self {
+            kind,
+            name: None,
+            position,
+            velocity,
+            yaw: 0.0,
+            pitch: 0.0,
+            on_ground: false,
+            age: 0,
+        }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_feet(self.position, self.kind.width(), self.kind.height())
+    }
+
+    /// Runs one tick of gravity and drag, moving through the world
+    pub fn update(&mut self, world: &World, registry: &BlockRegistry) {
+        let delta = 1.0 / TICK_RATE as f32;
+
+        self.velocity.y -= GRAVITY * delta;
+        self.velocity *= DRAG.powf(20.0 * delta);
+
+        let movement = physics::move_aabb(
+            world,
+            registry,
+            self.aabb(),
+            self.velocity * delta,
+            self.on_ground,
+            0.0,
+        );
+
+        self.position += movement.motion;
+        self.on_ground = movement.on_ground;
+        self.age += 1;
+
+        if movement.collided_x {
+            self.velocity.x = 0.0;
+        }
+
+        if movement.collided_y {
+            self.velocity.y = 0.0;
+        }
+
+        if movement.collided_z {
+            self.velocity.z = 0.0;
+        }
+    }
+}
+
+/// Entities of a world by id
+#[derive(Default)]
+pub struct Entities {
+    next_id: u32,
+    entities: BTreeMap<EntityId, Entity>,
+}
+
+impl Entities {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self, entity: Entity) -> EntityId {
+        let id = EntityId(self.next_id);
+
+        self.next_id += 1;
+        self.entities.insert(id, entity);
+
+        id
+    }
+
+    pub fn remove(&mut self, id: EntityId) -> Option<Entity> {
+        self.entities.remove(&id)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Entity> {
+        self.entities.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
+        self.entities.get_mut(&id)
+    }
+
+    pub fn ids(&self) -> Vec<EntityId> {
+        self.entities.keys().copied().collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &Entity)> {
+        self.entities.iter().map(|(id, entity)| (*id, entity))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut Entity)> {
+        self.entities.iter_mut().map(|(id, entity)| (*id, entity))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
@@ -0,0 +1,272 @@
+use crate::{
+    math::FastPrng,
+    world::{
+        Biome, BlockPos, BlockRegistry, BlockState, Chunk, ChunkPos, LocalPos, CHUNK_HEIGHT,
+        CHUNK_LENGTH, CHUNK_WIDTH,
+    },
+    InfiniteIterator,
+};
+
+use feature::{FeatureRegistry, MAX_REACH};
+use noise::Octaves;
+
+mod cave;
+mod decorate;
+pub mod feature;
+pub mod noise;
+mod ore;
+
+/// Lowest layer of the world, everything below it is air
+pub const BEDROCK_Y: i32 = -64;
+/// Layers above `BEDROCK_Y` that can still hold some bedrock
+pub const BEDROCK_LAYERS: i32 = 5;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("toml error")]
+    Toml(#[from] toml::de::Error),
+    #[error("missing block {0:?}")]
+    MissingBlock(String),
+    #[error("feature {0:?} is invalid")]
+    Feature(String),
+}
+
+struct Palette {
+    stone: BlockState,
+    dirt: BlockState,
+    grass: BlockState,
+    sand: BlockState,
+    snow: BlockState,
+    bedrock: BlockState,
+}
+
+/// Height, soil depth and biome of a column of blocks
+#[derive(Clone, Copy)]
+struct Column {
+    surface: i32,
+    soil: i32,
+    biome: Biome,
+}
+
+/// Generates chunks from a world seed
+///
+/// The same seed and position always produce the same chunk, on every
+/// platform and regardless of the order chunks are generated in.
+pub struct Generator {
+    seed: u64,
+    palette: Palette,
+    height: Octaves,
+    hills: Octaves,
+    soil: Octaves,
+    caves: Octaves,
+    temperature: Octaves,
+    humidity: Octaves,
+    /// Blocks of `ore::ORES`, in the same order
+    ores: Vec<BlockState>,
+    features: FeatureRegistry,
+}
+
+impl Generator {
+    /// Creates a generator placing the builtin features
+    pub fn new(seed: u64, registry: &BlockRegistry) -> Result<Self, Error> {
+        Self::with_features(seed, registry, FeatureRegistry::builtin(registry)?)
+    }
+
+    pub fn with_features(
+        seed: u64,
+        registry: &BlockRegistry,
+        features: FeatureRegistry,
+    ) -> Result<Self, Error> {
+        let block = |name: &str| {
+            registry
+                .default_state_by_name(name)
+                .ok_or_else(|| Error::MissingBlock(name.to_owned()))
+        };
+
+        Ok(Self {
+            seed,
+            palette: Palette {
+                stone: block("stone")?,
+                dirt: block("dirt")?,
+                grass: block("grass")?,
+                sand: block("sand")?,
+                snow: block("snow")?,
+                bedrock: block("bedrock")?,
+            },
+            height: Octaves::new(&mut rng(seed, &[1]), 6),
+            hills: Octaves::new(&mut rng(seed, &[2]), 4),
+            soil: Octaves::new(&mut rng(seed, &[3]), 3),
+            caves: Octaves::new(&mut rng(seed, &[4]), 3),
+            temperature: Octaves::new(&mut rng(seed, &[5]), 4),
+            humidity: Octaves::new(&mut rng(seed, &[6]), 4),
+            ores: ore::ORES
+                .iter()
+                .map(|ore| block(ore.block))
+                .collect::<Result<_, _>>()?,
+            features,
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Height of the topmost block of a column
+    pub fn surface(&self, x: i32, z: i32) -> i32 {
+        self.column(x, z).surface
+    }
+
+    pub fn biome(&self, x: i32, z: i32) -> Biome {
+        self.column(x, z).biome
+    }
+
+    pub fn generate(&self, pos: ChunkPos) -> Chunk {
+        let mut chunk = Chunk::new(pos);
+        let origin = pos.origin();
+
+        let columns: Vec<Column> = (0..CHUNK_LENGTH as i32)
+            .flat_map(|z| (0..CHUNK_WIDTH as i32).map(move |x| (x, z)))
+            .map(|(x, z)| self.column(origin.x + x, origin.z + z))
+            .collect();
+
+        for local in LocalPos::iter().filter(|local| local.y() == 0) {
+            chunk.set_biome(local, columns[local.column()].biome);
+        }
+
+        if origin.y + CHUNK_HEIGHT as i32 <= BEDROCK_Y || self.is_out_of_reach(pos, &columns) {
+            return chunk;
+        }
+
+        self.terrain(&mut chunk, &columns);
+        cave::carve(self, &mut chunk, &columns);
+        ore::place(self, &mut chunk);
+        decorate::decorate(self, &mut chunk);
+
+        chunk.compact();
+        chunk
+    }
+
+    /// Whether the chunk is too far above the ground for anything to reach it,
+    /// counting features rooted in the chunks around it, which can stand on
+    /// much higher ground
+    fn is_out_of_reach(&self, pos: ChunkPos, columns: &[Column]) -> bool {
+        let origin = pos.origin();
+        let below = |column: Column| column.surface + MAX_REACH < origin.y;
+
+        columns.iter().all(|column| below(*column))
+            && around(pos, 1)
+                .filter(|source| source.y == pos.y && *source != pos)
+                .all(|source| {
+                    let origin = source.origin();
+
+                    (0..CHUNK_LENGTH as i32).all(|z| {
+                        (0..CHUNK_WIDTH as i32)
+                            .all(|x| below(self.column(origin.x + x, origin.z + z)))
+                    })
+                })
+    }
+
+    fn terrain(&self, chunk: &mut Chunk, columns: &[Column]) {
+        let origin = chunk.pos.origin();
+        let above_bedrock = origin.y >= BEDROCK_Y + BEDROCK_LAYERS;
+        let below_soil = columns
+            .iter()
+            .all(|column| column.surface - column.soil >= origin.y + CHUNK_HEIGHT as i32 - 1);
+
+        if above_bedrock && below_soil {
+            chunk.fill(self.palette.stone);
+            return;
+        }
+
+        for local in LocalPos::iter() {
+            let state = self.block(chunk.pos.block(local), columns[local.column()]);
+
+            if state != BlockState::AIR {
+                chunk.set(local, state);
+            }
+        }
+    }
+
+    fn column(&self, x: i32, z: i32) -> Column {
+        let (x, z) = (x as f64, z as f64);
+
+        let height = self.height.sample2(x / 256.0, z / 256.0);
+        let hills = self.hills.sample2(x / 64.0, z / 64.0);
+        let soil = self.soil.sample2(x / 16.0, z / 16.0);
+        let temperature = self.temperature.sample2(x / 512.0, z / 512.0);
+        let humidity = self.humidity.sample2(x / 512.0, z / 512.0);
+
+        Column {
+            surface: (height * 64.0 + hills * (8.0 + height.max(0.0) * 48.0)).floor() as i32,
+            soil: (3 + (soil * 2.0).floor() as i32).max(1),
+            biome: Biome::from_climate(climate(temperature), climate(humidity)),
+        }
+    }
+
+    fn block(&self, pos: BlockPos, column: Column) -> BlockState {
+        if pos.y < BEDROCK_Y || pos.y > column.surface {
+            BlockState::AIR
+        } else if pos.y < BEDROCK_Y + BEDROCK_LAYERS && self.is_bedrock(pos) {
+            self.palette.bedrock
+        } else if pos.y == column.surface {
+            match column.biome {
+                Biome::Desert => self.palette.sand,
+                Biome::Tundra => self.palette.snow,
+                _ => self.palette.grass,
+            }
+        } else if pos.y > column.surface - column.soil {
+            match column.biome {
+                Biome::Desert => self.palette.sand,
+                _ => self.palette.dirt,
+            }
+        } else {
+            self.palette.stone
+        }
+    }
+
+    /// Bedrock thins out over `BEDROCK_LAYERS`, starting from a solid floor
+    fn is_bedrock(&self, pos: BlockPos) -> bool {
+        let layer = (pos.y - BEDROCK_Y) as u64;
+        let value = hash(self.seed, &[pos.x as i64, pos.y as i64, pos.z as i64]);
+
+        value % BEDROCK_LAYERS as u64 >= layer
+    }
+}
+
+/// Spreads noise out over `0.0..=1.0`
+fn climate(noise: f64) -> f64 {
+    (noise * 1.25 + 0.5).clamp(0.0, 1.0)
+}
+
+/// Chunks within `range` of `pos` on every axis
+fn around(pos: ChunkPos, range: i32) -> impl Iterator<Item = ChunkPos> {
+    (-range..=range).flat_map(move |y| {
+        (-range..=range).flat_map(move |z| (-range..=range).map(move |x| pos.offset(x, y, z)))
+    })
+}
+
+/// Mixes values into a seed, see SplitMix64
+fn hash(seed: u64, values: &[i64]) -> u64 {
+    let mut h = seed;
+
+    for value in values {
+        h = (h ^ *value as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        h ^= h >> 31;
+    }
+
+    h
+}
+
+/// Random generator for one use of the seed, independent from the others
+fn rng(seed: u64, values: &[i64]) -> FastPrng<u32> {
+    FastPrng {
+        state: hash(seed, values) as u32 | 1,
+    }
+}
+
+/// Random value in `0.0..=1.0`
+fn float(rng: &mut FastPrng<u32>) -> f64 {
+    rng.next() as f64 / u32::MAX as f64
+}
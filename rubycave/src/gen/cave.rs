@@ -0,0 +1,203 @@
+use glam::{DVec3, IVec3};
+
+use crate::{
+    math::FastPrng,
+    world::{BlockState, Chunk, ChunkPos, LocalPos, CHUNK_HEIGHT, CHUNK_LENGTH, CHUNK_WIDTH},
+    RangeIterator,
+};
+
+use super::{around, float, rng, Column, Generator};
+
+/// Chunks away from their starting chunk that tunnels can reach
+const RANGE: i32 = 4;
+/// Largest horizontal or vertical radius of a tunnel
+const MAX_RADIUS: f64 = 12.0;
+/// Longest tunnel, short enough to never leave `RANGE`
+const MAX_LENGTH: u32 = (RANGE as u32 * 16) - MAX_RADIUS as u32;
+
+/// Blocks of cave noise below the surface where the noise starts carving
+const NOISE_DEPTH: i32 = 8;
+const NOISE_THRESHOLD: f64 = 0.3;
+
+const WORM_SALT: i64 = 16;
+const RAVINE_SALT: i64 = 17;
+
+/// A tube carved by moving a sphere along a random path
+struct Tunnel {
+    pos: DVec3,
+    direction: DVec3,
+    radius: f64,
+    /// Vertical radius relative to the horizontal one
+    stretch: f64,
+    /// How much the path can bend up or down
+    pitch: f64,
+    length: u32,
+}
+
+/// Carves noise caves, worm caves and ravines out of the terrain of `chunk`
+///
+/// Worms and ravines are started by the chunks around `chunk` from their own
+/// seed, so each chunk carves its own part of a tunnel crossing its borders.
+pub(super) fn carve(generator: &Generator, chunk: &mut Chunk, columns: &[Column]) {
+    carve_noise(generator, chunk, columns);
+
+    for source in around(chunk.pos, RANGE) {
+        worms(generator, chunk, source);
+        ravines(generator, chunk, source);
+    }
+}
+
+fn carve_noise(generator: &Generator, chunk: &mut Chunk, columns: &[Column]) {
+    for local in LocalPos::iter() {
+        let pos = chunk.pos.block(local);
+
+        if pos.y > columns[local.column()].surface - NOISE_DEPTH {
+            continue;
+        }
+
+        let value = generator.caves.sample3(
+            pos.x as f64 / 48.0,
+            pos.y as f64 / 24.0,
+            pos.z as f64 / 48.0,
+        );
+
+        if value > NOISE_THRESHOLD {
+            clear(generator, chunk, local);
+        }
+    }
+}
+
+fn worms(generator: &Generator, chunk: &mut Chunk, source: ChunkPos) {
+    let mut rng = rng(
+        generator.seed,
+        &[WORM_SALT, source.x as i64, source.y as i64, source.z as i64],
+    );
+
+    // Most chunks don't start any
+    if rng.next_in(0..6u32) != 0 {
+        return;
+    }
+
+    for _ in 0..rng.next_in(1..=3u32) {
+        let tunnel = Tunnel {
+            pos: start(&mut rng, source),
+            direction: direction(&mut rng, 0.5),
+            radius: 1.5 + float(&mut rng) * 2.5,
+            stretch: 0.8 + float(&mut rng) * 0.4,
+            pitch: 0.5,
+            length: rng.next_in(MAX_LENGTH / 2..=MAX_LENGTH),
+        };
+
+        dig(generator, chunk, tunnel, &mut rng);
+    }
+}
+
+fn ravines(generator: &Generator, chunk: &mut Chunk, source: ChunkPos) {
+    let mut rng = rng(
+        generator.seed,
+        &[
+            RAVINE_SALT,
+            source.x as i64,
+            source.y as i64,
+            source.z as i64,
+        ],
+    );
+
+    if rng.next_in(0..200u32) != 0 {
+        return;
+    }
+
+    let tunnel = Tunnel {
+        pos: start(&mut rng, source),
+        direction: direction(&mut rng, 0.1),
+        radius: 1.5 + float(&mut rng) * 1.5,
+        stretch: 3.0 + float(&mut rng),
+        pitch: 0.05,
+        length: MAX_LENGTH,
+    };
+
+    dig(generator, chunk, tunnel, &mut rng);
+}
+
+fn start(rng: &mut FastPrng<u32>, source: ChunkPos) -> DVec3 {
+    let origin = source.origin();
+
+    DVec3::new(
+        origin.x as f64 + float(rng) * CHUNK_WIDTH as f64,
+        origin.y as f64 + float(rng) * CHUNK_HEIGHT as f64,
+        origin.z as f64 + float(rng) * CHUNK_LENGTH as f64,
+    )
+}
+
+/// Random unit vector, flattened vertically by `pitch`
+///
+/// Avoids trigonometry, which isn't guaranteed to give the same results on
+/// every platform.
+fn direction(rng: &mut FastPrng<u32>, pitch: f64) -> DVec3 {
+    let v = DVec3::new(
+        float(rng) * 2.0 - 1.0,
+        (float(rng) * 2.0 - 1.0) * pitch,
+        float(rng) * 2.0 - 1.0,
+    );
+
+    v.try_normalize().unwrap_or(DVec3::X)
+}
+
+fn dig(generator: &Generator, chunk: &mut Chunk, mut tunnel: Tunnel, rng: &mut FastPrng<u32>) {
+    let mut turn = DVec3::ZERO;
+
+    for step in 0..tunnel.length {
+        // Thickest in the middle, narrowing towards both ends
+        let t = step as f64 / tunnel.length as f64 * 2.0 - 1.0;
+        let radius = tunnel.radius * (0.5 + (1.0 - t * t) * 0.5);
+
+        sphere(
+            generator,
+            chunk,
+            tunnel.pos,
+            radius,
+            (radius * tunnel.stretch).min(MAX_RADIUS),
+        );
+
+        turn = turn * 0.75 + direction(rng, tunnel.pitch) * 0.25;
+        tunnel.direction = (tunnel.direction + turn * 0.3)
+            .try_normalize()
+            .unwrap_or(tunnel.direction);
+        tunnel.pos += tunnel.direction;
+    }
+}
+
+/// Clears an ellipsoid centered on `center`
+fn sphere(generator: &Generator, chunk: &mut Chunk, center: DVec3, radius: f64, height: f64) {
+    let origin = IVec3::from(chunk.pos.origin()).as_dvec3();
+    let size = DVec3::new(radius, height, radius);
+    let end = IVec3::new(CHUNK_WIDTH as i32, CHUNK_HEIGHT as i32, CHUNK_LENGTH as i32);
+    let min = (center - size - origin)
+        .floor()
+        .as_ivec3()
+        .clamp(IVec3::ZERO, end);
+    let max = (center + size - origin)
+        .ceil()
+        .as_ivec3()
+        .clamp(IVec3::ZERO, end);
+
+    for y in min.y..max.y {
+        for z in min.z..max.z {
+            for x in min.x..max.x {
+                let block = origin + DVec3::new(x as f64, y as f64, z as f64) + 0.5;
+
+                if ((block - center) / size).length_squared() < 1.0 {
+                    clear(generator, chunk, LocalPos::new(x, y, z));
+                }
+            }
+        }
+    }
+}
+
+fn clear(generator: &Generator, chunk: &mut Chunk, local: LocalPos) {
+    let state = chunk.get(local);
+
+    if state != BlockState::AIR && state != generator.palette.bedrock {
+        chunk.set(local, BlockState::AIR);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    math::FastPrng,
+    world::{Biome, BlockPos, Chunk, CHUNK_HEIGHT, CHUNK_LENGTH, CHUNK_WIDTH},
+    RangeIterator,
+};
+
+use super::{
+    around,
+    feature::{Feature, Placement},
+    float, rng, Generator,
+};
+
+const FEATURE_SALT: i64 = 64;
+
+/// Places the features started by `chunk` and the chunks around it, keeping
+/// the blocks that land inside `chunk`
+///
+/// Every chunk rolls its features from its own seed, so a feature crossing a
+/// border is built the same way by each chunk it touches.
+pub(super) fn decorate(generator: &Generator, chunk: &mut Chunk) {
+    let max_tree_density = Biome::ALL
+        .iter()
+        .map(|biome| biome.tree_density())
+        .fold(0.0, f32::max);
+
+    for source in around(chunk.pos, 1) {
+        let origin = source.origin();
+
+        for (i, feature) in generator.features.iter().enumerate() {
+            let mut rng = rng(
+                generator.seed,
+                &[
+                    FEATURE_SALT + i as i64,
+                    source.x as i64,
+                    source.y as i64,
+                    source.z as i64,
+                ],
+            );
+
+            match &feature.placement {
+                Placement::Surface {
+                    chance,
+                    trees,
+                    biomes,
+                } => {
+                    let max = if *trees { max_tree_density } else { *chance };
+
+                    if max <= 0.0 {
+                        continue;
+                    }
+
+                    for z in 0..CHUNK_LENGTH as i32 {
+                        for x in 0..CHUNK_WIDTH as i32 {
+                            let roll = float(&mut rng) as f32;
+
+                            // Cheap early out before computing the column
+                            if roll >= max {
+                                continue;
+                            }
+
+                            let column = generator.column(origin.x + x, origin.z + z);
+                            let pos = BlockPos::new(origin.x + x, column.surface + 1, origin.z + z);
+
+                            if pos.chunk() != source
+                                || (!biomes.is_empty() && !biomes.contains(&column.biome))
+                            {
+                                continue;
+                            }
+
+                            let chance = if *trees {
+                                column.biome.tree_density()
+                            } else {
+                                *chance
+                            };
+
+                            if roll < chance {
+                                place(generator, chunk, feature, pos, &mut rng);
+                            }
+                        }
+                    }
+                }
+                Placement::Underground {
+                    chance,
+                    min_y,
+                    max_y,
+                } => {
+                    if float(&mut rng) as f32 >= *chance {
+                        continue;
+                    }
+
+                    let pos = origin.offset(
+                        rng.next_in(0..CHUNK_WIDTH as u32) as i32,
+                        rng.next_in(0..CHUNK_HEIGHT as u32) as i32,
+                        rng.next_in(0..CHUNK_LENGTH as u32) as i32,
+                    );
+
+                    if (*min_y..=*max_y).contains(&pos.y) {
+                        place(generator, chunk, feature, pos, &mut rng);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn place(
+    generator: &Generator,
+    chunk: &mut Chunk,
+    feature: &Feature,
+    origin: BlockPos,
+    rng: &mut FastPrng<u32>,
+) {
+    feature.place(
+        generator.seed,
+        origin,
+        rng,
+        |x, z| generator.surface(x, z),
+        |pos, state, replace| {
+            let (chunk_pos, local) = pos.split();
+
+            if chunk_pos != chunk.pos {
+                return;
+            }
+
+            let current = chunk.get(local);
+
+            if current != generator.palette.bedrock && replace.allows(current) {
+                chunk.set(local, state);
+            }
+        },
+    );
+}
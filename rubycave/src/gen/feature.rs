@@ -0,0 +1,383 @@
+use glam::IVec3;
+
+use crate::{
+    math::FastPrng,
+    world::{Biome, BlockPos, BlockRegistry, BlockState},
+    RangeIterator,
+};
+
+use super::{hash, Error};
+
+const BUILTIN_FEATURES: &str = include_str!("../../res/feature.toml");
+
+/// Furthest a feature can reach from its origin on any axis
+pub const MAX_REACH: i32 = 16;
+
+pub enum Placement {
+    /// Tried on every column, on the block above the ground
+    Surface {
+        chance: f32,
+        /// Uses the biome's tree density as the chance
+        trees: bool,
+        /// Biomes the feature can appear in, any if empty
+        biomes: Vec<Biome>,
+    },
+    /// Tried once per chunk
+    Underground { chance: f32, min_y: i32, max_y: i32 },
+}
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    #[default]
+    Origin,
+    Top,
+}
+
+/// What a part is allowed to overwrite
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Replace {
+    #[default]
+    Air,
+    Solid,
+    Any,
+}
+
+impl Replace {
+    pub fn allows(self, current: BlockState) -> bool {
+        match self {
+            Replace::Air => current == BlockState::AIR,
+            Replace::Solid => current != BlockState::AIR,
+            Replace::Any => true,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Shape {
+    Column {
+        height: [u32; 2],
+    },
+    Sphere {
+        radius: [u32; 2],
+    },
+    Box {
+        size: [i32; 3],
+        #[serde(default)]
+        hollow: bool,
+    },
+    Scatter {
+        count: [u32; 2],
+        spread: i32,
+    },
+}
+
+pub struct Part {
+    pub shape: Shape,
+    pub blocks: Vec<BlockState>,
+    pub anchor: Anchor,
+    pub offset: IVec3,
+    pub replace: Replace,
+}
+
+pub struct Feature {
+    pub name: String,
+    pub placement: Placement,
+    pub parts: Vec<Part>,
+}
+
+/// Feature definitions, in file order
+pub struct FeatureRegistry {
+    features: Vec<Feature>,
+}
+
+#[derive(serde::Deserialize)]
+struct FeatureFile {
+    feature: Vec<FeatureEntry>,
+}
+
+#[derive(serde::Deserialize)]
+struct FeatureEntry {
+    name: String,
+    placement: PlacementEntry,
+    parts: Vec<PartEntry>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum PlacementEntry {
+    Surface {
+        #[serde(default)]
+        chance: f32,
+        #[serde(default)]
+        trees: bool,
+        #[serde(default)]
+        biomes: Vec<Biome>,
+    },
+    Underground {
+        chance: f32,
+        min_y: i32,
+        max_y: i32,
+    },
+}
+
+#[derive(serde::Deserialize)]
+struct PartEntry {
+    #[serde(flatten)]
+    shape: Shape,
+    block: BlockEntry,
+    #[serde(default)]
+    anchor: Anchor,
+    #[serde(default)]
+    offset: [i32; 3],
+    #[serde(default)]
+    replace: Replace,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum BlockEntry {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl FeatureRegistry {
+    /// Loads the feature definitions bundled with the game
+    pub fn builtin(registry: &BlockRegistry) -> Result<Self, Error> {
+        Self::load(BUILTIN_FEATURES, registry)
+    }
+
+    pub fn load(source: &str, registry: &BlockRegistry) -> Result<Self, Error> {
+        let file: FeatureFile = toml::from_str(source)?;
+        let mut features: Vec<Feature> = Vec::new();
+
+        for entry in file.feature {
+            if features.iter().any(|feature| feature.name == entry.name) {
+                return Err(Error::Feature(entry.name));
+            }
+
+            let feature = entry.resolve(registry)?;
+
+            if feature.reach() > MAX_REACH {
+                return Err(Error::Feature(feature.name));
+            }
+
+            features.push(feature);
+        }
+
+        Ok(Self { features })
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&Feature> {
+        self.features.iter().find(|feature| feature.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Feature> {
+        self.features.iter()
+    }
+}
+
+impl FeatureEntry {
+    fn resolve(self, registry: &BlockRegistry) -> Result<Feature, Error> {
+        let invalid = || Error::Feature(self.name.clone());
+
+        let placement = match self.placement {
+            PlacementEntry::Surface {
+                chance,
+                trees,
+                biomes,
+            } => Placement::Surface {
+                chance,
+                trees,
+                biomes,
+            },
+            PlacementEntry::Underground {
+                chance,
+                min_y,
+                max_y,
+            } => {
+                if min_y > max_y {
+                    return Err(invalid());
+                }
+
+                Placement::Underground {
+                    chance,
+                    min_y,
+                    max_y,
+                }
+            }
+        };
+
+        let mut parts = Vec::new();
+
+        for part in &self.parts {
+            let names = match &part.block {
+                BlockEntry::One(name) => std::slice::from_ref(name),
+                BlockEntry::Many(names) => names.as_slice(),
+            };
+
+            let blocks = names
+                .iter()
+                .map(|name| {
+                    registry
+                        .default_state_by_name(name)
+                        .ok_or_else(|| Error::MissingBlock(name.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let valid = !blocks.is_empty()
+                && match part.shape {
+                    Shape::Column { height: [min, max] } | Shape::Sphere { radius: [min, max] } => {
+                        min <= max
+                    }
+                    Shape::Scatter {
+                        count: [min, max],
+                        spread,
+                    } => min <= max && spread >= 0,
+                    Shape::Box { size, .. } => size.iter().all(|n| *n > 0),
+                };
+
+            if !valid {
+                return Err(invalid());
+            }
+
+            parts.push(Part {
+                shape: part.shape,
+                blocks,
+                anchor: part.anchor,
+                offset: IVec3::from(part.offset),
+                replace: part.replace,
+            });
+        }
+
+        Ok(Feature {
+            name: self.name,
+            placement,
+            parts,
+        })
+    }
+}
+
+impl Feature {
+    /// Furthest any block of the feature can be from its origin
+    pub fn reach(&self) -> i32 {
+        let mut top = 0;
+        let mut reach = 0;
+
+        for part in &self.parts {
+            let base = part.offset.abs().max_element()
+                + match part.anchor {
+                    Anchor::Origin => 0,
+                    Anchor::Top => top,
+                };
+
+            let extent = match part.shape {
+                Shape::Column { height: [_, max] } => {
+                    top = base + max as i32;
+                    max as i32
+                }
+                Shape::Sphere { radius: [_, max] } => max as i32,
+                Shape::Box { size, .. } => size.into_iter().max().unwrap_or_default(),
+                Shape::Scatter { spread, .. } => spread,
+            };
+
+            reach = reach.max(base + extent);
+        }
+
+        reach
+    }
+
+    /// Builds the feature at `origin`, passing every block to `put`
+    ///
+    /// Random choices are drawn from `rng` in a fixed order, and blocks picked
+    /// from a list only depend on `seed` and their position, so the same
+    /// arguments always place the same blocks. `surface` gives the height of
+    /// the ground of a column for scattered parts.
+    pub fn place(
+        &self,
+        seed: u64,
+        origin: BlockPos,
+        rng: &mut FastPrng<u32>,
+        surface: impl Fn(i32, i32) -> i32,
+        mut put: impl FnMut(BlockPos, BlockState, Replace),
+    ) {
+        let mut top = origin;
+
+        for part in &self.parts {
+            let base = match part.anchor {
+                Anchor::Origin => origin,
+                Anchor::Top => top,
+            };
+            let base = base.offset(part.offset.x, part.offset.y, part.offset.z);
+
+            let mut put = |pos: BlockPos| {
+                let block = if part.blocks.len() > 1 {
+                    let h = hash(seed, &[pos.x as i64, pos.y as i64, pos.z as i64]);
+                    part.blocks[(h % part.blocks.len() as u64) as usize]
+                } else {
+                    part.blocks[0]
+                };
+
+                put(pos, block, part.replace);
+            };
+
+            match part.shape {
+                Shape::Column { height: [min, max] } => {
+                    let height = rng.next_in(min..=max) as i32;
+
+                    for y in 0..height {
+                        put(base.offset(0, y, 0));
+                    }
+
+                    top = base.offset(0, height, 0);
+                }
+                Shape::Sphere { radius: [min, max] } => {
+                    let r = rng.next_in(min..=max) as i32;
+
+                    for y in -r..=r {
+                        for z in -r..=r {
+                            for x in -r..=r {
+                                // Slightly rounder than a true sphere
+                                if x * x + y * y + z * z <= r * r + r {
+                                    put(base.offset(x, y, z));
+                                }
+                            }
+                        }
+                    }
+                }
+                Shape::Box { size, hollow } => {
+                    for y in 0..size[1] {
+                        for z in 0..size[2] {
+                            for x in 0..size[0] {
+                                let edge = [(x, 0), (y, 1), (z, 2)]
+                                    .into_iter()
+                                    .any(|(n, i)| n == 0 || n == size[i] - 1);
+
+                                if !hollow || edge {
+                                    put(base.offset(x, y, z));
+                                }
+                            }
+                        }
+                    }
+                }
+                Shape::Scatter {
+                    count: [min, max],
+                    spread,
+                } => {
+                    for _ in 0..rng.next_in(min..=max) {
+                        let range = 0..=spread.unsigned_abs() * 2;
+                        let x = base.x + rng.next_in(range.clone()) as i32 - spread;
+                        let z = base.z + rng.next_in(range) as i32 - spread;
+                        let y = surface(x, z) + 1;
+
+                        if (y - base.y).abs() <= spread {
+                            put(BlockPos::new(x, y, z));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
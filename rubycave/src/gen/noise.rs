@@ -0,0 +1,143 @@
+use crate::{math::FastPrng, InfiniteIterator, RangeIterator};
+
+/// Improved Perlin noise with a permutation table shuffled from a seed
+///
+/// Only uses basic float arithmetic, so samples are the same on every
+/// platform.
+pub struct Perlin {
+    perm: [u8; 512],
+    offset: [f64; 3],
+}
+
+/// Several layers of Perlin noise, each at twice the frequency and half the
+/// amplitude of the previous one
+pub struct Octaves {
+    layers: Vec<Perlin>,
+}
+
+impl Perlin {
+    pub fn new(rng: &mut FastPrng<u32>) -> Self {
+        let mut perm = [0; 512];
+
+        for (i, p) in perm.iter_mut().take(256).enumerate() {
+            *p = i as u8;
+        }
+
+        for i in (1..256).rev() {
+            let j = rng.next_in(0..=i as u32) as usize;
+            perm.swap(i, j);
+        }
+
+        perm.copy_within(0..256, 256);
+
+        // Keeps integer coordinates from landing on the lattice, where the
+        // noise is always zero
+        let offset = [(); 3].map(|_| rng.next() as f64 / u32::MAX as f64 * 256.0);
+
+        Self { perm, offset }
+    }
+
+    pub fn sample2(&self, x: f64, z: f64) -> f64 {
+        self.sample3(x, 0.0, z)
+    }
+
+    /// Returns a value roughly in `-1.0..=1.0`
+    pub fn sample3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (x, y, z) = (x + self.offset[0], y + self.offset[1], z + self.offset[2]);
+        let (fx, fy, fz) = (x.floor(), y.floor(), z.floor());
+        let (xi, yi, zi) = (lattice(fx), lattice(fy), lattice(fz));
+        let (x, y, z) = (x - fx, y - fy, z - fz);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let p = &self.perm;
+        let a = p[xi] as usize + yi;
+        let aa = p[a] as usize + zi;
+        let ab = p[a + 1] as usize + zi;
+        let b = p[xi + 1] as usize + yi;
+        let ba = p[b] as usize + zi;
+        let bb = p[b + 1] as usize + zi;
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad(p[ab], x, y - 1.0, z),
+                    grad(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.0),
+                    grad(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+}
+
+impl Octaves {
+    pub fn new(rng: &mut FastPrng<u32>, count: usize) -> Self {
+        Self {
+            layers: (0..count).map(|_| Perlin::new(rng)).collect(),
+        }
+    }
+
+    pub fn sample2(&self, x: f64, z: f64) -> f64 {
+        self.sample3(x, 0.0, z)
+    }
+
+    /// Returns a value roughly in `-1.0..=1.0`
+    pub fn sample3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut max = 0.0;
+
+        for layer in &self.layers {
+            total += layer.sample3(x * frequency, y * frequency, z * frequency) * amplitude;
+            max += amplitude;
+            amplitude /= 2.0;
+            frequency *= 2.0;
+        }
+
+        if max > 0.0 {
+            total / max
+        } else {
+            0.0
+        }
+    }
+}
+
+fn lattice(x: f64) -> usize {
+    (x as i64 & 255) as usize
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -0,0 +1,102 @@
+use crate::{
+    world::{BlockPos, Chunk, Face, CHUNK_HEIGHT, CHUNK_LENGTH, CHUNK_WIDTH},
+    RangeIterator,
+};
+
+use super::{around, rng, Generator};
+
+/// A kind of ore vein, placed `count` times per chunk with its start between
+/// `min_y` and `max_y`
+pub(super) struct Ore {
+    pub block: &'static str,
+    pub min_y: i32,
+    pub max_y: i32,
+    /// Most blocks in a vein
+    pub size: u32,
+    pub count: u32,
+}
+
+pub(super) const ORES: [Ore; 4] = [
+    Ore {
+        block: "coal_ore",
+        min_y: -64,
+        max_y: 16,
+        size: 16,
+        count: 4,
+    },
+    Ore {
+        block: "iron_ore",
+        min_y: -64,
+        max_y: -8,
+        size: 8,
+        count: 3,
+    },
+    Ore {
+        block: "gold_ore",
+        min_y: -64,
+        max_y: -32,
+        size: 8,
+        count: 1,
+    },
+    Ore {
+        block: "diamond_ore",
+        min_y: -64,
+        max_y: -48,
+        size: 6,
+        count: 1,
+    },
+];
+
+const ORE_SALT: i64 = 32;
+
+/// Replaces stone in `chunk` with ore veins
+///
+/// Veins are walks of single blocks, started by each chunk around `chunk` from
+/// its own seed so veins crossing a border are cut the same way on both sides.
+pub(super) fn place(generator: &Generator, chunk: &mut Chunk) {
+    for source in around(chunk.pos, 1) {
+        let origin = source.origin();
+
+        for (i, ore) in ORES.iter().enumerate() {
+            if origin.y > ore.max_y || origin.y + (CHUNK_HEIGHT as i32) <= ore.min_y {
+                continue;
+            }
+
+            let mut rng = rng(
+                generator.seed,
+                &[
+                    ORE_SALT + i as i64,
+                    source.x as i64,
+                    source.y as i64,
+                    source.z as i64,
+                ],
+            );
+
+            for _ in 0..ore.count {
+                let mut pos = origin.offset(
+                    rng.next_in(0..CHUNK_WIDTH as u32) as i32,
+                    rng.next_in(0..CHUNK_HEIGHT as u32) as i32,
+                    rng.next_in(0..CHUNK_LENGTH as u32) as i32,
+                );
+                let size = rng.next_in(ore.size / 2..=ore.size);
+                let inside = (ore.min_y..=ore.max_y).contains(&pos.y);
+
+                for _ in 0..size {
+                    if inside {
+                        replace(generator, chunk, pos, i);
+                    }
+
+                    pos = pos.neighbor(Face::ALL[rng.next_in(0..6u32) as usize]);
+                }
+            }
+        }
+    }
+}
+
+fn replace(generator: &Generator, chunk: &mut Chunk, pos: BlockPos, ore: usize) {
+    let (chunk_pos, local) = pos.split();
+
+    if chunk_pos == chunk.pos && chunk.get(local) == generator.palette.stone {
+        chunk.set(local, generator.ores[ore]);
+    }
+}
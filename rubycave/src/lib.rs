@@ -8,12 +8,20 @@ pub use regex;
 pub use rkyv_codec;
 pub use tokio_util;
 
+pub mod entity;
+pub mod gen;
+pub mod math;
+pub mod physics;
 pub mod protocol;
 pub mod world;
 
 pub const TICK_RATE: u32 = 60;
+/// Milliseconds between keep-alives sent by the server
 pub const KEEP_ALIVE_INTERVAL: u32 = 5000;
 
+/// How far a player can reach blocks from its eyes
+pub const REACH: f32 = 5.0;
+
 pub trait InfiniteIterator {
     type Item;
 
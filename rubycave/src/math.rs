@@ -1,6 +1,6 @@
 use std::ops::{Bound, RangeBounds};
 
-use rubycave::{InfiniteIterator, RangeIterator};
+use crate::{InfiniteIterator, RangeIterator};
 
 macro_rules! prng_range_impl {
     ($x:ty) => {
@@ -0,0 +1,211 @@
+use glam::Vec3;
+
+use crate::world::{BlockPos, BlockRegistry, World};
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+/// Result of moving a box through the world
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Movement {
+    /// Motion that was actually applied
+    pub motion: Vec3,
+    pub on_ground: bool,
+    pub collided_x: bool,
+    pub collided_y: bool,
+    pub collided_z: bool,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Creates a box standing on `feet`, centered horizontally
+    pub fn from_feet(feet: Vec3, width: f32, height: f32) -> Self {
+        let half = width / 2.0;
+
+        Self {
+            min: feet - Vec3::new(half, 0.0, half),
+            max: feet + Vec3::new(half, height, half),
+        }
+    }
+
+    pub fn offset(self, v: Vec3) -> Self {
+        Self {
+            min: self.min + v,
+            max: self.max + v,
+        }
+    }
+
+    /// Grows the box in the direction of `v`
+    pub fn expand_towards(self, v: Vec3) -> Self {
+        Self {
+            min: self.min + v.min(Vec3::ZERO),
+            max: self.max + v.max(Vec3::ZERO),
+        }
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x < other.max.x
+            && self.max.x > other.min.x
+            && self.min.y < other.max.y
+            && self.max.y > other.min.y
+            && self.min.z < other.max.z
+            && self.max.z > other.min.z
+    }
+
+    /// Limits `dx` so that `other` moving along X doesn't enter this box
+    pub fn clip_x(&self, other: &Aabb, mut dx: f32) -> f32 {
+        if other.max.y <= self.min.y
+            || other.min.y >= self.max.y
+            || other.max.z <= self.min.z
+            || other.min.z >= self.max.z
+        {
+            return dx;
+        }
+
+        if dx > 0.0 && other.max.x <= self.min.x {
+            dx = dx.min(self.min.x - other.max.x);
+        } else if dx < 0.0 && other.min.x >= self.max.x {
+            dx = dx.max(self.max.x - other.min.x);
+        }
+
+        dx
+    }
+
+    /// Limits `dy` so that `other` moving along Y doesn't enter this box
+    pub fn clip_y(&self, other: &Aabb, mut dy: f32) -> f32 {
+        if other.max.x <= self.min.x
+            || other.min.x >= self.max.x
+            || other.max.z <= self.min.z
+            || other.min.z >= self.max.z
+        {
+            return dy;
+        }
+
+        if dy > 0.0 && other.max.y <= self.min.y {
+            dy = dy.min(self.min.y - other.max.y);
+        } else if dy < 0.0 && other.min.y >= self.max.y {
+            dy = dy.max(self.max.y - other.min.y);
+        }
+
+        dy
+    }
+
+    /// Limits `dz` so that `other` moving along Z doesn't enter this box
+    pub fn clip_z(&self, other: &Aabb, mut dz: f32) -> f32 {
+        if other.max.x <= self.min.x
+            || other.min.x >= self.max.x
+            || other.max.y <= self.min.y
+            || other.min.y >= self.max.y
+        {
+            return dz;
+        }
+
+        if dz > 0.0 && other.max.z <= self.min.z {
+            dz = dz.min(self.min.z - other.max.z);
+        } else if dz < 0.0 && other.min.z >= self.max.z {
+            dz = dz.max(self.max.z - other.min.z);
+        }
+
+        dz
+    }
+}
+
+/// Boxes of the solid blocks overlapping `area`, in a fixed order
+///
+/// Blocks in unloaded chunks are treated as empty.
+pub fn block_boxes(world: &World, registry: &BlockRegistry, area: Aabb) -> Vec<Aabb> {
+    let min = area.min.floor().as_ivec3();
+    let max = area.max.ceil().as_ivec3();
+    let mut boxes = Vec::new();
+
+    for y in min.y..max.y {
+        for z in min.z..max.z {
+            for x in min.x..max.x {
+                let pos = BlockPos::new(x, y, z);
+
+                if world
+                    .get_block(pos)
+                    .is_some_and(|state| registry.is_solid(state.id))
+                {
+                    let min = Vec3::new(x as f32, y as f32, z as f32);
+                    boxes.push(Aabb::new(min, min + Vec3::ONE));
+                }
+            }
+        }
+    }
+
+    boxes
+}
+
+fn collide(boxes: &[Aabb], mut aabb: Aabb, motion: Vec3) -> Vec3 {
+    let mut moved = motion;
+
+    for b in boxes {
+        moved.y = b.clip_y(&aabb, moved.y);
+    }
+    aabb = aabb.offset(Vec3::new(0.0, moved.y, 0.0));
+
+    for b in boxes {
+        moved.x = b.clip_x(&aabb, moved.x);
+    }
+    aabb = aabb.offset(Vec3::new(moved.x, 0.0, 0.0));
+
+    for b in boxes {
+        moved.z = b.clip_z(&aabb, moved.z);
+    }
+
+    moved
+}
+
+/// Moves `aabb` by `motion` one axis at a time (Y, X then Z), stopping at solid
+/// blocks. A box on the ground that is blocked horizontally tries to climb up
+/// to `step_height`.
+///
+/// This is deterministic, the server replays it to validate client movement.
+pub fn move_aabb(
+    world: &World,
+    registry: &BlockRegistry,
+    aabb: Aabb,
+    motion: Vec3,
+    on_ground: bool,
+    step_height: f32,
+) -> Movement {
+    let boxes = block_boxes(world, registry, aabb.expand_towards(motion));
+    let mut moved = collide(&boxes, aabb, motion);
+    let mut landed = motion.y < 0.0 && moved.y != motion.y;
+
+    let blocked_side = moved.x != motion.x || moved.z != motion.z;
+
+    if step_height > 0.0 && blocked_side && (on_ground || landed) {
+        let up = Vec3::new(motion.x, step_height, motion.z);
+        let area = aabb
+            .expand_towards(up)
+            .expand_towards(Vec3::new(0.0, motion.y.min(0.0), 0.0));
+        let boxes = block_boxes(world, registry, area);
+
+        let mut stepped = collide(&boxes, aabb, up);
+        let fall = motion.y.min(0.0) - stepped.y;
+        let down = collide(&boxes, aabb.offset(stepped), Vec3::new(0.0, fall, 0.0));
+
+        stepped.y += down.y;
+
+        if stepped.x * stepped.x + stepped.z * stepped.z > moved.x * moved.x + moved.z * moved.z {
+            moved = stepped;
+            landed = down.y != fall;
+        }
+    }
+
+    Movement {
+        motion: moved,
+        on_ground: landed,
+        collided_x: moved.x != motion.x,
+        collided_y: moved.y != motion.y,
+        collided_z: moved.z != motion.z,
+    }
+}
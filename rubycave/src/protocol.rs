@@ -1,9 +1,89 @@
+use std::collections::BTreeSet;
+
 use regex::Regex;
 use rkyv::{Archive, Deserialize, Serialize};
 
+use crate::world::CHUNK_VOLUME;
+
+use compress::Compression;
+
+pub mod chat;
 pub mod client;
+pub mod codec;
+pub mod compress;
+pub mod delta;
 pub mod server;
 
+/// Version of the protocol this build speaks, bumped on every breaking change
+pub const PROTOCOL_VERSION: u32 = 5;
+
+/// Oldest version this build can still talk to
+pub const MIN_PROTOCOL_VERSION: u32 = 5;
+
+/// Optional features this build supports, only used when both sides do
+pub const FEATURES: &[&str] = &[feature::CHAT];
+
+/// Most features a peer may list, and how long each may be
+const MAX_FEATURES: usize = 16;
+const MAX_FEATURE_LENGTH: usize = 32;
+
+pub mod feature {
+    /// Chat messages from the server
+    pub const CHAT: &str = "chat";
+}
+
+/// What both sides agreed on in the handshake
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Negotiated {
+    /// Highest version both sides speak
+    pub version: u32,
+    pub features: BTreeSet<String>,
+    /// How the server compresses chunks, if at all
+    pub compression: Option<Compression>,
+}
+
+impl Negotiated {
+    /// Agrees with a peer speaking up to `version` with `features`, assuming
+    /// the versions are compatible
+    pub fn new(version: u32, features: &[String]) -> Self {
+        Self {
+            version: version.min(PROTOCOL_VERSION),
+            features: features
+                .iter()
+                .filter(|feature| FEATURES.contains(&feature.as_str()))
+                .cloned()
+                .collect(),
+            compression: None,
+        }
+    }
+
+    pub fn with_compression(mut self, compression: Option<Compression>) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn has(&self, feature: &str) -> bool {
+        self.features.contains(feature)
+    }
+}
+
+/// Whether a peer may list `features`, whether or not they're known
+fn are_valid_features(features: &[String]) -> bool {
+    features.len() <= MAX_FEATURES
+        && features
+            .iter()
+            .all(|feature| feature.len() <= MAX_FEATURE_LENGTH)
+}
+
+fn is_finite(values: &[f32]) -> bool {
+    values.iter().all(|value| value.is_finite())
+}
+
+/// Whether a peer speaking `min_version` to `version` shares one with this build
+fn is_compatible(min_version: u32, version: u32) -> bool {
+    version >= MIN_PROTOCOL_VERSION && PROTOCOL_VERSION >= min_version
+}
+
 #[derive(Archive, Deserialize, Serialize, Debug)]
 #[archive(check_bytes)]
 #[archive_attr(derive(Debug))]
@@ -13,14 +93,12 @@ pub enum Packet {
 }
 
 pub struct PacketValidator {
-    crate_version: String,
     username_regex: Regex,
 }
 
 impl PacketValidator {
-    pub fn new(crate_version: &str) -> Result<Self, regex::Error> {
+    pub fn new() -> Result<Self, regex::Error> {
         Ok(Self {
-            crate_version: crate_version.to_owned(),
             username_regex: Regex::new(r"[^a-zA-Z0-9_]")?,
         })
     }
@@ -31,28 +109,151 @@ impl PacketValidator {
 
     pub fn check_client(&self, packet: &client::Packet) -> Result<(), server::PacketError> {
         match packet {
-            client::Packet::Handshake { version, username } => {
-                (self.crate_version == *version)
-                    .then_some(())
-                    .ok_or(server::PacketError::Version)?;
+            client::Packet::Handshake {
+                version,
+                min_version,
+                features,
+                username,
+                ..
+            } => {
+                is_compatible(*min_version, *version).then_some(()).ok_or(
+                    server::PacketError::Version {
+                        min: MIN_PROTOCOL_VERSION,
+                        max: PROTOCOL_VERSION,
+                    },
+                )?;
                 self.check_username(username)
                     .then_some(())
                     .ok_or(server::PacketError::Username)?;
+                are_valid_features(features)
+                    .then_some(())
+                    .ok_or(server::PacketError::Features)?;
                 Ok(())
             }
-            _ => Ok(()),
+            client::Packet::Move {
+                x,
+                y,
+                z,
+                yaw,
+                pitch,
+                ..
+            } => is_finite(&[*x, *y, *z, *yaw, *pitch])
+                .then_some(())
+                .ok_or(server::PacketError::NonFinite),
+            client::Packet::Chat { message } => chat::is_valid_message(message)
+                .then_some(())
+                .ok_or(server::PacketError::Chat),
+            client::Packet::Dig { pos, .. } | client::Packet::Place { pos, .. } => pos
+                .is_in_bounds()
+                .then_some(())
+                .ok_or(server::PacketError::Position),
+            client::Packet::Disconnect { .. } | client::Packet::KeepAlive { .. } => Ok(()),
         }
     }
 
     pub fn check_server(&self, packet: &server::Packet) -> Result<(), client::PacketError> {
         match packet {
-            server::Packet::Handshake { version } => {
-                (self.crate_version == *version)
+            server::Packet::Handshake {
+                version,
+                min_version,
+                features,
+                compression,
+            } => {
+                is_compatible(*min_version, *version).then_some(()).ok_or(
+                    client::PacketError::Version {
+                        min: MIN_PROTOCOL_VERSION,
+                        max: PROTOCOL_VERSION,
+                    },
+                )?;
+                are_valid_features(features)
+                    .then_some(())
+                    .ok_or(client::PacketError::Features)?;
+                (compression.len() <= compress::MAX_OFFERS
+                    && compression.iter().all(|offer| offer.is_valid()))
+                .then_some(())
+                .ok_or(client::PacketError::Compression)?;
+                Ok(())
+            }
+            server::Packet::Kick { reason } => match reason {
+                server::KickReason::Operator(reason) => (reason.chars().count()
+                    <= chat::MAX_MESSAGE_LENGTH)
+                    .then_some(())
+                    .ok_or(client::PacketError::Kick),
+                server::KickReason::Packet(_) | server::KickReason::Timeout => Ok(()),
+            },
+            server::Packet::Teleport {
+                x,
+                y,
+                z,
+                yaw,
+                pitch,
+            } => is_finite(&[*x, *y, *z, *yaw, *pitch])
+                .then_some(())
+                .ok_or(client::PacketError::NonFinite),
+            server::Packet::Chunk(chunk) => {
+                chunk
+                    .pos
+                    .is_in_bounds()
+                    .then_some(())
+                    .ok_or(client::PacketError::Position)?;
+                chunk
+                    .is_valid()
+                    .then_some(())
+                    .ok_or(client::PacketError::Chunk)?;
+                Ok(())
+            }
+            server::Packet::CompressedChunk { data, .. } => (data.len()
+                <= compress::MAX_CHUNK_SIZE)
+                .then_some(())
+                .ok_or(client::PacketError::Chunk),
+            server::Packet::MultiBlockChange { chunk, changes } => {
+                chunk
+                    .is_in_bounds()
+                    .then_some(())
+                    .ok_or(client::PacketError::Position)?;
+                (changes.len() <= CHUNK_VOLUME
+                    && changes
+                        .iter()
+                        .all(|change| (change.index as usize) < CHUNK_VOLUME))
+                .then_some(())
+                .ok_or(client::PacketError::BlockChange)?;
+                Ok(())
+            }
+            server::Packet::BlockChange { pos, .. } => pos
+                .is_in_bounds()
+                .then_some(())
+                .ok_or(client::PacketError::Position),
+            server::Packet::SpawnEntity {
+                name,
+                position,
+                yaw,
+                pitch,
+                ..
+            } => {
+                is_finite(position)
+                    .then_some(())
+                    .ok_or(client::PacketError::NonFinite)?;
+                is_finite(&[*yaw, *pitch])
+                    .then_some(())
+                    .ok_or(client::PacketError::NonFinite)?;
+                name.as_deref()
+                    .is_none_or(|name| self.check_username(name))
                     .then_some(())
-                    .ok_or(client::PacketError::Version)?;
+                    .ok_or(client::PacketError::Name)?;
                 Ok(())
             }
-            _ => Ok(()),
+            server::Packet::TeleportEntity { position, .. } => is_finite(position)
+                .then_some(())
+                .ok_or(client::PacketError::NonFinite),
+            server::Packet::RotateEntity { yaw, pitch, .. } => is_finite(&[*yaw, *pitch])
+                .then_some(())
+                .ok_or(client::PacketError::NonFinite),
+            server::Packet::Chat { segments } => chat::is_valid_segments(segments)
+                .then_some(())
+                .ok_or(client::PacketError::Chat),
+            server::Packet::KeepAlive { .. }
+            | server::Packet::MoveEntity { .. }
+            | server::Packet::DespawnEntity { .. } => Ok(()),
         }
     }
 }
@@ -0,0 +1,93 @@
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Longest chat message a client may send, in characters
+pub const MAX_MESSAGE_LENGTH: usize = 256;
+
+/// Most segments a message from the server may have
+pub const MAX_SEGMENTS: usize = 32;
+
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum Color {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    #[default]
+    White,
+}
+
+impl Color {
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Color::Black => [0x00, 0x00, 0x00],
+            Color::DarkBlue => [0x00, 0x00, 0xaa],
+            Color::DarkGreen => [0x00, 0xaa, 0x00],
+            Color::DarkAqua => [0x00, 0xaa, 0xaa],
+            Color::DarkRed => [0xaa, 0x00, 0x00],
+            Color::DarkPurple => [0xaa, 0x00, 0xaa],
+            Color::Gold => [0xff, 0xaa, 0x00],
+            Color::Gray => [0xaa, 0xaa, 0xaa],
+            Color::DarkGray => [0x55, 0x55, 0x55],
+            Color::Blue => [0x55, 0x55, 0xff],
+            Color::Green => [0x55, 0xff, 0x55],
+            Color::Aqua => [0x55, 0xff, 0xff],
+            Color::Red => [0xff, 0x55, 0x55],
+            Color::LightPurple => [0xff, 0x55, 0xff],
+            Color::Yellow => [0xff, 0xff, 0x55],
+            Color::White => [0xff, 0xff, 0xff],
+        }
+    }
+}
+
+/// A run of text drawn in one style
+#[derive(Archive, Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct Segment {
+    pub text: String,
+    pub color: Color,
+    pub bold: bool,
+}
+
+impl Segment {
+    pub fn new(text: impl Into<String>, color: Color) -> Self {
+        Self {
+            text: text.into(),
+            color,
+            bold: false,
+        }
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+}
+
+/// Whether a client may send `message`, no blank messages or control characters
+pub fn is_valid_message(message: &str) -> bool {
+    (1..=MAX_MESSAGE_LENGTH).contains(&message.chars().count())
+        && !message.trim().is_empty()
+        && !message.chars().any(char::is_control)
+}
+
+/// Whether the server may send `segments`, each no longer than a message
+pub fn is_valid_segments(segments: &[Segment]) -> bool {
+    segments.len() <= MAX_SEGMENTS
+        && segments
+            .iter()
+            .all(|segment| segment.text.chars().count() <= MAX_MESSAGE_LENGTH)
+}
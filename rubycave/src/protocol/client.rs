@@ -1,13 +1,35 @@
 use rkyv::{Archive, Deserialize, Serialize};
 
+use crate::world::{BlockPos, BlockState, Face};
+
+use super::compress::Compression;
+
 #[derive(Archive, Deserialize, Serialize, Debug, thiserror::Error)]
 #[archive(check_bytes)]
 #[archive_attr(derive(Debug))]
 pub enum PacketError {
     #[error("expected handshake")]
     Handshake,
-    #[error("client/server version mismatch")]
-    Version,
+    #[error("incompatible server, this client supports protocol versions {min} to {max}")]
+    Version { min: u32, max: u32 },
+    #[error("invalid block change")]
+    BlockChange,
+    #[error("non-finite number")]
+    NonFinite,
+    #[error("invalid features")]
+    Features,
+    #[error("invalid compression offer")]
+    Compression,
+    #[error("invalid chunk")]
+    Chunk,
+    #[error("invalid entity name")]
+    Name,
+    #[error("invalid chat message")]
+    Chat,
+    #[error("invalid kick reason")]
+    Kick,
+    #[error("position out of bounds")]
+    Position,
 }
 
 #[derive(Archive, Deserialize, Serialize, Debug)]
@@ -18,11 +40,55 @@ pub enum DisconnectReason {
     Player,
 }
 
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum DigAction {
+    Start,
+    Cancel,
+    Finish,
+}
+
 #[derive(Archive, Deserialize, Serialize, Debug)]
 #[archive(check_bytes)]
 #[archive_attr(derive(Debug))]
 pub enum Packet {
-    Handshake { version: String, username: String },
-    Disconnect { reason: DisconnectReason },
-    KeepAlive { epoch: u64 },
+    /// Speaks protocol versions `min_version` to `version`
+    Handshake {
+        version: u32,
+        min_version: u32,
+        features: Vec<String>,
+        /// Picked from the ones the server offered
+        compression: Option<Compression>,
+        username: String,
+    },
+    Disconnect {
+        reason: DisconnectReason,
+    },
+    /// Answers the server's keep-alive with the same id
+    KeepAlive {
+        id: u64,
+    },
+    Move {
+        x: f32,
+        y: f32,
+        z: f32,
+        yaw: f32,
+        pitch: f32,
+        on_ground: bool,
+    },
+    Dig {
+        action: DigAction,
+        pos: BlockPos,
+        face: Face,
+    },
+    /// Places `state` against the `face` of the block at `pos`
+    Place {
+        pos: BlockPos,
+        face: Face,
+        state: BlockState,
+    },
+    Chat {
+        message: String,
+    },
 }
@@ -0,0 +1,116 @@
+use std::io;
+
+use rkyv_codec::{futures_stream::RkyvCodec, RkyvCodecError, VarintLength};
+use tokio_util::{
+    bytes::BytesMut,
+    codec::{Decoder, Encoder},
+};
+
+use super::{server, Packet};
+
+/// Longest frame accepted unless told otherwise, enough for any chunk
+pub const MAX_FRAME_LENGTH: usize = 1 << 21;
+
+/// Bytes a varint length prefix of a `usize` can take
+const MAX_VARINT_LENGTH: usize = 10;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("io error")]
+    Io(#[from] io::Error),
+    #[error("rkyv_codec error")]
+    RkyvCodec(#[from] RkyvCodecError),
+    #[error("frame of {length} bytes is longer than {max}")]
+    TooLong { length: usize, max: usize },
+    #[error("invalid frame length")]
+    Length,
+}
+
+impl Error {
+    /// Why the peer gets kicked, `None` if it's not the peer's fault
+    pub fn packet_error(&self) -> Option<server::PacketError> {
+        match self {
+            Error::Io(_) => None,
+            Error::RkyvCodec(_) | Error::Length => Some(server::PacketError::Malformed),
+            Error::TooLong { max, .. } => {
+                Some(server::PacketError::FrameLength { max: *max as u32 })
+            }
+        }
+    }
+}
+
+/// Length prefixed packets, refusing frames longer than `max_frame_length`
+/// before any of it is buffered
+pub struct PacketCodec {
+    inner: RkyvCodec<Packet, VarintLength>,
+    max_frame_length: usize,
+}
+
+impl PacketCodec {
+    pub fn new(max_frame_length: usize) -> Self {
+        Self {
+            inner: RkyvCodec::default(),
+            max_frame_length,
+        }
+    }
+}
+
+impl Default for PacketCodec {
+    fn default() -> Self {
+        Self::new(MAX_FRAME_LENGTH)
+    }
+}
+
+impl Decoder for PacketCodec {
+    type Item = Packet;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Packet>, Error> {
+        if let Some(length) = frame_length(src)? {
+            if length > self.max_frame_length {
+                return Err(Error::TooLong {
+                    length,
+                    max: self.max_frame_length,
+                });
+            }
+        }
+
+        Ok(self.inner.decode(src)?)
+    }
+}
+
+impl Encoder<Packet> for PacketCodec {
+    type Error = Error;
+
+    fn encode(&mut self, packet: Packet, dst: &mut BytesMut) -> Result<(), Error> {
+        Ok(self.inner.encode(packet, dst)?)
+    }
+}
+
+/// Reads the LEB128 length prefix of the next frame, `None` until all of it
+/// has arrived
+fn frame_length(src: &[u8]) -> Result<Option<usize>, Error> {
+    let mut length = 0u64;
+
+    for (i, byte) in src.iter().take(MAX_VARINT_LENGTH).enumerate() {
+        let bits = (*byte & 0x7f) as u64;
+        let shift = i as u32 * 7;
+
+        // Overflowing a u64 can only be on purpose
+        if (bits << shift) >> shift != bits {
+            return Err(Error::Length);
+        }
+
+        length |= bits << shift;
+
+        if byte & 0x80 == 0 {
+            return usize::try_from(length).map(Some).map_err(|_| Error::Length);
+        }
+    }
+
+    if src.len() >= MAX_VARINT_LENGTH {
+        Err(Error::Length)
+    } else {
+        Ok(None)
+    }
+}
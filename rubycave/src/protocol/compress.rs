@@ -0,0 +1,155 @@
+use std::{io, str::FromStr};
+
+use rkyv::{AlignedVec, Archive, Deserialize, Serialize};
+
+use crate::world::Chunk;
+
+use super::server;
+
+/// Chunks smaller than this many bytes are sent as they are
+pub const THRESHOLD: usize = 1024;
+
+/// Largest a decompressed chunk may be, so a small packet can't claim a huge
+/// allocation
+pub const MAX_CHUNK_SIZE: usize = 1 << 20;
+
+/// Algorithms this build can decompress, in order of preference
+pub const ALGORITHMS: [Algorithm; 2] = [Algorithm::Zstd, Algorithm::Lz4];
+
+/// Most compressions the server may offer
+pub const MAX_OFFERS: usize = 8;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("zstd error")]
+    Zstd(#[from] io::Error),
+    #[error("lz4 error")]
+    Lz4(#[from] lz4_flex::block::DecompressError),
+    #[error("decompressed chunk too large")]
+    TooLarge,
+    #[error("invalid chunk")]
+    Chunk,
+    #[error("invalid compression: {0}")]
+    Parse(String),
+}
+
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum Algorithm {
+    Zstd,
+    Lz4,
+}
+
+/// An algorithm and how hard it tries, the level is ignored by lz4
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct Compression {
+    pub algorithm: Algorithm,
+    pub level: i32,
+}
+
+impl Algorithm {
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Algorithm::Zstd => Ok(zstd::bulk::decompress(data, MAX_CHUNK_SIZE)?),
+            Algorithm::Lz4 => {
+                // The size is prepended, checked before anything is allocated
+                let size = data
+                    .first_chunk::<4>()
+                    .map(|size| u32::from_le_bytes(*size) as usize);
+
+                if size.is_none_or(|size| size > MAX_CHUNK_SIZE) {
+                    return Err(Error::TooLarge);
+                }
+
+                Ok(lz4_flex::decompress_size_prepended(data)?)
+            }
+        }
+    }
+}
+
+impl Compression {
+    pub fn is_valid(self) -> bool {
+        match self.algorithm {
+            Algorithm::Zstd => zstd::compression_level_range().contains(&self.level),
+            Algorithm::Lz4 => true,
+        }
+    }
+
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match self.algorithm {
+            Algorithm::Zstd => Ok(zstd::bulk::compress(data, self.level)?),
+            Algorithm::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+        }
+    }
+}
+
+/// Parses `zstd`, `zstd:<level>` or `lz4`
+impl FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, level) = match s.split_once(':') {
+            Some((name, level)) => (
+                name,
+                Some(level.parse().map_err(|_| Error::Parse(s.to_owned()))?),
+            ),
+            None => (s, None),
+        };
+
+        let algorithm = match name {
+            "zstd" => Algorithm::Zstd,
+            "lz4" => Algorithm::Lz4,
+            _ => return Err(Error::Parse(s.to_owned())),
+        };
+
+        let default = match algorithm {
+            Algorithm::Zstd => zstd::DEFAULT_COMPRESSION_LEVEL,
+            Algorithm::Lz4 => 0,
+        };
+
+        Ok(Self {
+            algorithm,
+            level: level.unwrap_or(default),
+        })
+    }
+}
+
+/// Turns a large enough chunk packet into a compressed one, leaving any other
+/// packet alone
+pub fn compress_packet(
+    packet: server::Packet,
+    compression: Option<Compression>,
+) -> Result<server::Packet, Error> {
+    let (server::Packet::Chunk(chunk), Some(compression)) = (&packet, compression) else {
+        return Ok(packet);
+    };
+
+    let bytes = rkyv::to_bytes::<_, 4096>(chunk.as_ref()).map_err(|_| Error::Chunk)?;
+
+    if bytes.len() < THRESHOLD {
+        return Ok(packet);
+    }
+
+    Ok(server::Packet::CompressedChunk {
+        algorithm: compression.algorithm,
+        data: compression.compress(&bytes)?,
+    })
+}
+
+/// Undoes [`compress_packet`]
+pub fn decompress_packet(packet: server::Packet) -> Result<server::Packet, Error> {
+    let server::Packet::CompressedChunk { algorithm, data } = packet else {
+        return Ok(packet);
+    };
+
+    // Archived data has to be aligned to be read
+    let mut bytes = AlignedVec::new();
+    bytes.extend_from_slice(&algorithm.decompress(&data)?);
+
+    let chunk = rkyv::from_bytes::<Chunk>(&bytes).map_err(|_| Error::Chunk)?;
+
+    Ok(server::Packet::Chunk(Box::new(chunk)))
+}
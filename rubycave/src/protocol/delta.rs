@@ -0,0 +1,101 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::world::{BlockPos, BlockState, Chunk, ChunkPos, LocalPos, World, CHUNK_VOLUME};
+
+use super::server;
+
+/// A block set in a chunk, by its [`LocalPos::index`]
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct BlockDelta {
+    pub index: u16,
+    pub state: BlockState,
+}
+
+/// Every change to a chunk in one tick, in whichever form is cheapest to send
+#[derive(Clone, Debug)]
+pub enum ChunkDelta {
+    Block(BlockPos, BlockState),
+    Blocks(ChunkPos, Vec<BlockDelta>),
+    Chunk(Box<Chunk>),
+}
+
+impl ChunkDelta {
+    pub fn chunk(&self) -> ChunkPos {
+        match self {
+            ChunkDelta::Block(pos, _) => pos.chunk(),
+            ChunkDelta::Blocks(pos, _) => *pos,
+            ChunkDelta::Chunk(chunk) => chunk.pos,
+        }
+    }
+
+    pub fn to_packet(&self) -> server::Packet {
+        match self {
+            ChunkDelta::Block(pos, state) => server::Packet::BlockChange {
+                pos: *pos,
+                state: *state,
+            },
+            ChunkDelta::Blocks(chunk, changes) => server::Packet::MultiBlockChange {
+                chunk: *chunk,
+                changes: changes.clone(),
+            },
+            ChunkDelta::Chunk(chunk) => server::Packet::Chunk(chunk.clone()),
+        }
+    }
+}
+
+/// Groups changes by chunk, later ones to a block replacing earlier ones, and
+/// picks for each chunk whether the changes or the whole chunk are smaller
+pub fn batch(
+    world: &World,
+    changes: impl IntoIterator<Item = (BlockPos, BlockState)>,
+) -> Vec<ChunkDelta> {
+    let mut chunks = HashMap::<_, BTreeMap<_, _>>::new();
+
+    for (pos, state) in changes {
+        let (chunk, local) = pos.split();
+        chunks
+            .entry(chunk)
+            .or_default()
+            .insert(local.index() as u16, state);
+    }
+
+    chunks
+        .into_iter()
+        .map(|(pos, changes)| {
+            let changes = changes
+                .into_iter()
+                .map(|(index, state)| BlockDelta { index, state })
+                .collect::<Vec<_>>();
+
+            if let &[delta] = changes.as_slice() {
+                let local = LocalPos::from_index(delta.index as usize);
+                return ChunkDelta::Block(pos.block(local), delta.state);
+            }
+
+            // Scheduled ticks stay on the server
+            match world.get_chunk(pos) {
+                Some(chunk) if is_cheaper(chunk, changes.len()) => {
+                    ChunkDelta::Chunk(Box::new(chunk.clone()))
+                }
+                _ => ChunkDelta::Blocks(pos, changes),
+            }
+        })
+        .collect()
+}
+
+/// Whether sending `chunk` takes fewer bytes than `changes` block deltas
+fn is_cheaper(chunk: &Chunk, changes: usize) -> bool {
+    let delta_size = changes * size_of::<ArchivedBlockDelta>();
+
+    // Both light arrays alone take half a byte per block, so the chunk only
+    // has to be archived once the changes come close
+    if delta_size < CHUNK_VOLUME {
+        return false;
+    }
+
+    rkyv::to_bytes::<_, 4096>(chunk).is_ok_and(|bytes| bytes.len() < delta_size)
+}
@@ -1,6 +1,18 @@
 use rkyv::{Archive, Deserialize, Serialize};
 
-use crate::world::Chunk;
+use crate::{
+    entity::{EntityId, EntityKind},
+    world::{BlockPos, BlockState, Chunk, ChunkPos},
+};
+
+use super::{
+    chat::Segment,
+    compress::{Algorithm, Compression},
+    delta::BlockDelta,
+};
+
+/// Steps per block of [`Packet::MoveEntity`], moves of up to 8 blocks fit
+pub const MOVE_SCALE: f32 = 4096.0;
 
 #[derive(Archive, Deserialize, Serialize, Debug, thiserror::Error)]
 #[archive(check_bytes)]
@@ -8,10 +20,24 @@ use crate::world::Chunk;
 pub enum PacketError {
     #[error("expected handshake")]
     Handshake,
-    #[error("client/server version mismatch")]
-    Version,
+    #[error("incompatible client, this server supports protocol versions {min} to {max}")]
+    Version { min: u32, max: u32 },
     #[error("invalid username")]
     Username,
+    #[error("invalid chat message")]
+    Chat,
+    #[error("compression not offered")]
+    Compression,
+    #[error("non-finite number")]
+    NonFinite,
+    #[error("invalid features")]
+    Features,
+    #[error("frame longer than {max} bytes")]
+    FrameLength { max: u32 },
+    #[error("malformed packet")]
+    Malformed,
+    #[error("position out of bounds")]
+    Position,
 }
 
 #[derive(Archive, Deserialize, Serialize, Debug)]
@@ -19,6 +45,8 @@ pub enum PacketError {
 #[archive_attr(derive(Debug))]
 pub enum KickReason {
     Packet(PacketError),
+    /// Stopped answering keep-alives
+    Timeout,
     Operator(String),
 }
 
@@ -26,12 +54,23 @@ pub enum KickReason {
 #[archive(check_bytes)]
 #[archive_attr(derive(Debug))]
 pub enum Packet {
+    /// Speaks protocol versions `min_version` to `version`
     Handshake {
-        version: String,
+        version: u32,
+        min_version: u32,
+        features: Vec<String>,
+        /// Compressions the server can use, in order of preference
+        compression: Vec<Compression>,
     },
     Kick {
         reason: KickReason,
     },
+    /// Sent every [`KEEP_ALIVE_INTERVAL`](crate::KEEP_ALIVE_INTERVAL) ms,
+    /// with the round trip time of the last one in ms
+    KeepAlive {
+        id: u64,
+        latency: Option<u32>,
+    },
     Teleport {
         x: f32,
         y: f32,
@@ -40,4 +79,46 @@ pub enum Packet {
         pitch: f32,
     },
     Chunk(Box<Chunk>),
+    /// A [`Packet::Chunk`] compressed with `algorithm`, sent in its place
+    CompressedChunk {
+        algorithm: Algorithm,
+        data: Vec<u8>,
+    },
+    BlockChange {
+        pos: BlockPos,
+        state: BlockState,
+    },
+    /// Several blocks of one chunk, changed in the same tick
+    MultiBlockChange {
+        chunk: ChunkPos,
+        changes: Vec<BlockDelta>,
+    },
+    SpawnEntity {
+        id: EntityId,
+        kind: EntityKind,
+        name: Option<String>,
+        position: [f32; 3],
+        yaw: f32,
+        pitch: f32,
+    },
+    /// Moves an entity by `delta` / [`MOVE_SCALE`] blocks
+    MoveEntity {
+        id: EntityId,
+        delta: [i16; 3],
+    },
+    TeleportEntity {
+        id: EntityId,
+        position: [f32; 3],
+    },
+    RotateEntity {
+        id: EntityId,
+        yaw: f32,
+        pitch: f32,
+    },
+    DespawnEntity {
+        id: EntityId,
+    },
+    Chat {
+        segments: Vec<Segment>,
+    },
 }
@@ -1,23 +1,373 @@
+use std::collections::BTreeSet;
+
+use gxhash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use rkyv::{Archive, Deserialize, Serialize};
 
+pub use biome::{Biome, Colormap};
+pub use block::{BlockDef, BlockId, BlockRegistry};
+pub use fluid::{Fluid, FluidSim, FluidState};
+pub use heightmap::{Heightmap, HeightmapKind, Heightmaps};
+pub use light::{LightArray, LightKind};
+pub use pos::{Axis, BlockPos, ChunkPos, Face, LocalPos};
+pub use ray::{raycast, RayHit};
+pub use state::{BlockState, PropertyValue};
+pub use storage::BlockStorage;
+pub use tick::{Behavior, ChunkTick, TickQueue, Ticker};
+
+pub mod biome;
+pub mod block;
+pub mod fluid;
+pub mod heightmap;
+pub mod light;
+pub mod pos;
+pub mod ray;
+pub mod state;
+pub mod storage;
+pub mod tick;
+
 pub const CHUNK_WIDTH: usize = 16;
 pub const CHUNK_LENGTH: usize = 16;
 pub const CHUNK_HEIGHT: usize = 16;
+pub const CHUNK_VOLUME: usize = CHUNK_WIDTH * CHUNK_LENGTH * CHUNK_HEIGHT;
 
-#[derive(Archive, Deserialize, Serialize, Debug)]
-#[archive(check_bytes)]
-#[archive_attr(derive(Debug))]
-pub enum BlockId {
-    Air,
-    Grass,
-}
+/// Furthest a block can be from the origin along any axis, so neighbors and
+/// chunk origins never overflow
+pub const MAX_COORD: i32 = 30_000_000;
 
-#[derive(Archive, Deserialize, Serialize, Debug)]
+#[derive(Archive, Deserialize, Serialize, Clone, Debug)]
 #[archive(check_bytes)]
 #[archive_attr(derive(Debug))]
 pub struct Chunk {
-    pub x: u32,
-    pub y: u32,
-    pub z: u32,
-    pub blocks: [BlockId; CHUNK_WIDTH * CHUNK_LENGTH * CHUNK_HEIGHT],
+    pub pos: ChunkPos,
+    blocks: BlockStorage,
+    block_light: LightArray,
+    sky_light: LightArray,
+    biomes: [Biome; CHUNK_WIDTH * CHUNK_LENGTH],
+    /// Derived from the blocks, so left out when sending or saving
+    #[with(rkyv::with::Skip)]
+    heightmaps: Heightmaps,
+    /// Scheduled ticks, only kept here while out of a world
+    ticks: Vec<ChunkTick>,
+}
+
+impl Chunk {
+    pub fn new(pos: ChunkPos) -> Self {
+        Self {
+            pos,
+            blocks: BlockStorage::default(),
+            block_light: LightArray::new(),
+            sky_light: LightArray::new(),
+            biomes: [Biome::default(); CHUNK_WIDTH * CHUNK_LENGTH],
+            heightmaps: Heightmaps::default(),
+            ticks: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, pos: LocalPos) -> BlockState {
+        self.blocks.get(pos.index())
+    }
+
+    /// Leaves the heightmaps as they are, see [`Chunk::compute_heightmaps`]
+    pub fn set(&mut self, pos: LocalPos, block: BlockState) {
+        self.blocks.set(pos.index(), block);
+    }
+
+    pub fn fill(&mut self, block: BlockState) {
+        self.blocks.fill(block);
+    }
+
+    pub fn compact(&mut self) {
+        self.blocks.compact();
+    }
+
+    pub fn get_storage(&self) -> &BlockStorage {
+        &self.blocks
+    }
+
+    pub fn get_light(&self, kind: LightKind, pos: LocalPos) -> u8 {
+        match kind {
+            LightKind::Block => self.block_light.get(pos.index()),
+            LightKind::Sky => self.sky_light.get(pos.index()),
+        }
+    }
+
+    pub fn set_light(&mut self, kind: LightKind, pos: LocalPos, level: u8) {
+        match kind {
+            LightKind::Block => self.block_light.set(pos.index(), level),
+            LightKind::Sky => self.sky_light.set(pos.index(), level),
+        }
+    }
+
+    /// Biome of the column `pos` is in
+    pub fn get_biome(&self, pos: LocalPos) -> Biome {
+        self.biomes[pos.column()]
+    }
+
+    pub fn set_biome(&mut self, pos: LocalPos, biome: Biome) {
+        self.biomes[pos.column()] = biome;
+    }
+
+    /// Local height of the highest block of `kind` in the column `pos` is in
+    pub fn get_height(&self, kind: HeightmapKind, pos: LocalPos) -> Option<i32> {
+        self.heightmaps.get(kind).get(pos.column())
+    }
+
+    pub fn get_heightmaps(&self) -> &Heightmaps {
+        &self.heightmaps
+    }
+
+    /// Rebuilds the heightmaps from every block
+    pub fn compute_heightmaps(&mut self, registry: &BlockRegistry) {
+        self.heightmaps = Heightmaps::default();
+
+        for local in LocalPos::iter() {
+            let state = self.get(local);
+            self.heightmaps
+                .update(registry, local.column(), local.y(), state);
+        }
+    }
+
+    pub fn ticks(&self) -> &[ChunkTick] {
+        &self.ticks
+    }
+
+    /// Drops the scheduled ticks, for a world that never ticks
+    pub fn clear_ticks(&mut self) {
+        self.ticks.clear();
+    }
+
+    /// Whether the storage and light arrays are whole and the ticks inside the
+    /// chunk, so a chunk from the wire can't cause a panic later
+    pub fn is_valid(&self) -> bool {
+        self.blocks.is_valid()
+            && self.block_light.is_valid()
+            && self.sky_light.is_valid()
+            && self
+                .ticks
+                .iter()
+                .all(|tick| LocalPos::try_new(tick.pos.x(), tick.pos.y(), tick.pos.z()).is_some())
+    }
+}
+
+/// Loaded chunks keyed by position
+///
+/// Chunks touched by `insert_chunk` or `set_block` are marked dirty, along with
+/// any loaded neighbor whose border faces may have changed, until taken with
+/// `take_dirty`.
+#[derive(Default)]
+pub struct World {
+    chunks: HashMap<ChunkPos, Box<Chunk>>,
+    /// Heights of the loaded chunks of each column
+    columns: HashMap<(i32, i32), BTreeSet<i32>>,
+    dirty: HashSet<ChunkPos>,
+    ticks: TickQueue,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            chunks: HashMap::new(),
+            columns: HashMap::new(),
+            dirty: HashSet::new(),
+            ticks: TickQueue::new(),
+        }
+    }
+
+    pub fn get_chunk(&self, pos: ChunkPos) -> Option<&Chunk> {
+        self.chunks.get(&pos).map(|chunk| chunk.as_ref())
+    }
+
+    pub fn get_chunk_mut(&mut self, pos: ChunkPos) -> Option<&mut Chunk> {
+        self.mark_dirty(pos);
+        self.chunks.get_mut(&pos).map(|chunk| chunk.as_mut())
+    }
+
+    pub fn insert_chunk(&mut self, mut chunk: Box<Chunk>) -> Option<Box<Chunk>> {
+        let pos = chunk.pos;
+
+        self.ticks.load_chunk(pos, std::mem::take(&mut chunk.ticks));
+
+        let old = self.chunks.insert(pos, chunk);
+
+        self.columns
+            .entry((pos.x, pos.z))
+            .or_default()
+            .insert(pos.y);
+
+        self.mark_dirty(pos);
+        self.mark_neighbors_dirty(pos);
+        old
+    }
+
+    /// Removes a chunk along with its scheduled ticks
+    pub fn remove_chunk(&mut self, pos: ChunkPos) -> Option<Box<Chunk>> {
+        let mut chunk = self.chunks.remove(&pos)?;
+
+        chunk.ticks = self.ticks.take_chunk(pos);
+
+        if let Some(column) = self.columns.get_mut(&(pos.x, pos.z)) {
+            column.remove(&pos.y);
+
+            if column.is_empty() {
+                self.columns.remove(&(pos.x, pos.z));
+            }
+        }
+
+        self.dirty.remove(&pos);
+        self.mark_neighbors_dirty(pos);
+
+        Some(chunk)
+    }
+
+    /// Copies a chunk along with its scheduled ticks, for saving it
+    pub fn export_chunk(&self, pos: ChunkPos) -> Option<Chunk> {
+        let mut chunk = self.get_chunk(pos)?.clone();
+
+        chunk.ticks = self.ticks.chunk_ticks(pos);
+        Some(chunk)
+    }
+
+    pub fn is_loaded(&self, pos: ChunkPos) -> bool {
+        self.chunks.contains_key(&pos)
+    }
+
+    /// Returns `None` if the block's chunk isn't loaded
+    pub fn get_block(&self, pos: BlockPos) -> Option<BlockState> {
+        let (chunk, local) = pos.split();
+        Some(self.chunks.get(&chunk)?.get(local))
+    }
+
+    /// Sets a block and updates its chunk's heightmaps, but not the light
+    ///
+    /// Returns `false` if the block's chunk isn't loaded
+    pub fn set_block(
+        &mut self,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        state: BlockState,
+    ) -> bool {
+        let (chunk_pos, local) = pos.split();
+
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else {
+            return false;
+        };
+
+        chunk.set(local, state);
+        chunk
+            .heightmaps
+            .update(registry, local.column(), local.y(), state);
+        self.mark_block_dirty(chunk_pos, local);
+
+        true
+    }
+
+    /// Height of the highest block of `kind` in the column at `x`, `z`,
+    /// looking through its loaded chunks only
+    pub fn get_height(&self, kind: HeightmapKind, x: i32, z: i32) -> Option<i32> {
+        let (column, local) = BlockPos::new(x, 0, z).split();
+
+        self.columns
+            .get(&(column.x, column.z))?
+            .iter()
+            .rev()
+            .find_map(|y| {
+                let chunk = self.chunks.get(&ChunkPos::new(column.x, *y, column.z))?;
+                let height = chunk.get_height(kind, local)?;
+
+                Some(y * CHUNK_HEIGHT as i32 + height)
+            })
+    }
+
+    /// Returns `None` if the block's chunk isn't loaded
+    pub fn get_light(&self, kind: LightKind, pos: BlockPos) -> Option<u8> {
+        let (chunk, local) = pos.split();
+        Some(self.chunks.get(&chunk)?.get_light(kind, local))
+    }
+
+    /// Returns `None` if the block's chunk isn't loaded
+    pub fn get_biome(&self, pos: BlockPos) -> Option<Biome> {
+        let (chunk, local) = pos.split();
+        Some(self.chunks.get(&chunk)?.get_biome(local))
+    }
+
+    /// Returns `false` if the block's chunk isn't loaded
+    pub fn set_light(&mut self, kind: LightKind, pos: BlockPos, level: u8) -> bool {
+        let (chunk_pos, local) = pos.split();
+
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else {
+            return false;
+        };
+
+        if chunk.get_light(kind, local) != level {
+            chunk.set_light(kind, local, level);
+            self.mark_block_dirty(chunk_pos, local);
+        }
+
+        true
+    }
+
+    pub fn neighbor(&self, pos: ChunkPos, face: Face) -> Option<&Chunk> {
+        self.get_chunk(pos.neighbor(face))
+    }
+
+    pub fn neighbors(&self, pos: ChunkPos) -> impl Iterator<Item = (Face, &Chunk)> {
+        Face::ALL
+            .into_iter()
+            .filter_map(move |face| Some((face, self.neighbor(pos, face)?)))
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = &Chunk> {
+        self.chunks.values().map(|chunk| chunk.as_ref())
+    }
+
+    pub fn chunks_mut(&mut self) -> impl Iterator<Item = &mut Chunk> {
+        self.chunks.values_mut().map(|chunk| chunk.as_mut())
+    }
+
+    pub fn ticks(&self) -> &TickQueue {
+        &self.ticks
+    }
+
+    pub fn ticks_mut(&mut self) -> &mut TickQueue {
+        &mut self.ticks
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Only loaded chunks can be marked dirty
+    pub fn mark_dirty(&mut self, pos: ChunkPos) {
+        if self.chunks.contains_key(&pos) {
+            self.dirty.insert(pos);
+        }
+    }
+
+    pub fn is_dirty(&self, pos: ChunkPos) -> bool {
+        self.dirty.contains(&pos)
+    }
+
+    pub fn take_dirty(&mut self) -> Vec<ChunkPos> {
+        self.dirty.drain().collect()
+    }
+
+    fn mark_block_dirty(&mut self, pos: ChunkPos, local: LocalPos) {
+        self.mark_dirty(pos);
+
+        for face in Face::ALL {
+            if local.neighbor(face).is_none() {
+                self.mark_dirty(pos.neighbor(face));
+            }
+        }
+    }
+
+    fn mark_neighbors_dirty(&mut self, pos: ChunkPos) {
+        for face in Face::ALL {
+            self.mark_dirty(pos.neighbor(face));
+        }
+    }
 }
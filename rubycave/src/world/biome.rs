@@ -0,0 +1,120 @@
+use rkyv::{Archive, Deserialize, Serialize};
+
+#[derive(
+    Archive,
+    Deserialize,
+    Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    Debug,
+)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+#[serde(rename_all = "snake_case")]
+pub enum Biome {
+    #[default]
+    Plains,
+    Forest,
+    Swamp,
+    Desert,
+    Savanna,
+    Taiga,
+    Tundra,
+}
+
+/// Colormaps used to tint block textures by biome
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Colormap {
+    Grass,
+    Foliage,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 7] = [
+        Biome::Plains,
+        Biome::Forest,
+        Biome::Swamp,
+        Biome::Desert,
+        Biome::Savanna,
+        Biome::Taiga,
+        Biome::Tundra,
+    ];
+
+    /// Picks a biome from a temperature and humidity in `0.0..=1.0`
+    pub fn from_climate(temperature: f64, humidity: f64) -> Self {
+        if temperature < 0.2 {
+            Biome::Tundra
+        } else if temperature < 0.4 {
+            if humidity < 0.4 {
+                Biome::Plains
+            } else {
+                Biome::Taiga
+            }
+        } else if temperature < 0.7 {
+            if humidity < 0.35 {
+                Biome::Plains
+            } else if humidity < 0.75 {
+                Biome::Forest
+            } else {
+                Biome::Swamp
+            }
+        } else if humidity < 0.3 {
+            Biome::Desert
+        } else if humidity < 0.6 {
+            Biome::Savanna
+        } else {
+            Biome::Forest
+        }
+    }
+
+    pub fn temperature(self) -> f32 {
+        match self {
+            Biome::Plains => 0.8,
+            Biome::Forest => 0.7,
+            Biome::Swamp => 0.8,
+            Biome::Desert => 1.0,
+            Biome::Savanna => 1.0,
+            Biome::Taiga => 0.25,
+            Biome::Tundra => 0.0,
+        }
+    }
+
+    pub fn humidity(self) -> f32 {
+        match self {
+            Biome::Plains => 0.4,
+            Biome::Forest => 0.8,
+            Biome::Swamp => 0.9,
+            Biome::Desert => 0.0,
+            Biome::Savanna => 0.2,
+            Biome::Taiga => 0.8,
+            Biome::Tundra => 0.5,
+        }
+    }
+
+    /// Position of the biome's color in a 256x256 colormap, both in `0.0..=1.0`
+    pub fn colormap_coords(self) -> (f32, f32) {
+        let temperature = self.temperature().clamp(0.0, 1.0);
+        let humidity = self.humidity().clamp(0.0, 1.0) * temperature;
+
+        (1.0 - temperature, 1.0 - humidity)
+    }
+
+    /// Chance of a column to grow a tree
+    pub fn tree_density(self) -> f32 {
+        match self {
+            Biome::Plains => 0.002,
+            Biome::Forest => 0.04,
+            Biome::Swamp => 0.01,
+            Biome::Desert => 0.0,
+            Biome::Savanna => 0.004,
+            Biome::Taiga => 0.03,
+            Biome::Tundra => 0.001,
+        }
+    }
+}
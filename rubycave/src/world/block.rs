@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::TICK_RATE;
+
+use super::{
+    biome::Colormap,
+    pos::{Axis, Face},
+    state::{BlockState, PropertyDef, PropertyKind, PropertyValue},
+    tick::Behavior,
+};
+
+const BUILTIN_BLOCKS: &str = include_str!("../../res/block.toml");
+
+/// Tiles along each side of the terrain atlas
+pub const ATLAS_TILES: u16 = 16;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("toml error")]
+    Toml(#[from] toml::de::Error),
+    #[error("duplicate block id {0}")]
+    DuplicateId(u16),
+    #[error("duplicate block name {0:?}")]
+    DuplicateName(String),
+    #[error("block {0:?} has a light level above 15")]
+    Light(String),
+    #[error("block {0:?} has a texture outside the atlas")]
+    Texture(String),
+    #[error("block {0:?} has an invalid property {1:?}")]
+    Property(String, String),
+    #[error("block id 0 must be air")]
+    Air,
+}
+
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct BlockId(pub u16);
+
+impl BlockId {
+    pub const AIR: BlockId = BlockId(0);
+}
+
+#[derive(Debug)]
+pub struct BlockDef {
+    pub name: String,
+    pub id: BlockId,
+    pub solid: bool,
+    pub opaque: bool,
+    pub light: u8,
+    pub hardness: f32,
+    pub textures: [u16; 6],
+    /// Colormap tinting each face, if any
+    pub tints: [Option<Colormap>; 6],
+    /// What the block does when ticked, if anything
+    pub behavior: Option<Behavior>,
+    pub properties: Vec<PropertyDef>,
+    pub default_state: BlockState,
+}
+
+impl BlockDef {
+    pub fn texture(&self, face: Face) -> u16 {
+        self.textures[face as usize]
+    }
+
+    pub fn tint(&self, face: Face) -> Option<Colormap> {
+        self.tints[face as usize]
+    }
+
+    /// Returns the texture of a face, rotated so that blocks with an `axis`
+    /// property show their top and bottom textures along that axis.
+    pub fn texture_for(&self, state: BlockState, face: Face) -> u16 {
+        let axis = match self.get(state, "axis") {
+            Some(PropertyValue::Axis(axis)) => axis,
+            _ => return self.texture(face),
+        };
+
+        let face = match (axis, face) {
+            (Axis::Y, face) => face,
+            (_, face) if face.axis() == axis => {
+                if face.normal().max_element() > 0 {
+                    Face::Up
+                } else {
+                    Face::Down
+                }
+            }
+            (_, Face::Up | Face::Down) => Face::North,
+            (_, face) => face,
+        };
+
+        self.texture(face)
+    }
+
+    pub fn property(&self, name: &str) -> Option<&PropertyDef> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+
+    pub fn get(&self, state: BlockState, name: &str) -> Option<PropertyValue> {
+        self.property(name)?.get(state)
+    }
+
+    pub fn with(&self, state: BlockState, name: &str, value: PropertyValue) -> Option<BlockState> {
+        self.property(name)?.set(state, value)
+    }
+
+    /// Ticks it takes to dig the block, `None` if it can't be dug
+    pub fn dig_ticks(&self) -> Option<u32> {
+        (self.hardness >= 0.0).then(|| (self.hardness * 1.5 * TICK_RATE as f32).ceil() as u32)
+    }
+
+    pub fn is_valid(&self, state: BlockState) -> bool {
+        let used = self
+            .properties
+            .last()
+            .map_or(0, |property| property.shift + property.bits);
+
+        state.id == self.id
+            && state.data.checked_shr(used as u32).unwrap_or(0) == 0
+            && self
+                .properties
+                .iter()
+                .all(|property| property.is_valid(state))
+    }
+}
+
+/// Block definitions, indexed by id and by name
+pub struct BlockRegistry {
+    blocks: Vec<Option<BlockDef>>,
+    names: HashMap<String, BlockId>,
+}
+
+#[derive(serde::Deserialize)]
+struct RegistryFile {
+    block: Vec<BlockEntry>,
+}
+
+#[derive(serde::Deserialize)]
+struct BlockEntry {
+    name: String,
+    id: u16,
+    #[serde(default = "default_true")]
+    solid: bool,
+    #[serde(default = "default_true")]
+    opaque: bool,
+    #[serde(default)]
+    light: u8,
+    #[serde(default)]
+    hardness: f32,
+    #[serde(default)]
+    texture: TextureEntry,
+    tint: Option<TintEntry>,
+    behavior: Option<Behavior>,
+    #[serde(default)]
+    properties: Vec<PropertyEntry>,
+}
+
+#[derive(serde::Deserialize)]
+struct PropertyEntry {
+    name: String,
+    #[serde(flatten)]
+    kind: PropertyKind,
+    default: Option<toml::Value>,
+}
+
+#[derive(serde::Deserialize, Default)]
+struct TextureEntry {
+    all: Option<u16>,
+    side: Option<u16>,
+    top: Option<u16>,
+    bottom: Option<u16>,
+    north: Option<u16>,
+    south: Option<u16>,
+    west: Option<u16>,
+    east: Option<u16>,
+}
+
+#[derive(serde::Deserialize)]
+struct TintEntry {
+    colormap: Colormap,
+    /// Tinted faces, all of them if empty
+    #[serde(default)]
+    faces: Vec<FaceEntry>,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum FaceEntry {
+    All,
+    Side,
+    Top,
+    Bottom,
+    North,
+    South,
+    West,
+    East,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl TextureEntry {
+    fn resolve(&self) -> [u16; 6] {
+        let all = self.all.unwrap_or_default();
+        let side = self.side.unwrap_or(all);
+
+        [
+            self.bottom.unwrap_or(all),
+            self.top.unwrap_or(all),
+            self.north.unwrap_or(side),
+            self.south.unwrap_or(side),
+            self.west.unwrap_or(side),
+            self.east.unwrap_or(side),
+        ]
+    }
+}
+
+impl TintEntry {
+    fn resolve(&self) -> [Option<Colormap>; 6] {
+        Face::ALL.map(|face| {
+            let tinted = self.faces.is_empty()
+                || self.faces.iter().any(|entry| match entry {
+                    FaceEntry::All => true,
+                    FaceEntry::Side => face.axis() != Axis::Y,
+                    FaceEntry::Top => face == Face::Up,
+                    FaceEntry::Bottom => face == Face::Down,
+                    FaceEntry::North => face == Face::North,
+                    FaceEntry::South => face == Face::South,
+                    FaceEntry::West => face == Face::West,
+                    FaceEntry::East => face == Face::East,
+                });
+
+            tinted.then_some(self.colormap)
+        })
+    }
+}
+
+impl BlockEntry {
+    fn properties(&self) -> Result<(Vec<PropertyDef>, BlockState), Error> {
+        let mut properties: Vec<PropertyDef> = Vec::new();
+        let mut state = BlockState::new(BlockId(self.id));
+        let mut shift = 0;
+
+        for entry in &self.properties {
+            let invalid = || Error::Property(self.name.clone(), entry.name.clone());
+
+            if properties
+                .iter()
+                .any(|property| property.name == entry.name)
+            {
+                return Err(invalid());
+            }
+
+            if let PropertyKind::Int { min, max } = entry.kind {
+                if min > max {
+                    return Err(invalid());
+                }
+            }
+
+            let bits = (u16::BITS - (entry.kind.count() - 1).leading_zeros()) as u8;
+
+            if shift + bits > u16::BITS as u8 {
+                return Err(invalid());
+            }
+
+            let property = PropertyDef {
+                name: entry.name.clone(),
+                kind: entry.kind,
+                shift,
+                bits,
+            };
+
+            let default = match &entry.default {
+                Some(value) => entry.kind.parse(value).ok_or_else(invalid)?,
+                None => entry.kind.value(0).ok_or_else(invalid)?,
+            };
+
+            state = property.set(state, default).ok_or_else(invalid)?;
+            shift += bits;
+            properties.push(property);
+        }
+
+        Ok((properties, state))
+    }
+}
+
+impl BlockRegistry {
+    /// Loads the block definitions bundled with the game
+    pub fn builtin() -> Result<Self, Error> {
+        Self::load(BUILTIN_BLOCKS)
+    }
+
+    pub fn load(source: &str) -> Result<Self, Error> {
+        let file: RegistryFile = toml::from_str(source)?;
+
+        let mut blocks: Vec<Option<BlockDef>> = Vec::new();
+        let mut names = HashMap::new();
+
+        for entry in file.block {
+            let id = entry.id as usize;
+
+            if entry.light > 15 {
+                return Err(Error::Light(entry.name));
+            }
+
+            let textures = entry.texture.resolve();
+
+            if textures
+                .iter()
+                .any(|tile| *tile >= ATLAS_TILES * ATLAS_TILES)
+            {
+                return Err(Error::Texture(entry.name));
+            }
+
+            if names.contains_key(&entry.name) {
+                return Err(Error::DuplicateName(entry.name));
+            }
+
+            if blocks.len() <= id {
+                blocks.resize_with(id + 1, || None);
+            } else if blocks[id].is_some() {
+                return Err(Error::DuplicateId(entry.id));
+            }
+
+            let (properties, default_state) = entry.properties()?;
+
+            names.insert(entry.name.clone(), BlockId(entry.id));
+            blocks[id] = Some(BlockDef {
+                textures,
+                tints: entry.tint.as_ref().map_or([None; 6], |tint| tint.resolve()),
+                name: entry.name,
+                id: BlockId(entry.id),
+                solid: entry.solid,
+                opaque: entry.opaque,
+                light: entry.light,
+                hardness: entry.hardness,
+                behavior: entry.behavior,
+                properties,
+                default_state,
+            });
+        }
+
+        match blocks.first() {
+            Some(Some(air)) if air.name == "air" => {}
+            _ => return Err(Error::Air),
+        }
+
+        Ok(Self { blocks, names })
+    }
+
+    pub fn get(&self, id: BlockId) -> Option<&BlockDef> {
+        self.blocks.get(id.0 as usize)?.as_ref()
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&BlockDef> {
+        self.get(*self.names.get(name)?)
+    }
+
+    pub fn id(&self, name: &str) -> Option<BlockId> {
+        self.names.get(name).copied()
+    }
+
+    pub fn contains(&self, id: BlockId) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn default_state(&self, id: BlockId) -> Option<BlockState> {
+        self.get(id).map(|def| def.default_state)
+    }
+
+    pub fn default_state_by_name(&self, name: &str) -> Option<BlockState> {
+        self.get_by_name(name).map(|def| def.default_state)
+    }
+
+    /// Checks that a state refers to a known block and only holds values its
+    /// properties allow
+    pub fn is_valid(&self, state: BlockState) -> bool {
+        self.get(state.id).is_some_and(|def| def.is_valid(state))
+    }
+
+    pub fn is_solid(&self, id: BlockId) -> bool {
+        self.get(id).is_some_and(|def| def.solid)
+    }
+
+    pub fn is_opaque(&self, id: BlockId) -> bool {
+        self.get(id).is_some_and(|def| def.opaque)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &BlockDef> {
+        self.blocks.iter().flatten()
+    }
+}
@@ -0,0 +1,507 @@
+use crate::TICK_RATE;
+
+use super::{
+    light, BlockId, BlockPos, BlockRegistry, BlockState, ChunkPos, Face, LocalPos, PropertyValue,
+    World,
+};
+
+/// Level of the weakest flow, the furthest a fluid gets from its source
+pub const MAX_LEVEL: u8 = 7;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("missing block {0:?}")]
+    MissingBlock(&'static str),
+    #[error("block {0:?} is missing a level or falling property")]
+    Property(&'static str),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Fluid {
+    Water,
+    Lava,
+}
+
+impl Fluid {
+    /// Ticks between two steps of a flow
+    pub fn delay(self) -> u64 {
+        match self {
+            Fluid::Water => TICK_RATE as u64 / 4,
+            Fluid::Lava => TICK_RATE as u64 * 3 / 2,
+        }
+    }
+
+    /// Levels lost for every block flowed sideways
+    pub fn drop(self) -> u8 {
+        match self {
+            Fluid::Water => 1,
+            Fluid::Lava => 2,
+        }
+    }
+
+    /// How far a flow looks around for a way down
+    pub fn slope_distance(self) -> u32 {
+        match self {
+            Fluid::Water => 4,
+            Fluid::Lava => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FluidState {
+    pub fluid: Fluid,
+    /// 0 for a source, growing by the fluid's drop as it flows away
+    pub level: u8,
+    /// Fed from above, which keeps the flow at full strength
+    pub falling: bool,
+}
+
+impl FluidState {
+    pub fn source(fluid: Fluid) -> Self {
+        Self {
+            fluid,
+            level: 0,
+            falling: false,
+        }
+    }
+
+    pub fn is_source(self) -> bool {
+        self.level == 0 && !self.falling
+    }
+
+    fn falling(fluid: Fluid) -> Self {
+        Self {
+            fluid,
+            level: 0,
+            falling: true,
+        }
+    }
+}
+
+/// Flowing water and lava
+///
+/// Fluid blocks get a scheduled tick a fixed delay after they or a neighbor
+/// changed. Each update first fixes the block's own level from its neighbors,
+/// then flows down if it can, and otherwise sideways toward the nearest drop.
+pub struct FluidSim {
+    water: BlockId,
+    lava: BlockId,
+    stone: BlockState,
+    cobblestone: BlockState,
+    obsidian: BlockState,
+}
+
+impl FluidSim {
+    pub fn new(registry: &BlockRegistry) -> Result<Self, Error> {
+        let block = |name| {
+            registry
+                .default_state_by_name(name)
+                .ok_or(Error::MissingBlock(name))
+        };
+
+        let fluid = |name| {
+            let def = registry
+                .get_by_name(name)
+                .ok_or(Error::MissingBlock(name))?;
+
+            let valid = [0, MAX_LEVEL].into_iter().all(|level| {
+                def.with(def.default_state, "level", PropertyValue::Int(level))
+                    .and_then(|state| def.with(state, "falling", PropertyValue::Bool(true)))
+                    .is_some()
+            });
+
+            valid.then_some(def.id).ok_or(Error::Property(name))
+        };
+
+        Ok(Self {
+            water: fluid("water")?,
+            lava: fluid("lava")?,
+            stone: block("stone")?,
+            cobblestone: block("cobblestone")?,
+            obsidian: block("obsidian")?,
+        })
+    }
+
+    pub fn get(&self, registry: &BlockRegistry, state: BlockState) -> Option<FluidState> {
+        let fluid = if state.id == self.water {
+            Fluid::Water
+        } else if state.id == self.lava {
+            Fluid::Lava
+        } else {
+            return None;
+        };
+
+        let def = registry.get(state.id)?;
+
+        let Some(PropertyValue::Int(level)) = def.get(state, "level") else {
+            return None;
+        };
+        let Some(PropertyValue::Bool(falling)) = def.get(state, "falling") else {
+            return None;
+        };
+
+        Some(FluidState {
+            fluid,
+            level,
+            falling,
+        })
+    }
+
+    pub fn state(&self, registry: &BlockRegistry, fluid: FluidState) -> BlockState {
+        let id = match fluid.fluid {
+            Fluid::Water => self.water,
+            Fluid::Lava => self.lava,
+        };
+
+        // Both properties were checked in `new`
+        let def = registry.get(id).unwrap();
+
+        def.with(def.default_state, "level", PropertyValue::Int(fluid.level))
+            .and_then(|state| def.with(state, "falling", PropertyValue::Bool(fluid.falling)))
+            .unwrap()
+    }
+
+    /// Schedules updates for the fluids at and around `pos`, to be called
+    /// after the block there was changed
+    pub fn notify(&self, world: &mut World, registry: &BlockRegistry, pos: BlockPos) {
+        self.schedule(world, registry, pos);
+
+        for face in Face::ALL {
+            self.schedule(world, registry, pos.neighbor(face));
+        }
+    }
+
+    /// Schedules updates for every fluid of a chunk, to be called after it was
+    /// loaded
+    pub fn notify_chunk(&self, world: &mut World, registry: &BlockRegistry, pos: ChunkPos) {
+        let Some(chunk) = world.get_chunk(pos) else {
+            return;
+        };
+
+        let fluids = LocalPos::iter()
+            .filter(|local| self.get(registry, chunk.get(*local)).is_some())
+            .map(|local| pos.block(local))
+            .collect::<Vec<_>>();
+
+        for block in fluids {
+            self.schedule(world, registry, block);
+        }
+    }
+
+    fn schedule(&self, world: &mut World, registry: &BlockRegistry, pos: BlockPos) {
+        let Some(fluid) = world
+            .get_block(pos)
+            .and_then(|state| self.get(registry, state))
+        else {
+            return;
+        };
+
+        world.ticks_mut().schedule(pos, fluid.fluid.delay());
+    }
+
+    fn fluid_at(
+        &self,
+        world: &World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+    ) -> Option<FluidState> {
+        self.get(registry, world.get_block(pos)?)
+    }
+
+    fn set(
+        &self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        state: BlockState,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        if light::set_block(world, registry, pos, state) {
+            changes.push((pos, state));
+            self.notify(world, registry, pos);
+        }
+    }
+
+    /// Runs the scheduled tick of a fluid block
+    pub fn update(
+        &self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        let Some(current) = self.fluid_at(world, registry, pos) else {
+            return;
+        };
+
+        if current.fluid == Fluid::Lava {
+            let touches_water = [Face::Up, Face::North, Face::South, Face::West, Face::East]
+                .into_iter()
+                .any(|face| {
+                    world
+                        .get_block(pos.neighbor(face))
+                        .is_some_and(|state| state.id == self.water)
+                });
+
+            if touches_water {
+                let block = if current.is_source() {
+                    self.obsidian
+                } else {
+                    self.cobblestone
+                };
+
+                self.set(world, registry, pos, block, changes);
+                return;
+            }
+        }
+
+        let fluid = if current.is_source() {
+            current
+        } else {
+            match self.expected(world, registry, pos, current.fluid) {
+                Some(expected) => expected,
+                None => {
+                    self.set(world, registry, pos, BlockState::AIR, changes);
+                    return;
+                }
+            }
+        };
+
+        if fluid != current {
+            let state = self.state(registry, fluid);
+            self.set(world, registry, pos, state, changes);
+        }
+
+        self.spread(world, registry, pos, fluid, changes);
+    }
+
+    /// Level a flowing block should have from its neighbors, `None` if nothing
+    /// feeds it anymore
+    fn expected(
+        &self,
+        world: &World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        fluid: Fluid,
+    ) -> Option<FluidState> {
+        if self
+            .fluid_at(world, registry, pos.neighbor(Face::Up))
+            .is_some_and(|above| above.fluid == fluid)
+        {
+            return Some(FluidState::falling(fluid));
+        }
+
+        let mut sources = 0;
+        let mut strongest = None;
+
+        for face in Face::HORIZONTAL {
+            let Some(neighbor) = self.fluid_at(world, registry, pos.neighbor(face)) else {
+                continue;
+            };
+
+            if neighbor.fluid != fluid {
+                continue;
+            }
+
+            if neighbor.is_source() {
+                sources += 1;
+            }
+
+            let level = if neighbor.falling { 0 } else { neighbor.level };
+            strongest = Some(strongest.map_or(level, |strongest: u8| strongest.min(level)));
+        }
+
+        // Water between two sources refills, as long as it has ground to sit on
+        if fluid == Fluid::Water && sources >= 2 {
+            let below = pos.neighbor(Face::Down);
+            let grounded = world
+                .get_block(below)
+                .is_some_and(|state| registry.is_solid(state.id))
+                || self
+                    .fluid_at(world, registry, below)
+                    .is_some_and(|below| below.fluid == fluid && below.is_source());
+
+            if grounded {
+                return Some(FluidState::source(fluid));
+            }
+        }
+
+        let level = strongest? + fluid.drop();
+
+        (level <= MAX_LEVEL).then_some(FluidState {
+            fluid,
+            level,
+            falling: false,
+        })
+    }
+
+    fn spread(
+        &self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        fluid: FluidState,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        let below = pos.neighbor(Face::Down);
+        let falls = self.flow_into(
+            world,
+            registry,
+            below,
+            FluidState::falling(fluid.fluid),
+            changes,
+        );
+
+        // Flows only spread sideways once they hit the ground
+        if falls && !fluid.is_source() {
+            return;
+        }
+
+        let level = if fluid.falling { 0 } else { fluid.level } + fluid.fluid.drop();
+
+        if level > MAX_LEVEL {
+            return;
+        }
+
+        let next = FluidState {
+            fluid: fluid.fluid,
+            level,
+            falling: false,
+        };
+
+        for face in self.directions(world, registry, pos, fluid.fluid) {
+            self.flow_into(world, registry, pos.neighbor(face), next, changes);
+        }
+    }
+
+    /// Flows `fluid` into `pos`, returning whether it's now there
+    fn flow_into(
+        &self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        fluid: FluidState,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) -> bool {
+        let Some(target) = world.get_block(pos) else {
+            return false;
+        };
+
+        if target == BlockState::AIR {
+            let state = self.state(registry, fluid);
+            self.set(world, registry, pos, state, changes);
+            return true;
+        }
+
+        let Some(existing) = self.get(registry, target) else {
+            return false;
+        };
+
+        if existing.fluid != fluid.fluid {
+            let block = match fluid.fluid {
+                Fluid::Water if existing.is_source() => self.obsidian,
+                Fluid::Water => self.cobblestone,
+                Fluid::Lava => self.stone,
+            };
+
+            self.set(world, registry, pos, block, changes);
+            return false;
+        }
+
+        if existing.is_source() {
+            return false;
+        }
+
+        let stronger = !existing.falling && (fluid.falling || fluid.level < existing.level);
+
+        if stronger {
+            let state = self.state(registry, fluid);
+            self.set(world, registry, pos, state, changes);
+        }
+
+        true
+    }
+
+    /// Sides to flow toward: those closest to a way down, or all open sides
+    /// if there's none nearby
+    fn directions(
+        &self,
+        world: &World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        fluid: Fluid,
+    ) -> Vec<Face> {
+        let mut best = u32::MAX;
+        let mut faces = Vec::new();
+
+        for face in Face::HORIZONTAL {
+            let next = pos.neighbor(face);
+
+            if !self.is_open(world, registry, next, fluid) {
+                continue;
+            }
+
+            let distance = self.drop_distance(world, registry, next, fluid, face.opposite(), 1);
+
+            if distance < best {
+                best = distance;
+                faces.clear();
+            }
+
+            if distance == best {
+                faces.push(face);
+            }
+        }
+
+        faces
+    }
+
+    /// Shortest number of sideways steps from `pos` to a block the fluid can
+    /// fall from, `u32::MAX` if none is within the fluid's slope distance
+    fn drop_distance(
+        &self,
+        world: &World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        fluid: Fluid,
+        from: Face,
+        depth: u32,
+    ) -> u32 {
+        if self.is_open(world, registry, pos.neighbor(Face::Down), fluid) {
+            return depth;
+        }
+
+        if depth >= fluid.slope_distance() {
+            return u32::MAX;
+        }
+
+        Face::HORIZONTAL
+            .into_iter()
+            .filter(|face| *face != from)
+            .map(|face| (face, pos.neighbor(face)))
+            .filter(|(_, next)| self.is_open(world, registry, *next, fluid))
+            .map(|(face, next)| {
+                self.drop_distance(world, registry, next, fluid, face.opposite(), depth + 1)
+            })
+            .min()
+            .unwrap_or(u32::MAX)
+    }
+
+    /// Whether `fluid` can flow into `pos`
+    fn is_open(
+        &self,
+        world: &World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        fluid: Fluid,
+    ) -> bool {
+        match world.get_block(pos) {
+            Some(BlockState::AIR) => true,
+            Some(state) => self
+                .get(registry, state)
+                .is_some_and(|existing| existing.fluid != fluid || !existing.is_source()),
+            None => false,
+        }
+    }
+}
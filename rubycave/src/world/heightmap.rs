@@ -0,0 +1,85 @@
+use super::{Behavior, BlockRegistry, BlockState, CHUNK_HEIGHT, CHUNK_LENGTH, CHUNK_WIDTH};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HeightmapKind {
+    /// Blocks that stop sky light
+    Opaque,
+    /// Blocks that stop entities or fluids
+    MotionBlocking,
+    NonAir,
+}
+
+impl HeightmapKind {
+    pub const ALL: [Self; 3] = [Self::Opaque, Self::MotionBlocking, Self::NonAir];
+
+    pub fn matches(self, registry: &BlockRegistry, state: BlockState) -> bool {
+        match self {
+            Self::Opaque => registry.is_opaque(state.id),
+            Self::MotionBlocking => {
+                registry.is_solid(state.id)
+                    || registry.get(state.id).and_then(|def| def.behavior) == Some(Behavior::Fluid)
+            }
+            Self::NonAir => state != BlockState::AIR,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Which heights of each column of a chunk hold a matching block, one bit per
+/// block so that removing the top block finds the next one right away
+#[derive(Clone, Debug)]
+pub struct Heightmap {
+    columns: [u16; CHUNK_WIDTH * CHUNK_LENGTH],
+}
+
+const _: () = assert!(CHUNK_HEIGHT <= u16::BITS as usize);
+
+impl Heightmap {
+    pub fn new() -> Self {
+        Self {
+            columns: [0; CHUNK_WIDTH * CHUNK_LENGTH],
+        }
+    }
+
+    /// Local height of the highest matching block in `column`
+    pub fn get(&self, column: usize) -> Option<i32> {
+        let bits = self.columns[column];
+        (bits != 0).then(|| (u16::BITS - 1 - bits.leading_zeros()) as i32)
+    }
+
+    pub fn set(&mut self, column: usize, y: i32, matches: bool) {
+        if matches {
+            self.columns[column] |= 1 << y;
+        } else {
+            self.columns[column] &= !(1 << y);
+        }
+    }
+}
+
+impl Default for Heightmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One [`Heightmap`] of each kind
+#[derive(Clone, Default, Debug)]
+pub struct Heightmaps {
+    maps: [Heightmap; 3],
+}
+
+impl Heightmaps {
+    pub fn get(&self, kind: HeightmapKind) -> &Heightmap {
+        &self.maps[kind.index()]
+    }
+
+    /// Updates every kind for the block at local `y` of `column`
+    pub fn update(&mut self, registry: &BlockRegistry, column: usize, y: i32, state: BlockState) {
+        for kind in HeightmapKind::ALL {
+            self.maps[kind.index()].set(column, y, kind.matches(registry, state));
+        }
+    }
+}
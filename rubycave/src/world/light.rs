@@ -0,0 +1,323 @@
+use std::collections::VecDeque;
+
+use rkyv::{Archive, Deserialize, Serialize};
+
+use super::{
+    BlockPos, BlockRegistry, BlockState, ChunkPos, Face, HeightmapKind, LocalPos, World,
+    CHUNK_HEIGHT, CHUNK_LENGTH, CHUNK_VOLUME, CHUNK_WIDTH,
+};
+
+pub const MAX_LIGHT: u8 = 15;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum LightKind {
+    Block,
+    Sky,
+}
+
+/// Light levels of a chunk, packed two per byte
+#[derive(Archive, Deserialize, Serialize, Clone, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct LightArray {
+    data: Vec<u8>,
+}
+
+impl LightArray {
+    pub fn new() -> Self {
+        Self::filled(0)
+    }
+
+    pub fn filled(level: u8) -> Self {
+        Self {
+            data: vec![(level & 0xf) * 0x11; CHUNK_VOLUME / 2],
+        }
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        (self.data[index / 2] >> ((index % 2) * 4)) & 0xf
+    }
+
+    pub fn set(&mut self, index: usize, level: u8) {
+        let shift = (index % 2) * 4;
+        let byte = &mut self.data[index / 2];
+
+        *byte = (*byte & !(0xf << shift)) | ((level & 0xf) << shift);
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.data.len() == CHUNK_VOLUME / 2
+    }
+}
+
+impl Default for LightArray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn emission(registry: &BlockRegistry, kind: LightKind, state: BlockState) -> u8 {
+    match kind {
+        LightKind::Block => registry.get(state.id).map_or(0, |def| def.light),
+        LightKind::Sky => 0,
+    }
+}
+
+fn is_opaque(world: &World, registry: &BlockRegistry, pos: BlockPos) -> Option<bool> {
+    world
+        .get_block(pos)
+        .map(|state| registry.is_opaque(state.id))
+}
+
+/// Light passed from a block at `level` to its neighbor on `face`. Direct sky
+/// light travels down without decaying.
+fn falloff(kind: LightKind, face: Face, level: u8) -> u8 {
+    if kind == LightKind::Sky && face == Face::Down && level == MAX_LIGHT {
+        MAX_LIGHT
+    } else {
+        level.saturating_sub(1)
+    }
+}
+
+fn spread(
+    world: &mut World,
+    registry: &BlockRegistry,
+    kind: LightKind,
+    mut queue: VecDeque<BlockPos>,
+) {
+    while let Some(pos) = queue.pop_front() {
+        let Some(level) = world.get_light(kind, pos) else {
+            continue;
+        };
+
+        for face in Face::ALL {
+            let neighbor = pos.neighbor(face);
+            let new = falloff(kind, face, level);
+
+            if new == 0 || is_opaque(world, registry, neighbor) != Some(false) {
+                continue;
+            }
+
+            if world.get_light(kind, neighbor).is_some_and(|old| old < new) {
+                world.set_light(kind, neighbor, new);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+}
+
+/// Clears light that depended on the queued blocks and returns the blocks that
+/// have to spread their light again.
+fn unspread(
+    world: &mut World,
+    registry: &BlockRegistry,
+    kind: LightKind,
+    mut queue: VecDeque<(BlockPos, u8)>,
+) -> VecDeque<BlockPos> {
+    let mut relight = VecDeque::new();
+
+    while let Some((pos, level)) = queue.pop_front() {
+        for face in Face::ALL {
+            let neighbor = pos.neighbor(face);
+
+            let Some(neighbor_level) = world.get_light(kind, neighbor) else {
+                continue;
+            };
+
+            if neighbor_level == 0 {
+                continue;
+            }
+
+            if neighbor_level < level || falloff(kind, face, level) == neighbor_level {
+                world.set_light(kind, neighbor, 0);
+                queue.push_back((neighbor, neighbor_level));
+
+                let state = world.get_block(neighbor).unwrap_or_default();
+                let emitted = emission(registry, kind, state);
+
+                if emitted > 0 {
+                    world.set_light(kind, neighbor, emitted);
+                    relight.push_back(neighbor);
+                }
+            } else {
+                relight.push_back(neighbor);
+            }
+        }
+    }
+
+    relight
+}
+
+fn border(pos: ChunkPos, face: Face) -> impl Iterator<Item = (BlockPos, BlockPos)> {
+    LocalPos::iter()
+        .filter(move |local| local.neighbor(face).is_none())
+        .map(move |local| {
+            let block = pos.block(local);
+            (block, block.neighbor(face))
+        })
+}
+
+/// Computes the heightmaps and light of a chunk that was just inserted into the
+/// world and spreads the light across the borders of its loaded neighbors.
+pub fn init_chunk(world: &mut World, registry: &BlockRegistry, pos: ChunkPos) {
+    let Some(chunk) = world.get_chunk_mut(pos) else {
+        return;
+    };
+
+    chunk.compute_heightmaps(registry);
+
+    init_sky(world, registry, pos);
+    init_block(world, registry, pos);
+}
+
+fn init_block(world: &mut World, registry: &BlockRegistry, pos: ChunkPos) {
+    let kind = LightKind::Block;
+    let mut queue = VecDeque::new();
+
+    for local in LocalPos::iter() {
+        let block = pos.block(local);
+        let emitted = emission(registry, kind, world.get_block(block).unwrap_or_default());
+
+        world.set_light(kind, block, emitted);
+
+        if emitted > 0 {
+            queue.push_back(block);
+        }
+    }
+
+    for face in Face::ALL {
+        for (_, outside) in border(pos, face) {
+            if world
+                .get_light(kind, outside)
+                .is_some_and(|level| level > 0)
+            {
+                queue.push_back(outside);
+            }
+        }
+    }
+
+    spread(world, registry, kind, queue);
+}
+
+fn init_sky(world: &mut World, registry: &BlockRegistry, pos: ChunkPos) {
+    let kind = LightKind::Sky;
+    let above = pos.neighbor(Face::Up);
+    let mut queue = VecDeque::new();
+
+    for local in LocalPos::iter() {
+        world.set_light(kind, pos.block(local), 0);
+    }
+
+    for z in 0..CHUNK_LENGTH as i32 {
+        for x in 0..CHUNK_WIDTH as i32 {
+            let top = pos.block(LocalPos::new(x, CHUNK_HEIGHT as i32 - 1, z));
+
+            // Columns under unloaded chunks are assumed to see the sky
+            let exposed = !world.is_loaded(above)
+                || world.get_light(kind, top.neighbor(Face::Up)) == Some(MAX_LIGHT);
+
+            if !exposed {
+                continue;
+            }
+
+            let bottom = world
+                .get_chunk(pos)
+                .and_then(|chunk| chunk.get_height(HeightmapKind::Opaque, LocalPos::new(x, 0, z)))
+                .map_or(0, |height| height + 1);
+
+            for y in bottom..CHUNK_HEIGHT as i32 {
+                let block = pos.block(LocalPos::new(x, y, z));
+
+                world.set_light(kind, block, MAX_LIGHT);
+                queue.push_back(block);
+            }
+        }
+    }
+
+    // Columns below that assumed this chunk was open sky
+    let mut removed = VecDeque::new();
+
+    for (inside, below) in border(pos, Face::Down) {
+        if world.get_light(kind, below) == Some(MAX_LIGHT)
+            && world.get_light(kind, inside) != Some(MAX_LIGHT)
+        {
+            world.set_light(kind, below, 0);
+            removed.push_back((below, MAX_LIGHT));
+        }
+    }
+
+    queue.extend(unspread(world, registry, kind, removed));
+
+    for face in Face::ALL {
+        for (_, outside) in border(pos, face) {
+            if world
+                .get_light(kind, outside)
+                .is_some_and(|level| level > 0)
+            {
+                queue.push_back(outside);
+            }
+        }
+    }
+
+    spread(world, registry, kind, queue);
+}
+
+/// Updates both light kinds after the block at `pos` was replaced
+pub fn update_block(world: &mut World, registry: &BlockRegistry, pos: BlockPos) {
+    for kind in [LightKind::Block, LightKind::Sky] {
+        update_kind(world, registry, kind, pos);
+    }
+}
+
+fn update_kind(world: &mut World, registry: &BlockRegistry, kind: LightKind, pos: BlockPos) {
+    let Some(state) = world.get_block(pos) else {
+        return;
+    };
+    let Some(old) = world.get_light(kind, pos) else {
+        return;
+    };
+
+    world.set_light(kind, pos, 0);
+
+    let mut relight = unspread(world, registry, kind, VecDeque::from([(pos, old)]));
+    let opaque = registry.is_opaque(state.id);
+    let emitted = emission(registry, kind, state);
+
+    let exposed =
+        kind == LightKind::Sky && !opaque && !world.is_loaded(pos.neighbor(Face::Up).chunk());
+
+    if exposed || emitted > 0 {
+        world.set_light(kind, pos, if exposed { MAX_LIGHT } else { emitted });
+        relight.push_back(pos);
+    }
+
+    if !opaque {
+        for face in Face::ALL {
+            let neighbor = pos.neighbor(face);
+
+            if world
+                .get_light(kind, neighbor)
+                .is_some_and(|level| level > 0)
+            {
+                relight.push_back(neighbor);
+            }
+        }
+    }
+
+    spread(world, registry, kind, relight);
+}
+
+/// Sets a block and updates the light around it
+pub fn set_block(
+    world: &mut World,
+    registry: &BlockRegistry,
+    pos: BlockPos,
+    state: BlockState,
+) -> bool {
+    if !world.set_block(registry, pos, state) {
+        return false;
+    }
+
+    update_block(world, registry, pos);
+    true
+}
@@ -0,0 +1,291 @@
+use std::ops::{Add, Sub};
+
+use glam::IVec3;
+use rkyv::{Archive, Deserialize, Serialize};
+
+use super::{CHUNK_HEIGHT, CHUNK_LENGTH, CHUNK_WIDTH, MAX_COORD};
+
+const WIDTH: i32 = CHUNK_WIDTH as i32;
+const LENGTH: i32 = CHUNK_LENGTH as i32;
+const HEIGHT: i32 = CHUNK_HEIGHT as i32;
+
+/// Absolute block position in the world
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct BlockPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// Position of a chunk, in chunks
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct ChunkPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// Side of a block, in the order used for per-face data
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum Face {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// Block position relative to the origin of its chunk
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct LocalPos {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+impl BlockPos {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn chunk(self) -> ChunkPos {
+        ChunkPos::new(
+            self.x.div_euclid(WIDTH),
+            self.y.div_euclid(HEIGHT),
+            self.z.div_euclid(LENGTH),
+        )
+    }
+
+    pub fn local(self) -> LocalPos {
+        LocalPos {
+            x: self.x.rem_euclid(WIDTH),
+            y: self.y.rem_euclid(HEIGHT),
+            z: self.z.rem_euclid(LENGTH),
+        }
+    }
+
+    pub fn split(self) -> (ChunkPos, LocalPos) {
+        (self.chunk(), self.local())
+    }
+
+    pub fn offset(self, x: i32, y: i32, z: i32) -> Self {
+        Self::new(self.x + x, self.y + y, self.z + z)
+    }
+
+    pub fn neighbor(self, face: Face) -> Self {
+        self + face.normal()
+    }
+
+    /// Whether it's within [`MAX_COORD`] of the origin along every axis
+    pub fn is_in_bounds(self) -> bool {
+        [self.x, self.y, self.z]
+            .iter()
+            .all(|axis| (-MAX_COORD..=MAX_COORD).contains(axis))
+    }
+}
+
+impl ChunkPos {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn origin(self) -> BlockPos {
+        BlockPos::new(self.x * WIDTH, self.y * HEIGHT, self.z * LENGTH)
+    }
+
+    pub fn block(self, local: LocalPos) -> BlockPos {
+        self.origin().offset(local.x, local.y, local.z)
+    }
+
+    pub fn offset(self, x: i32, y: i32, z: i32) -> Self {
+        Self::new(self.x + x, self.y + y, self.z + z)
+    }
+
+    pub fn neighbor(self, face: Face) -> Self {
+        self + face.normal()
+    }
+
+    /// Whether every block in it is in bounds
+    pub fn is_in_bounds(self) -> bool {
+        let in_bounds =
+            |axis: i32, size: i32| (-MAX_COORD / size..MAX_COORD / size).contains(&axis);
+
+        in_bounds(self.x, WIDTH) && in_bounds(self.y, HEIGHT) && in_bounds(self.z, LENGTH)
+    }
+}
+
+impl LocalPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        assert!((0..WIDTH).contains(&x) && (0..HEIGHT).contains(&y) && (0..LENGTH).contains(&z));
+
+        Self { x, y, z }
+    }
+
+    pub fn try_new(x: i32, y: i32, z: i32) -> Option<Self> {
+        ((0..WIDTH).contains(&x) && (0..HEIGHT).contains(&y) && (0..LENGTH).contains(&z))
+            .then_some(Self { x, y, z })
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        assert!(index < CHUNK_WIDTH * CHUNK_LENGTH * CHUNK_HEIGHT);
+
+        let index = index as i32;
+
+        Self {
+            x: index % WIDTH,
+            y: index / (WIDTH * LENGTH),
+            z: index / WIDTH % LENGTH,
+        }
+    }
+
+    /// Returns `None` if the neighbor lies in another chunk
+    pub fn neighbor(self, face: Face) -> Option<Self> {
+        let normal = face.normal();
+        Self::try_new(self.x + normal.x, self.y + normal.y, self.z + normal.z)
+    }
+
+    pub fn index(self) -> usize {
+        ((self.y * LENGTH + self.z) * WIDTH + self.x) as usize
+    }
+
+    /// Index of the column in a chunk, ignoring the height
+    pub fn column(self) -> usize {
+        (self.z * WIDTH + self.x) as usize
+    }
+
+    pub fn x(self) -> i32 {
+        self.x
+    }
+
+    pub fn y(self) -> i32 {
+        self.y
+    }
+
+    pub fn z(self) -> i32 {
+        self.z
+    }
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        (0..CHUNK_WIDTH * CHUNK_LENGTH * CHUNK_HEIGHT).map(Self::from_index)
+    }
+}
+
+impl Face {
+    pub const ALL: [Face; 6] = [
+        Face::Down,
+        Face::Up,
+        Face::North,
+        Face::South,
+        Face::West,
+        Face::East,
+    ];
+    pub const HORIZONTAL: [Face; 4] = [Face::North, Face::South, Face::West, Face::East];
+
+    pub fn normal(self) -> IVec3 {
+        match self {
+            Face::Down => IVec3::NEG_Y,
+            Face::Up => IVec3::Y,
+            Face::North => IVec3::NEG_Z,
+            Face::South => IVec3::Z,
+            Face::West => IVec3::NEG_X,
+            Face::East => IVec3::X,
+        }
+    }
+
+    pub fn axis(self) -> Axis {
+        match self {
+            Face::Down | Face::Up => Axis::Y,
+            Face::North | Face::South => Axis::Z,
+            Face::West | Face::East => Axis::X,
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Face::Down => Face::Up,
+            Face::Up => Face::Down,
+            Face::North => Face::South,
+            Face::South => Face::North,
+            Face::West => Face::East,
+            Face::East => Face::West,
+        }
+    }
+}
+
+impl From<IVec3> for BlockPos {
+    fn from(value: IVec3) -> Self {
+        Self::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<BlockPos> for IVec3 {
+    fn from(value: BlockPos) -> Self {
+        IVec3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<IVec3> for ChunkPos {
+    fn from(value: IVec3) -> Self {
+        Self::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<ChunkPos> for IVec3 {
+    fn from(value: ChunkPos) -> Self {
+        IVec3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<BlockPos> for ChunkPos {
+    fn from(value: BlockPos) -> Self {
+        value.chunk()
+    }
+}
+
+impl From<BlockPos> for LocalPos {
+    fn from(value: BlockPos) -> Self {
+        value.local()
+    }
+}
+
+impl Add<IVec3> for BlockPos {
+    type Output = Self;
+
+    fn add(self, rhs: IVec3) -> Self::Output {
+        self.offset(rhs.x, rhs.y, rhs.z)
+    }
+}
+
+impl Sub<BlockPos> for BlockPos {
+    type Output = IVec3;
+
+    fn sub(self, rhs: BlockPos) -> Self::Output {
+        IVec3::from(self) - IVec3::from(rhs)
+    }
+}
+
+impl Add<IVec3> for ChunkPos {
+    type Output = Self;
+
+    fn add(self, rhs: IVec3) -> Self::Output {
+        self.offset(rhs.x, rhs.y, rhs.z)
+    }
+}
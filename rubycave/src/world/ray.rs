@@ -0,0 +1,132 @@
+use glam::{IVec3, Vec3};
+
+use super::{BlockId, BlockPos, BlockState, Face, World};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RayHit {
+    pub pos: BlockPos,
+    pub state: BlockState,
+    /// Face of the block the ray entered through
+    pub face: Face,
+    pub point: Vec3,
+    pub distance: f32,
+}
+
+/// Walks the voxels along a ray and returns the first non-air block
+pub fn raycast(world: &World, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<RayHit> {
+    raycast_by(world, origin, direction, max_distance, |state| {
+        state.id != BlockId::AIR
+    })
+}
+
+/// Walks the voxels along a ray using a DDA and returns the first loaded block
+/// accepted by `hit`
+pub fn raycast_by(
+    world: &World,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    mut hit: impl FnMut(BlockState) -> bool,
+) -> Option<RayHit> {
+    let direction = direction.try_normalize()?;
+
+    if !origin.is_finite() || !max_distance.is_finite() || max_distance < 0.0 {
+        return None;
+    }
+
+    let mut block = origin.floor().as_ivec3();
+    let step = IVec3::new(sign(direction.x), sign(direction.y), sign(direction.z));
+    let t_delta = Vec3::new(
+        (1.0 / direction.x).abs(),
+        (1.0 / direction.y).abs(),
+        (1.0 / direction.z).abs(),
+    );
+    let mut t_max = Vec3::new(
+        boundary(origin.x, block.x, direction.x),
+        boundary(origin.y, block.y, direction.y),
+        boundary(origin.z, block.z, direction.z),
+    );
+
+    // A ray starting inside a block hits the face it's looking out of
+    let mut face = entry_face(max_axis(direction.abs()), -step);
+    let mut distance = 0.0;
+
+    loop {
+        let pos = BlockPos::from(block);
+
+        if let Some(state) = world.get_block(pos) {
+            if hit(state) {
+                return Some(RayHit {
+                    pos,
+                    state,
+                    face,
+                    point: origin + direction * distance,
+                    distance,
+                });
+            }
+        }
+
+        let axis = min_axis(t_max);
+
+        distance = t_max[axis];
+
+        if distance > max_distance {
+            return None;
+        }
+
+        block[axis] += step[axis];
+        t_max[axis] += t_delta[axis];
+        face = entry_face(axis, step);
+    }
+}
+
+fn sign(x: f32) -> i32 {
+    if x > 0.0 {
+        1
+    } else if x < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+fn boundary(origin: f32, block: i32, direction: f32) -> f32 {
+    if direction > 0.0 {
+        (block as f32 + 1.0 - origin) / direction
+    } else if direction < 0.0 {
+        (origin - block as f32) / -direction
+    } else {
+        f32::INFINITY
+    }
+}
+
+fn min_axis(v: Vec3) -> usize {
+    if v.x <= v.y && v.x <= v.z {
+        0
+    } else if v.y <= v.z {
+        1
+    } else {
+        2
+    }
+}
+
+fn max_axis(v: Vec3) -> usize {
+    if v.x >= v.y && v.x >= v.z {
+        0
+    } else if v.y >= v.z {
+        1
+    } else {
+        2
+    }
+}
+
+fn entry_face(axis: usize, step: IVec3) -> Face {
+    match (axis, step[axis] > 0) {
+        (0, true) => Face::West,
+        (0, false) => Face::East,
+        (1, true) => Face::Down,
+        (1, false) => Face::Up,
+        (_, true) => Face::North,
+        (_, false) => Face::South,
+    }
+}
@@ -0,0 +1,213 @@
+use rkyv::{Archive, Deserialize, Serialize};
+
+use super::{
+    block::BlockId,
+    pos::{Axis, Face},
+};
+
+/// A block id together with its packed property values
+///
+/// Properties are laid out in `data` in declaration order, each taking as many
+/// bits as its number of values needs. The layout is described by the
+/// [`PropertyDef`]s of the block's definition.
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct BlockState {
+    pub id: BlockId,
+    pub data: u16,
+}
+
+impl BlockState {
+    pub const AIR: BlockState = BlockState::new(BlockId::AIR);
+
+    pub const fn new(id: BlockId) -> Self {
+        Self { id, data: 0 }
+    }
+}
+
+impl From<BlockId> for BlockState {
+    fn from(value: BlockId) -> Self {
+        Self::new(value)
+    }
+}
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Color {
+    White,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    Black,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PropertyKind {
+    Bool,
+    Int { min: u8, max: u8 },
+    Facing,
+    HorizontalFacing,
+    Axis,
+    Color,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(u8),
+    Face(Face),
+    Axis(Axis),
+    Color(Color),
+}
+
+#[derive(Debug)]
+pub struct PropertyDef {
+    pub name: String,
+    pub kind: PropertyKind,
+    pub shift: u8,
+    pub bits: u8,
+}
+
+const FACES: [Face; 6] = Face::ALL;
+const HORIZONTAL_FACES: [Face; 4] = [Face::North, Face::South, Face::West, Face::East];
+const AXES: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+const COLORS: [Color; 16] = [
+    Color::White,
+    Color::Orange,
+    Color::Magenta,
+    Color::LightBlue,
+    Color::Yellow,
+    Color::Lime,
+    Color::Pink,
+    Color::Gray,
+    Color::LightGray,
+    Color::Cyan,
+    Color::Purple,
+    Color::Blue,
+    Color::Brown,
+    Color::Green,
+    Color::Red,
+    Color::Black,
+];
+
+impl PropertyKind {
+    pub fn count(self) -> u16 {
+        match self {
+            PropertyKind::Bool => 2,
+            PropertyKind::Int { min, max } => max as u16 - min as u16 + 1,
+            PropertyKind::Facing => FACES.len() as u16,
+            PropertyKind::HorizontalFacing => HORIZONTAL_FACES.len() as u16,
+            PropertyKind::Axis => AXES.len() as u16,
+            PropertyKind::Color => COLORS.len() as u16,
+        }
+    }
+
+    pub fn value(self, index: u16) -> Option<PropertyValue> {
+        let i = index as usize;
+
+        match self {
+            PropertyKind::Bool => (index < 2).then_some(PropertyValue::Bool(index == 1)),
+            PropertyKind::Int { min, max } => {
+                (index <= (max - min) as u16).then_some(PropertyValue::Int(min + index as u8))
+            }
+            PropertyKind::Facing => FACES.get(i).copied().map(PropertyValue::Face),
+            PropertyKind::HorizontalFacing => {
+                HORIZONTAL_FACES.get(i).copied().map(PropertyValue::Face)
+            }
+            PropertyKind::Axis => AXES.get(i).copied().map(PropertyValue::Axis),
+            PropertyKind::Color => COLORS.get(i).copied().map(PropertyValue::Color),
+        }
+    }
+
+    pub fn index(self, value: PropertyValue) -> Option<u16> {
+        let index = match (self, value) {
+            (PropertyKind::Bool, PropertyValue::Bool(b)) => b as usize,
+            (PropertyKind::Int { min, max }, PropertyValue::Int(n)) => {
+                if !(min..=max).contains(&n) {
+                    return None;
+                }
+
+                (n - min) as usize
+            }
+            (PropertyKind::Facing, PropertyValue::Face(face)) => face as usize,
+            (PropertyKind::HorizontalFacing, PropertyValue::Face(face)) => {
+                HORIZONTAL_FACES.iter().position(|f| *f == face)?
+            }
+            (PropertyKind::Axis, PropertyValue::Axis(axis)) => axis as usize,
+            (PropertyKind::Color, PropertyValue::Color(color)) => color as usize,
+            _ => return None,
+        };
+
+        Some(index as u16)
+    }
+
+    /// Parses a default value from the block definition file
+    pub fn parse(self, value: &toml::Value) -> Option<PropertyValue> {
+        let value = match (self, value) {
+            (PropertyKind::Bool, toml::Value::Boolean(b)) => PropertyValue::Bool(*b),
+            (PropertyKind::Int { .. }, toml::Value::Integer(n)) => {
+                PropertyValue::Int(u8::try_from(*n).ok()?)
+            }
+            (PropertyKind::Facing | PropertyKind::HorizontalFacing, toml::Value::String(s)) => {
+                PropertyValue::Face(*FACES.iter().find(|face| face_name(**face) == s)?)
+            }
+            (PropertyKind::Axis, toml::Value::String(s)) => PropertyValue::Axis(match s.as_str() {
+                "x" => Axis::X,
+                "y" => Axis::Y,
+                "z" => Axis::Z,
+                _ => return None,
+            }),
+            (PropertyKind::Color, value) => PropertyValue::Color(value.clone().try_into().ok()?),
+            _ => return None,
+        };
+
+        self.index(value).map(|_| value)
+    }
+}
+
+impl PropertyDef {
+    fn mask(&self) -> u16 {
+        (((1u32 << self.bits) - 1) as u16) << self.shift
+    }
+
+    pub fn get(&self, state: BlockState) -> Option<PropertyValue> {
+        self.kind.value((state.data & self.mask()) >> self.shift)
+    }
+
+    pub fn set(&self, state: BlockState, value: PropertyValue) -> Option<BlockState> {
+        let index = self.kind.index(value)?;
+
+        Some(BlockState {
+            id: state.id,
+            data: (state.data & !self.mask()) | (index << self.shift),
+        })
+    }
+
+    pub fn is_valid(&self, state: BlockState) -> bool {
+        self.get(state).is_some()
+    }
+}
+
+fn face_name(face: Face) -> &'static str {
+    match face {
+        Face::Down => "down",
+        Face::Up => "up",
+        Face::North => "north",
+        Face::South => "south",
+        Face::West => "west",
+        Face::East => "east",
+    }
+}
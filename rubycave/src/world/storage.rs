@@ -0,0 +1,199 @@
+use rkyv::{Archive, Deserialize, Serialize};
+
+use super::{BlockState, CHUNK_VOLUME};
+
+/// Paletted block state storage for a single chunk
+///
+/// Uniform chunks only store their single block. Otherwise, each block is an
+/// index into `palette`, packed `bits` wide into 64-bit words without spanning
+/// word boundaries. `bits` grows whenever the palette outgrows it.
+#[derive(Archive, Deserialize, Serialize, Clone, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum BlockStorage {
+    Single(BlockState),
+    Paletted {
+        palette: Vec<BlockState>,
+        bits: u8,
+        data: Vec<u64>,
+    },
+}
+
+impl BlockStorage {
+    pub fn new(block: BlockState) -> Self {
+        Self::Single(block)
+    }
+
+    pub fn get(&self, index: usize) -> BlockState {
+        match self {
+            Self::Single(block) => *block,
+            Self::Paletted {
+                palette,
+                bits,
+                data,
+            } => palette[unpack(data, *bits, index)],
+        }
+    }
+
+    pub fn set(&mut self, index: usize, block: BlockState) {
+        match self {
+            Self::Single(single) => {
+                if *single == block {
+                    return;
+                }
+
+                let mut data = vec![0; words(1)];
+                pack(&mut data, 1, index, 1);
+
+                *self = Self::Paletted {
+                    palette: vec![*single, block],
+                    bits: 1,
+                    data,
+                };
+            }
+            Self::Paletted {
+                palette,
+                bits,
+                data,
+            } => {
+                let value = match palette.iter().position(|b| *b == block) {
+                    Some(value) => value,
+                    None => {
+                        palette.push(block);
+
+                        let needed = bits_for(palette.len());
+
+                        if needed > *bits {
+                            *data = repack(data, *bits, needed);
+                            *bits = needed;
+                        }
+
+                        palette.len() - 1
+                    }
+                };
+
+                pack(data, *bits, index, value);
+            }
+        }
+    }
+
+    pub fn fill(&mut self, block: BlockState) {
+        *self = Self::Single(block);
+    }
+
+    /// Whether every index points into the palette, which a storage from the
+    /// wire doesn't have to do
+    pub fn is_valid(&self) -> bool {
+        let Self::Paletted {
+            palette,
+            bits,
+            data,
+        } = self
+        else {
+            return true;
+        };
+
+        (1..=16).contains(bits)
+            && !palette.is_empty()
+            && data.len() == words(*bits)
+            && (0..CHUNK_VOLUME).all(|index| unpack(data, *bits, index) < palette.len())
+    }
+
+    /// Drops unused palette entries and shrinks the index width, collapsing to
+    /// a single value if only one block remains.
+    pub fn compact(&mut self) {
+        let Self::Paletted {
+            palette,
+            bits,
+            data,
+        } = self
+        else {
+            return;
+        };
+
+        let mut counts = vec![0usize; palette.len()];
+
+        for index in 0..CHUNK_VOLUME {
+            counts[unpack(data, *bits, index)] += 1;
+        }
+
+        let mut remap = vec![0; palette.len()];
+        let mut new_palette = Vec::new();
+
+        for (i, count) in counts.iter().enumerate() {
+            if *count > 0 {
+                remap[i] = new_palette.len();
+                new_palette.push(palette[i]);
+            }
+        }
+
+        if new_palette.len() == 1 {
+            *self = Self::Single(new_palette[0]);
+            return;
+        }
+
+        if new_palette.len() == palette.len() {
+            return;
+        }
+
+        let new_bits = bits_for(new_palette.len());
+        let mut new_data = vec![0; words(new_bits)];
+
+        for index in 0..CHUNK_VOLUME {
+            pack(
+                &mut new_data,
+                new_bits,
+                index,
+                remap[unpack(data, *bits, index)],
+            );
+        }
+
+        *self = Self::Paletted {
+            palette: new_palette,
+            bits: new_bits,
+            data: new_data,
+        };
+    }
+}
+
+impl Default for BlockStorage {
+    fn default() -> Self {
+        Self::Single(BlockState::AIR)
+    }
+}
+
+fn bits_for(len: usize) -> u8 {
+    (usize::BITS - (len - 1).leading_zeros()).max(1) as u8
+}
+
+fn words(bits: u8) -> usize {
+    let per_word = 64 / bits as usize;
+    CHUNK_VOLUME.div_ceil(per_word)
+}
+
+fn unpack(data: &[u64], bits: u8, index: usize) -> usize {
+    let per_word = 64 / bits as usize;
+    let shift = (index % per_word) * bits as usize;
+    let mask = (1u64 << bits) - 1;
+
+    ((data[index / per_word] >> shift) & mask) as usize
+}
+
+fn pack(data: &mut [u64], bits: u8, index: usize, value: usize) {
+    let per_word = 64 / bits as usize;
+    let shift = (index % per_word) * bits as usize;
+    let mask = (1u64 << bits) - 1;
+    let word = &mut data[index / per_word];
+
+    *word = (*word & !(mask << shift)) | ((value as u64 & mask) << shift);
+}
+
+fn repack(data: &[u64], bits: u8, new_bits: u8) -> Vec<u64> {
+    let mut new_data = vec![0; words(new_bits)];
+
+    for index in 0..CHUNK_VOLUME {
+        pack(&mut new_data, new_bits, index, unpack(data, bits, index));
+    }
+
+    new_data
+}
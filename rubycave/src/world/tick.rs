@@ -0,0 +1,546 @@
+use std::collections::BTreeMap;
+
+use gxhash::{HashMap, HashMapExt};
+use rkyv::{Archive, Deserialize, Serialize};
+
+use glam::Vec3;
+
+use crate::{
+    entity::{Entities, Entity, EntityKind, ITEM_LIFETIME, MAX_FALL_AGE},
+    gen::{self, feature::FeatureRegistry},
+    math::FastPrng,
+    InfiniteIterator, RangeIterator, TICK_RATE,
+};
+
+use super::{
+    fluid::{self, Fluid, FluidSim, FluidState},
+    light, BlockPos, BlockRegistry, BlockState, ChunkPos, Face, LightKind, LocalPos, PropertyValue,
+    World, CHUNK_VOLUME,
+};
+
+/// Random ticks each chunk gets per second
+pub const RANDOM_TICK_RATE: u32 = 60;
+
+/// Light a sapling or grass needs to grow
+const GROWTH_LIGHT: u8 = 9;
+
+/// Block light above which ice melts
+const MELT_LIGHT: u8 = 11;
+
+/// Ticks between a falling block losing its support and starting to fall
+const FALL_DELAY: u64 = TICK_RATE as u64 / 10;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("missing block {0:?}")]
+    MissingBlock(&'static str),
+    #[error("missing feature {0:?}")]
+    MissingFeature(&'static str),
+    #[error("fluid error")]
+    Fluid(#[from] fluid::Error),
+    #[error("feature error")]
+    Gen(#[from] gen::Error),
+}
+
+/// What a block does when ticked
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Behavior {
+    /// Flows on scheduled ticks
+    Fluid,
+    /// Spreads to dirt nearby on random ticks, and turns back to dirt when
+    /// covered
+    Grass,
+    /// Grows into a tree on random ticks, through a `stage` property
+    Sapling,
+    /// Melts on random ticks when near bright block light
+    Ice,
+    /// Turns into a falling block entity on scheduled ticks when there's air
+    /// or a fluid below
+    Falling,
+}
+
+/// A pending scheduled tick, as kept in a chunk out of its world
+#[derive(Archive, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct ChunkTick {
+    pub pos: LocalPos,
+    /// Ticks left until it's due
+    pub delay: u64,
+}
+
+/// Scheduled ticks of a world
+///
+/// The ticks of a chunk move into it when it's removed from the world and back
+/// when it's inserted, so they're saved along with the chunk.
+#[derive(Default)]
+pub struct TickQueue {
+    now: u64,
+    /// Positions by the tick they're due, possibly outdated by `due`
+    pending: BTreeMap<u64, Vec<BlockPos>>,
+    due: HashMap<BlockPos, u64>,
+}
+
+impl TickQueue {
+    pub fn new() -> Self {
+        Self {
+            now: 0,
+            pending: BTreeMap::new(),
+            due: HashMap::new(),
+        }
+    }
+
+    /// Number of ticks run so far
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Schedules a tick at `pos` in `delay` ticks, unless one is already
+    /// pending there
+    pub fn schedule(&mut self, pos: BlockPos, delay: u64) -> bool {
+        if self.due.contains_key(&pos) {
+            return false;
+        }
+
+        let due = self.now + delay.max(1);
+
+        self.due.insert(pos, due);
+        self.pending.entry(due).or_default().push(pos);
+
+        true
+    }
+
+    pub fn is_scheduled(&self, pos: BlockPos) -> bool {
+        self.due.contains_key(&pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.due.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.due.is_empty()
+    }
+
+    /// Moves to the next tick, returning the positions due in the order they
+    /// were scheduled
+    pub fn advance(&mut self) -> Vec<BlockPos> {
+        self.now += 1;
+
+        let mut due = Vec::new();
+
+        while let Some(entry) = self.pending.first_entry() {
+            if *entry.key() > self.now {
+                break;
+            }
+
+            let tick = *entry.key();
+
+            for pos in entry.remove() {
+                if self.due.get(&pos) == Some(&tick) {
+                    self.due.remove(&pos);
+                    due.push(pos);
+                }
+            }
+        }
+
+        due
+    }
+
+    /// Removes the ticks pending in a chunk
+    pub(super) fn take_chunk(&mut self, chunk: ChunkPos) -> Vec<ChunkTick> {
+        let mut ticks = self
+            .due
+            .iter()
+            .filter(|(pos, _)| pos.chunk() == chunk)
+            .map(|(pos, due)| (*pos, *due))
+            .collect::<Vec<_>>();
+
+        ticks.sort_unstable_by_key(|(pos, due)| (*due, pos.local().index()));
+
+        ticks
+            .into_iter()
+            .map(|(pos, due)| {
+                self.due.remove(&pos);
+
+                ChunkTick {
+                    pos: pos.local(),
+                    delay: due - self.now,
+                }
+            })
+            .collect()
+    }
+
+    /// Copies the ticks pending in a chunk
+    pub(super) fn chunk_ticks(&self, chunk: ChunkPos) -> Vec<ChunkTick> {
+        let mut ticks = self
+            .due
+            .iter()
+            .filter(|(pos, _)| pos.chunk() == chunk)
+            .map(|(pos, due)| ChunkTick {
+                pos: pos.local(),
+                delay: due - self.now,
+            })
+            .collect::<Vec<_>>();
+
+        ticks.sort_unstable_by_key(|tick| (tick.delay, tick.pos.index()));
+        ticks
+    }
+
+    pub(super) fn load_chunk(&mut self, chunk: ChunkPos, ticks: Vec<ChunkTick>) {
+        for tick in ticks {
+            self.schedule(chunk.block(tick.pos), tick.delay);
+        }
+    }
+}
+
+/// Runs the scheduled and random ticks of a world through each block's
+/// [`Behavior`]
+///
+/// Every tick, `RANDOM_TICK_RATE / TICK_RATE` random blocks of each loaded
+/// chunk get a random tick, so growth speed doesn't depend on the tick rate.
+/// Entities are ticked last, placing falling blocks back when they land.
+pub struct Ticker {
+    fluids: FluidSim,
+    entities: Entities,
+    features: FeatureRegistry,
+    rng: FastPrng<u32>,
+    dirt: BlockState,
+    grass: BlockState,
+    bedrock: BlockState,
+}
+
+impl Ticker {
+    pub fn new(registry: &BlockRegistry, seed: u64) -> Result<Self, Error> {
+        let block = |name| {
+            registry
+                .default_state_by_name(name)
+                .ok_or(Error::MissingBlock(name))
+        };
+
+        let features = FeatureRegistry::builtin(registry)?;
+
+        if features.get_by_name("tree").is_none() {
+            return Err(Error::MissingFeature("tree"));
+        }
+
+        Ok(Self {
+            fluids: FluidSim::new(registry)?,
+            entities: Entities::new(),
+            features,
+            rng: FastPrng {
+                state: (seed ^ (seed >> 32)) as u32 | 1,
+            },
+            dirt: block("dirt")?,
+            grass: block("grass")?,
+            bedrock: block("bedrock")?,
+        })
+    }
+
+    pub fn fluids(&self) -> &FluidSim {
+        &self.fluids
+    }
+
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    pub fn entities_mut(&mut self) -> &mut Entities {
+        &mut self.entities
+    }
+
+    /// Schedules ticks for the blocks around `pos` that react to it changing
+    pub fn notify(&self, world: &mut World, registry: &BlockRegistry, pos: BlockPos) {
+        self.fluids.notify(world, registry, pos);
+
+        for pos in [pos, pos.neighbor(Face::Up)] {
+            if behavior(world, registry, pos) == Some(Behavior::Falling) {
+                world.ticks_mut().schedule(pos, FALL_DELAY);
+            }
+        }
+    }
+
+    /// Runs one tick, returning the blocks that changed in order
+    pub fn tick(
+        &mut self,
+        world: &mut World,
+        registry: &BlockRegistry,
+    ) -> Vec<(BlockPos, BlockState)> {
+        let mut changes = Vec::new();
+
+        for pos in world.ticks_mut().advance() {
+            self.scheduled_tick(world, registry, pos, &mut changes);
+        }
+
+        // Sorted so ticks don't depend on the order of the chunk map
+        let mut chunks = world.chunks().map(|chunk| chunk.pos).collect::<Vec<_>>();
+        chunks.sort_unstable_by_key(|pos| (pos.x, pos.y, pos.z));
+
+        let count = (RANDOM_TICK_RATE / TICK_RATE).max(1);
+
+        for chunk in chunks {
+            for _ in 0..count {
+                let index = self.rng.next_in(0..CHUNK_VOLUME as u32) as usize;
+                let pos = chunk.block(LocalPos::from_index(index));
+
+                self.random_tick(world, registry, pos, &mut changes);
+            }
+        }
+
+        self.update_entities(world, registry, &mut changes);
+
+        changes
+    }
+
+    pub fn scheduled_tick(
+        &mut self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        match behavior(world, registry, pos) {
+            Some(Behavior::Fluid) => self.fluids.update(world, registry, pos, changes),
+            Some(Behavior::Falling) => self.fall(world, registry, pos, changes),
+            _ => {}
+        }
+    }
+
+    pub fn random_tick(
+        &mut self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        match behavior(world, registry, pos) {
+            Some(Behavior::Grass) => self.grass(world, registry, pos, changes),
+            Some(Behavior::Sapling) => self.sapling(world, registry, pos, changes),
+            Some(Behavior::Ice) => self.ice(world, registry, pos, changes),
+            Some(Behavior::Fluid | Behavior::Falling) | None => {}
+        }
+    }
+
+    fn set(
+        &self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        state: BlockState,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        if light::set_block(world, registry, pos, state) {
+            changes.push((pos, state));
+            self.notify(world, registry, pos);
+        }
+    }
+
+    fn grass(
+        &mut self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        let above = pos.neighbor(Face::Up);
+
+        if is_opaque(world, registry, above) {
+            self.set(world, registry, pos, self.dirt, changes);
+            return;
+        }
+
+        if light(world, above) < GROWTH_LIGHT {
+            return;
+        }
+
+        for _ in 0..4 {
+            let target = pos.offset(
+                self.rng.next_in(0..3u32) as i32 - 1,
+                self.rng.next_in(0..5u32) as i32 - 3,
+                self.rng.next_in(0..3u32) as i32 - 1,
+            );
+            let above = target.neighbor(Face::Up);
+
+            if world.get_block(target) == Some(self.dirt)
+                && !is_opaque(world, registry, above)
+                && light(world, above) >= 4
+            {
+                self.set(world, registry, target, self.grass, changes);
+            }
+        }
+    }
+
+    fn sapling(
+        &mut self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        if light(world, pos.neighbor(Face::Up)) < GROWTH_LIGHT || self.rng.next_in(0..7u32) != 0 {
+            return;
+        }
+
+        let Some(state) = world.get_block(pos) else {
+            return;
+        };
+        let Some(def) = registry.get(state.id) else {
+            return;
+        };
+
+        if let Some(PropertyValue::Int(0)) = def.get(state, "stage") {
+            if let Some(grown) = def.with(state, "stage", PropertyValue::Int(1)) {
+                self.set(world, registry, pos, grown, changes);
+            }
+
+            return;
+        }
+
+        // Checked in `new`
+        let tree = self.features.get_by_name("tree").unwrap();
+        let seed = self.rng.next() as u64;
+        let mut blocks = Vec::new();
+
+        tree.place(
+            seed,
+            pos,
+            &mut self.rng,
+            |_, _| pos.y - 1,
+            |pos, state, replace| blocks.push((pos, state, replace)),
+        );
+
+        self.set(world, registry, pos, BlockState::AIR, changes);
+
+        for (pos, state, replace) in blocks {
+            let Some(current) = world.get_block(pos) else {
+                continue;
+            };
+
+            if current != self.bedrock && replace.allows(current) {
+                self.set(world, registry, pos, state, changes);
+            }
+        }
+    }
+
+    fn ice(
+        &mut self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        if world.get_light(LightKind::Block, pos).unwrap_or(0) > MELT_LIGHT {
+            let water = self
+                .fluids
+                .state(registry, FluidState::source(Fluid::Water));
+
+            self.set(world, registry, pos, water, changes);
+        }
+    }
+
+    fn fall(
+        &mut self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        let Some(state) = world.get_block(pos) else {
+            return;
+        };
+
+        if !can_fall_into(world, registry, pos.neighbor(Face::Down)) {
+            return;
+        }
+
+        self.set(world, registry, pos, BlockState::AIR, changes);
+        self.entities.spawn(Entity::new(
+            EntityKind::FallingBlock(state),
+            Vec3::new(pos.x as f32 + 0.5, pos.y as f32, pos.z as f32 + 0.5),
+            Vec3::ZERO,
+        ));
+    }
+
+    /// Moves every entity but players, landing falling blocks and removing old
+    /// items
+    fn update_entities(
+        &mut self,
+        world: &mut World,
+        registry: &BlockRegistry,
+        changes: &mut Vec<(BlockPos, BlockState)>,
+    ) {
+        for id in self.entities.ids() {
+            let Some(entity) = self.entities.get_mut(id) else {
+                continue;
+            };
+
+            if entity.kind == EntityKind::Player {
+                continue;
+            }
+
+            entity.update(world, registry);
+
+            let entity = entity.clone();
+
+            match entity.kind {
+                EntityKind::FallingBlock(state) => {
+                    if !entity.on_ground && entity.age < MAX_FALL_AGE {
+                        continue;
+                    }
+
+                    self.entities.remove(id);
+
+                    let feet = entity.position + Vec3::new(0.0, 0.5, 0.0);
+                    let pos = BlockPos::new(
+                        feet.x.floor() as i32,
+                        feet.y.floor() as i32,
+                        feet.z.floor() as i32,
+                    );
+
+                    if entity.on_ground && can_fall_into(world, registry, pos) {
+                        self.set(world, registry, pos, state, changes);
+                    } else {
+                        self.entities.spawn(Entity::new(
+                            EntityKind::Item(state),
+                            entity.position,
+                            Vec3::ZERO,
+                        ));
+                    }
+                }
+                EntityKind::Item(_) => {
+                    if entity.age >= ITEM_LIFETIME {
+                        self.entities.remove(id);
+                    }
+                }
+                EntityKind::Player => {}
+            }
+        }
+    }
+}
+
+fn behavior(world: &World, registry: &BlockRegistry, pos: BlockPos) -> Option<Behavior> {
+    registry.get(world.get_block(pos)?.id)?.behavior
+}
+
+/// Whether a falling block can fall through or land in `pos`
+fn can_fall_into(world: &World, registry: &BlockRegistry, pos: BlockPos) -> bool {
+    world.get_block(pos).is_some_and(|state| {
+        state == BlockState::AIR
+            || registry.get(state.id).and_then(|def| def.behavior) == Some(Behavior::Fluid)
+    })
+}
+
+fn is_opaque(world: &World, registry: &BlockRegistry, pos: BlockPos) -> bool {
+    world
+        .get_block(pos)
+        .is_some_and(|state| registry.is_opaque(state.id))
+}
+
+fn light(world: &World, pos: BlockPos) -> u8 {
+    [LightKind::Block, LightKind::Sky]
+        .into_iter()
+        .filter_map(|kind| world.get_light(kind, pos))
+        .max()
+        .unwrap_or(0)
+}
@@ -0,0 +1,193 @@
+use rubycave::world::{
+    block::{Error, ATLAS_TILES},
+    BlockId, BlockRegistry, Face,
+};
+
+const AIR: &str = r#"
+[[block]]
+name = "air"
+id = 0
+solid = false
+opaque = false
+"#;
+
+fn load(blocks: &str) -> Result<BlockRegistry, Error> {
+    BlockRegistry::load(&format!("{AIR}{blocks}"))
+}
+
+#[test]
+fn builtin_ids_are_stable() {
+    let registry = BlockRegistry::builtin().unwrap();
+
+    // Stored in chunks and sent over the wire, these can never move
+    let ids = [
+        "air",
+        "stone",
+        "grass",
+        "dirt",
+        "cobblestone",
+        "planks",
+        "bedrock",
+        "sand",
+        "gravel",
+        "log",
+        "leaves",
+        "glass",
+        "gold_ore",
+        "iron_ore",
+        "coal_ore",
+        "diamond_ore",
+        "obsidian",
+        "wool",
+        "wheat",
+        "lit_furnace",
+        "snow",
+        "pumpkin",
+        "mossy_cobblestone",
+        "water",
+        "lava",
+        "ice",
+        "sapling",
+    ];
+
+    for (id, name) in ids.into_iter().enumerate() {
+        let id = BlockId(id as u16);
+
+        assert_eq!(registry.id(name), Some(id), "{name}");
+        assert_eq!(registry.get(id).unwrap().name, name);
+    }
+
+    assert_eq!(registry.get(BlockId::AIR).unwrap().name, "air");
+    assert!(!registry.is_solid(BlockId::AIR));
+}
+
+#[test]
+fn textures_fall_back_to_wider_faces() {
+    let registry = load(
+        r#"
+        [[block]]
+        name = "crate"
+        id = 3
+        texture = { all = 1, side = 2, north = 3, top = 4 }
+        "#,
+    )
+    .unwrap();
+    let def = registry.get_by_name("crate").unwrap();
+
+    assert_eq!(def.id, BlockId(3));
+    assert!(def.solid && def.opaque);
+    assert_eq!(def.textures[Face::Down as usize], 1);
+    assert_eq!(def.textures[Face::Up as usize], 4);
+    assert_eq!(def.textures[Face::North as usize], 3);
+    assert_eq!(def.textures[Face::South as usize], 2);
+
+    // Ids in between are left empty
+    assert!(registry.get(BlockId(1)).is_none());
+    assert!(registry.get(BlockId(4)).is_none());
+}
+
+#[test]
+fn duplicates_are_refused() {
+    let names = load(
+        r#"
+        [[block]]
+        name = "stone"
+        id = 1
+
+        [[block]]
+        name = "stone"
+        id = 2
+        "#,
+    );
+    assert!(matches!(names, Err(Error::DuplicateName(name)) if name == "stone"));
+
+    let ids = load(
+        r#"
+        [[block]]
+        name = "stone"
+        id = 1
+
+        [[block]]
+        name = "dirt"
+        id = 1
+        "#,
+    );
+    assert!(matches!(ids, Err(Error::DuplicateId(1))));
+}
+
+#[test]
+fn air_has_to_come_first() {
+    let missing = BlockRegistry::load(
+        r#"
+        [[block]]
+        name = "stone"
+        id = 1
+        "#,
+    );
+    assert!(matches!(missing, Err(Error::Air)));
+
+    let renamed = BlockRegistry::load(
+        r#"
+        [[block]]
+        name = "stone"
+        id = 0
+        "#,
+    );
+    assert!(matches!(renamed, Err(Error::Air)));
+}
+
+#[test]
+fn out_of_range_values_are_refused() {
+    let last = ATLAS_TILES * ATLAS_TILES - 1;
+    let texture = |tile| {
+        load(&format!(
+            r#"
+            [[block]]
+            name = "stone"
+            id = 1
+            texture = {{ all = 0, east = {tile} }}
+            "#
+        ))
+    };
+
+    assert!(texture(last).is_ok());
+    assert!(matches!(texture(last + 1), Err(Error::Texture(name)) if name == "stone"));
+
+    let light = load(
+        r#"
+        [[block]]
+        name = "lamp"
+        id = 1
+        light = 16
+        "#,
+    );
+    assert!(matches!(light, Err(Error::Light(name)) if name == "lamp"));
+
+    let property = load(
+        r#"
+        [[block]]
+        name = "log"
+        id = 1
+        properties = [{ name = "axis", kind = "axis", default = "w" }]
+        "#,
+    );
+    assert!(
+        matches!(property, Err(Error::Property(block, name)) if block == "log" && name == "axis")
+    );
+}
+
+#[test]
+fn malformed_files_are_refused() {
+    assert!(matches!(
+        BlockRegistry::load("[[block]"),
+        Err(Error::Toml(_))
+    ));
+    assert!(matches!(
+        load("[[block]]\nname = \"stone\"\n"),
+        Err(Error::Toml(_))
+    ));
+    assert!(matches!(
+        load("[[block]]\nname = \"stone\"\nid = -1\n"),
+        Err(Error::Toml(_))
+    ));
+}
@@ -0,0 +1,73 @@
+use rubycave::{
+    protocol::{
+        client,
+        codec::{self, PacketCodec},
+        server, Packet,
+    },
+    tokio_util::{
+        bytes::BytesMut,
+        codec::{Decoder, Encoder},
+    },
+};
+
+fn chat(message: &str) -> Packet {
+    Packet::Client(client::Packet::Chat {
+        message: message.to_owned(),
+    })
+}
+
+#[test]
+fn packets_round_trip() {
+    let mut codec = PacketCodec::default();
+    let mut buf = BytesMut::new();
+
+    codec.encode(chat("hello"), &mut buf).unwrap();
+    codec.encode(chat("world"), &mut buf).unwrap();
+
+    for expected in ["hello", "world"] {
+        let Some(Packet::Client(client::Packet::Chat { message })) =
+            codec.decode(&mut buf).unwrap()
+        else {
+            panic!("expected a chat message");
+        };
+
+        assert_eq!(message, expected);
+    }
+
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+}
+
+#[test]
+fn long_frames_are_refused_before_they_arrive() {
+    let mut buf = BytesMut::new();
+    PacketCodec::default()
+        .encode(chat(&"x".repeat(200)), &mut buf)
+        .unwrap();
+
+    // Only the length prefix has to be there
+    buf.truncate(2);
+
+    let error = PacketCodec::new(64).decode(&mut buf).unwrap_err();
+
+    assert!(matches!(error, codec::Error::TooLong { max: 64, .. }));
+    assert!(matches!(
+        error.packet_error(),
+        Some(server::PacketError::FrameLength { max: 64 })
+    ));
+}
+
+#[test]
+fn overlong_lengths_are_malformed() {
+    let mut buf = BytesMut::from(&[0xff; 16][..]);
+    let error = PacketCodec::default().decode(&mut buf).unwrap_err();
+
+    assert!(matches!(error, codec::Error::Length));
+    assert!(matches!(
+        error.packet_error(),
+        Some(server::PacketError::Malformed)
+    ));
+
+    // Not all there yet
+    let mut buf = BytesMut::from(&[0xff; 3][..]);
+    assert!(PacketCodec::default().decode(&mut buf).unwrap().is_none());
+}
@@ -0,0 +1,51 @@
+//! Fixtures shared by the integration tests, not every test uses all of them
+#![allow(dead_code)]
+
+use rubycave::world::{
+    light, BlockPos, BlockRegistry, BlockState, Chunk, ChunkPos, LocalPos, Ticker, World,
+};
+
+pub fn state(registry: &BlockRegistry, name: &str) -> BlockState {
+    registry.default_state_by_name(name).unwrap()
+}
+
+/// Name of the block at `pos`, which has to be loaded
+pub fn name<'a>(world: &World, registry: &'a BlockRegistry, pos: BlockPos) -> &'a str {
+    let state = world.get_block(pos).unwrap();
+    &registry.get(state.id).unwrap().name
+}
+
+/// The chunk at the origin, filled with `floor` below `height`
+pub fn floor_chunk(registry: &BlockRegistry, floor: &str, height: i32) -> Chunk {
+    let floor = state(registry, floor);
+    let mut chunk = Chunk::new(ChunkPos::new(0, 0, 0));
+
+    for local in LocalPos::iter().filter(|local| local.y() < height) {
+        chunk.set(local, floor);
+    }
+
+    chunk
+}
+
+/// A world of `chunks` with their light worked out
+pub fn lit_world(registry: &BlockRegistry, chunks: impl IntoIterator<Item = Chunk>) -> World {
+    let mut world = World::new();
+
+    for chunk in chunks {
+        let pos = chunk.pos;
+
+        world.insert_chunk(Box::new(chunk));
+        light::init_chunk(&mut world, registry, pos);
+    }
+
+    world
+}
+
+/// A lit chunk with a floor and a ticker seeded with `seed`
+pub fn ticked_floor(floor: &str, height: i32, seed: u64) -> (World, BlockRegistry, Ticker) {
+    let registry = BlockRegistry::builtin().unwrap();
+    let world = lit_world(&registry, [floor_chunk(&registry, floor, height)]);
+    let ticker = Ticker::new(&registry, seed).unwrap();
+
+    (world, registry, ticker)
+}
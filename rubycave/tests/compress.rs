@@ -0,0 +1,65 @@
+use rubycave::{
+    entity::EntityId,
+    gen::Generator,
+    protocol::{
+        compress::{self, Algorithm, Compression},
+        server,
+    },
+    world::{BlockRegistry, Chunk, ChunkPos, LocalPos},
+};
+
+fn chunk() -> Chunk {
+    let registry = BlockRegistry::builtin().unwrap();
+    Generator::new(0x5eed, &registry)
+        .unwrap()
+        .generate(ChunkPos::new(0, 0, 0))
+}
+
+#[test]
+fn chunks_round_trip() {
+    let chunk = chunk();
+
+    for compression in ["zstd:3", "lz4"] {
+        let compression: Compression = compression.parse().unwrap();
+        let packet = server::Packet::Chunk(Box::new(chunk.clone()));
+
+        let compressed = compress::compress_packet(packet, Some(compression)).unwrap();
+        let server::Packet::CompressedChunk { algorithm, .. } = &compressed else {
+            panic!("expected a compressed chunk, got {compressed:?}");
+        };
+        assert_eq!(*algorithm, compression.algorithm);
+
+        let server::Packet::Chunk(decompressed) = compress::decompress_packet(compressed).unwrap()
+        else {
+            panic!("expected a chunk");
+        };
+
+        assert!(LocalPos::iter().all(|pos| decompressed.get(pos) == chunk.get(pos)));
+    }
+}
+
+#[test]
+fn left_alone_without_compression() {
+    let packet = compress::compress_packet(server::Packet::Chunk(Box::new(chunk())), None).unwrap();
+    assert!(matches!(packet, server::Packet::Chunk(_)));
+
+    // Only chunks are compressed
+    let packet = compress::compress_packet(
+        server::Packet::DespawnEntity { id: EntityId(1) },
+        Some("lz4".parse().unwrap()),
+    )
+    .unwrap();
+    assert!(matches!(packet, server::Packet::DespawnEntity { .. }));
+}
+
+#[test]
+fn oversized_chunks_are_rejected() {
+    let mut data = (compress::MAX_CHUNK_SIZE as u32 + 1).to_le_bytes().to_vec();
+    data.extend_from_slice(&[0; 16]);
+
+    assert!(matches!(
+        Algorithm::Lz4.decompress(&data),
+        Err(compress::Error::TooLarge)
+    ));
+    assert!(Algorithm::Zstd.decompress(&[0; 16]).is_err());
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use rubycave::{
+    protocol::{
+        client,
+        delta::{self, BlockDelta, ChunkDelta},
+        server, PacketValidator,
+    },
+    world::{BlockPos, BlockRegistry, BlockState, Chunk, ChunkPos, LocalPos, World, CHUNK_VOLUME},
+};
+
+fn setup() -> (World, BlockState) {
+    let registry = BlockRegistry::builtin().unwrap();
+    let stone = common::state(&registry, "stone");
+
+    let mut world = World::new();
+    world.insert_chunk(Box::new(Chunk::new(ChunkPos::new(0, 0, 0))));
+    world.insert_chunk(Box::new(Chunk::new(ChunkPos::new(1, 0, 0))));
+
+    (world, stone)
+}
+
+#[test]
+fn changes_are_batched_per_chunk() {
+    let (world, stone) = setup();
+    let a = BlockPos::new(1, 2, 3);
+    let b = BlockPos::new(4, 5, 6);
+    let other = BlockPos::new(20, 0, 0);
+
+    let mut deltas = delta::batch(
+        &world,
+        [
+            (a, BlockState::default()),
+            (b, stone),
+            (other, stone),
+            (a, stone),
+        ],
+    );
+    deltas.sort_by_key(|delta| delta.chunk().x);
+
+    let [ChunkDelta::Blocks(chunk, changes), ChunkDelta::Block(pos, state)] = &deltas[..] else {
+        panic!("expected a delta and a single block, got {deltas:?}");
+    };
+
+    assert_eq!(*chunk, ChunkPos::new(0, 0, 0));
+    // The later change to `a` wins
+    assert_eq!(changes.len(), 2);
+    assert!(changes.contains(&BlockDelta {
+        index: a.local().index() as u16,
+        state: stone,
+    }));
+    assert_eq!((*pos, *state), (other, stone));
+}
+
+#[test]
+fn large_changes_resend_the_chunk() {
+    let (mut world, stone) = setup();
+    let origin = ChunkPos::new(0, 0, 0);
+
+    world
+        .ticks_mut()
+        .schedule(origin.block(LocalPos::new(1, 2, 3)), 5);
+
+    let deltas = delta::batch(
+        &world,
+        LocalPos::iter().map(|local| (origin.block(local), stone)),
+    );
+
+    assert!(matches!(
+        &deltas[..],
+        [ChunkDelta::Chunk(chunk)] if chunk.pos == origin && chunk.ticks().is_empty()
+    ));
+}
+
+#[test]
+fn out_of_chunk_indices_are_rejected() {
+    let validator = PacketValidator::new().unwrap();
+    let packet = |index| server::Packet::MultiBlockChange {
+        chunk: ChunkPos::new(0, 0, 0),
+        changes: vec![BlockDelta {
+            index,
+            state: BlockState::default(),
+        }],
+    };
+
+    assert!(validator
+        .check_server(&packet(CHUNK_VOLUME as u16 - 1))
+        .is_ok());
+    assert!(matches!(
+        validator.check_server(&packet(CHUNK_VOLUME as u16)),
+        Err(client::PacketError::BlockChange)
+    ));
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use rubycave::{
+    entity::EntityKind,
+    world::{
+        light, Behavior, BlockPos, BlockRegistry, BlockState, Fluid, FluidState, Ticker, World,
+    },
+};
+
+use common::state;
+
+fn setup() -> (World, BlockRegistry, Ticker) {
+    common::ticked_floor("stone", 1, 0)
+}
+
+fn place(world: &mut World, registry: &BlockRegistry, ticker: &Ticker, pos: BlockPos, name: &str) {
+    assert!(light::set_block(
+        world,
+        registry,
+        pos,
+        state(registry, name)
+    ));
+    ticker.notify(world, registry, pos);
+}
+
+/// Ticks until every entity is gone, returning the block changes
+fn settle(
+    world: &mut World,
+    registry: &BlockRegistry,
+    ticker: &mut Ticker,
+) -> Vec<(BlockPos, BlockState)> {
+    let mut changes = Vec::new();
+
+    for _ in 0..10_000 {
+        changes.extend(ticker.tick(world, registry));
+
+        let falling = ticker
+            .entities()
+            .iter()
+            .any(|(_, entity)| matches!(entity.kind, EntityKind::FallingBlock(_)));
+
+        if !falling && world.ticks().is_empty() {
+            return changes;
+        }
+    }
+
+    panic!("blocks never landed");
+}
+
+#[test]
+fn sand_and_gravel_fall() {
+    let registry = BlockRegistry::builtin().unwrap();
+    let behavior = |name| registry.get_by_name(name).unwrap().behavior;
+
+    assert_eq!(behavior("sand"), Some(Behavior::Falling));
+    assert_eq!(behavior("gravel"), Some(Behavior::Falling));
+}
+
+#[test]
+fn unsupported_sand_lands() {
+    let (mut world, registry, mut ticker) = setup();
+    let sand = state(&registry, "sand");
+
+    place(
+        &mut world,
+        &registry,
+        &ticker,
+        BlockPos::new(4, 10, 4),
+        "sand",
+    );
+    place(
+        &mut world,
+        &registry,
+        &ticker,
+        BlockPos::new(4, 11, 4),
+        "gravel",
+    );
+
+    ticker.tick(&mut world, &registry);
+    assert_eq!(ticker.entities().len(), 0, "falls after a short delay");
+
+    let changes = settle(&mut world, &registry, &mut ticker);
+
+    assert_eq!(world.get_block(BlockPos::new(4, 1, 4)), Some(sand));
+    assert_eq!(
+        world.get_block(BlockPos::new(4, 2, 4)),
+        Some(state(&registry, "gravel"))
+    );
+
+    for y in 3..=11 {
+        assert_eq!(
+            world.get_block(BlockPos::new(4, y, 4)),
+            Some(BlockState::AIR)
+        );
+    }
+
+    assert!(changes.contains(&(BlockPos::new(4, 1, 4), sand)));
+    assert!(ticker.entities().is_empty());
+}
+
+#[test]
+fn supported_sand_stays() {
+    let (mut world, registry, mut ticker) = setup();
+    let pos = BlockPos::new(4, 1, 4);
+
+    place(&mut world, &registry, &ticker, pos, "sand");
+
+    // Never lifted out of place
+    assert!(settle(&mut world, &registry, &mut ticker).is_empty());
+    assert_eq!(world.get_block(pos), Some(state(&registry, "sand")));
+}
+
+#[test]
+fn sand_falls_when_its_support_goes() {
+    let (mut world, registry, mut ticker) = setup();
+    let dirt = BlockPos::new(4, 5, 4);
+
+    place(&mut world, &registry, &ticker, dirt, "dirt");
+    place(&mut world, &registry, &ticker, dirt.offset(0, 1, 0), "sand");
+    settle(&mut world, &registry, &mut ticker);
+
+    assert!(ticker.entities().is_empty());
+
+    assert!(light::set_block(
+        &mut world,
+        &registry,
+        dirt,
+        BlockState::AIR
+    ));
+    ticker.notify(&mut world, &registry, dirt);
+    settle(&mut world, &registry, &mut ticker);
+
+    assert_eq!(
+        world.get_block(BlockPos::new(4, 1, 4)),
+        Some(state(&registry, "sand"))
+    );
+}
+
+#[test]
+fn sand_sinks_through_water() {
+    let (mut world, registry, mut ticker) = setup();
+    let water = ticker
+        .fluids()
+        .state(&registry, FluidState::source(Fluid::Water));
+
+    // A still pool, walled in so it doesn't spread
+    for (x, z) in [(3, 4), (5, 4), (4, 3), (4, 5)] {
+        place(
+            &mut world,
+            &registry,
+            &ticker,
+            BlockPos::new(x, 1, z),
+            "stone",
+        );
+    }
+
+    assert!(light::set_block(
+        &mut world,
+        &registry,
+        BlockPos::new(4, 1, 4),
+        water
+    ));
+    place(
+        &mut world,
+        &registry,
+        &ticker,
+        BlockPos::new(4, 6, 4),
+        "sand",
+    );
+    settle(&mut world, &registry, &mut ticker);
+
+    assert_eq!(
+        world.get_block(BlockPos::new(4, 1, 4)),
+        Some(state(&registry, "sand"))
+    );
+}
+
+#[test]
+fn blocked_landing_drops_an_item() {
+    let (mut world, registry, mut ticker) = setup();
+    let wheat = BlockPos::new(4, 1, 4);
+
+    place(&mut world, &registry, &ticker, wheat, "wheat");
+    place(
+        &mut world,
+        &registry,
+        &ticker,
+        BlockPos::new(4, 8, 4),
+        "gravel",
+    );
+    settle(&mut world, &registry, &mut ticker);
+
+    assert_eq!(world.get_block(wheat), Some(state(&registry, "wheat")));
+
+    let items = ticker
+        .entities()
+        .iter()
+        .map(|(_, entity)| entity.kind)
+        .collect::<Vec<_>>();
+
+    assert_eq!(items, [EntityKind::Item(state(&registry, "gravel"))]);
+}
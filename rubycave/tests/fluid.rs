@@ -0,0 +1,261 @@
+mod common;
+
+use rubycave::world::{
+    light, BlockPos, BlockRegistry, BlockState, ChunkPos, Fluid, FluidState, LocalPos, Ticker,
+    World,
+};
+
+use common::state;
+
+struct Sim {
+    world: World,
+    registry: BlockRegistry,
+    ticker: Ticker,
+}
+
+impl Sim {
+    /// A single chunk with a stone floor `floor` blocks thick, and walls of
+    /// stone at `walls`
+    fn new(floor: i32, walls: &[BlockPos]) -> Self {
+        let registry = BlockRegistry::builtin().unwrap();
+        let stone = state(&registry, "stone");
+        let mut chunk = common::floor_chunk(&registry, "stone", floor);
+
+        for wall in walls {
+            chunk.set(wall.local(), stone);
+        }
+
+        let mut world = World::new();
+        world.insert_chunk(Box::new(chunk));
+
+        Self {
+            ticker: Ticker::new(&registry, 0).unwrap(),
+            world,
+            registry,
+        }
+    }
+
+    fn place(&mut self, pos: BlockPos, state: BlockState) {
+        assert!(light::set_block(
+            &mut self.world,
+            &self.registry,
+            pos,
+            state
+        ));
+        self.ticker.notify(&mut self.world, &self.registry, pos);
+    }
+
+    fn source(&mut self, pos: BlockPos, fluid: Fluid) {
+        let state = self
+            .ticker
+            .fluids()
+            .state(&self.registry, FluidState::source(fluid));
+        self.place(pos, state);
+    }
+
+    fn run(&mut self, ticks: u64) -> Vec<(BlockPos, BlockState)> {
+        (0..ticks)
+            .flat_map(|_| self.ticker.tick(&mut self.world, &self.registry))
+            .collect()
+    }
+
+    /// Runs until no update is left
+    fn settle(&mut self) {
+        for _ in 0..100_000 {
+            if self.world.ticks().is_empty() {
+                return;
+            }
+
+            self.run(1);
+        }
+
+        panic!("fluids never settled");
+    }
+
+    fn fluid(&self, pos: BlockPos) -> Option<FluidState> {
+        self.ticker
+            .fluids()
+            .get(&self.registry, self.world.get_block(pos).unwrap())
+    }
+
+    fn name(&self, pos: BlockPos) -> &str {
+        common::name(&self.world, &self.registry, pos)
+    }
+}
+
+#[test]
+fn water_spreads_on_flat_ground() {
+    let mut sim = Sim::new(1, &[]);
+    let source = BlockPos::new(8, 1, 8);
+
+    sim.source(source, Fluid::Water);
+    sim.settle();
+
+    for z in 0..16 {
+        for x in 0..16 {
+            let pos = BlockPos::new(x, 1, z);
+            let distance = ((x - 8).abs() + (z - 8).abs()) as u8;
+
+            let expected = (distance <= 7).then_some(FluidState {
+                fluid: Fluid::Water,
+                level: distance,
+                falling: false,
+            });
+
+            assert_eq!(sim.fluid(pos), expected, "{pos:?}");
+        }
+    }
+}
+
+#[test]
+fn water_flows_toward_the_nearest_drop() {
+    let hole = BlockPos::new(11, 1, 8);
+    let mut sim = Sim::new(2, &[]);
+
+    sim.place(hole, BlockState::AIR);
+    sim.source(BlockPos::new(8, 2, 8), Fluid::Water);
+
+    let changes = sim.run(Fluid::Water.delay());
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].0, BlockPos::new(9, 2, 8));
+
+    sim.settle();
+
+    assert!(sim.fluid(hole).is_some_and(|fluid| fluid.falling));
+
+    for pos in [
+        BlockPos::new(7, 2, 8),
+        BlockPos::new(8, 2, 7),
+        BlockPos::new(8, 2, 9),
+        BlockPos::new(12, 2, 8),
+    ] {
+        assert_eq!(sim.fluid(pos), None, "{pos:?}");
+    }
+}
+
+#[test]
+fn water_falls_before_spreading() {
+    let mut sim = Sim::new(1, &[]);
+    let ledge = BlockPos::new(8, 8, 8);
+
+    sim.place(ledge, state(&sim.registry, "stone"));
+    sim.source(ledge.offset(0, 1, 0), Fluid::Water);
+    sim.settle();
+
+    for y in 1..=8 {
+        let pos = BlockPos::new(9, y, 8);
+        assert!(sim.fluid(pos).is_some_and(|fluid| fluid.falling), "{pos:?}");
+    }
+
+    // The fall feeds a full spread on the ground
+    assert_eq!(
+        sim.fluid(BlockPos::new(9, 1, 15)).map(|fluid| fluid.level),
+        Some(7)
+    );
+    assert_eq!(sim.fluid(BlockPos::new(10, 9, 8)), None);
+}
+
+#[test]
+fn water_drains_without_a_source() {
+    let mut sim = Sim::new(1, &[]);
+    let source = BlockPos::new(8, 1, 8);
+
+    sim.source(source, Fluid::Water);
+    sim.settle();
+    sim.place(source, BlockState::AIR);
+    sim.settle();
+
+    for local in LocalPos::iter() {
+        let pos = ChunkPos::new(0, 0, 0).block(local);
+        assert_eq!(sim.fluid(pos), None, "{pos:?}");
+    }
+}
+
+#[test]
+fn water_between_sources_refills() {
+    let mut sim = Sim::new(1, &[]);
+
+    sim.source(BlockPos::new(7, 1, 8), Fluid::Water);
+    sim.source(BlockPos::new(9, 1, 8), Fluid::Water);
+    sim.settle();
+
+    assert_eq!(
+        sim.fluid(BlockPos::new(8, 1, 8)),
+        Some(FluidState::source(Fluid::Water))
+    );
+}
+
+#[test]
+fn fluids_use_their_own_delay() {
+    let mut sim = Sim::new(1, &[]);
+
+    sim.source(BlockPos::new(3, 1, 3), Fluid::Water);
+    sim.source(BlockPos::new(12, 1, 12), Fluid::Lava);
+
+    sim.run(Fluid::Water.delay());
+    assert!(sim.fluid(BlockPos::new(4, 1, 3)).is_some());
+    assert_eq!(sim.fluid(BlockPos::new(13, 1, 12)), None);
+
+    sim.run(Fluid::Lava.delay() - Fluid::Water.delay());
+    assert_eq!(
+        sim.fluid(BlockPos::new(13, 1, 12)),
+        Some(FluidState {
+            fluid: Fluid::Lava,
+            level: 2,
+            falling: false,
+        })
+    );
+
+    sim.settle();
+
+    // Lava loses two levels per block
+    assert!(sim.fluid(BlockPos::new(12, 1, 9)).is_some());
+    assert_eq!(sim.fluid(BlockPos::new(12, 1, 8)), None);
+}
+
+#[test]
+fn water_and_lava_harden() {
+    // A one block wide channel along x
+    let walls = (0..16)
+        .flat_map(|x| [BlockPos::new(x, 1, 7), BlockPos::new(x, 1, 9)])
+        .collect::<Vec<_>>();
+    let mut sim = Sim::new(1, &walls);
+
+    sim.source(BlockPos::new(2, 1, 8), Fluid::Lava);
+    sim.settle();
+
+    assert_eq!(
+        sim.fluid(BlockPos::new(5, 1, 8)).map(|fluid| fluid.level),
+        Some(6)
+    );
+
+    // Water reaching flowing lava turns it into cobblestone
+    sim.source(BlockPos::new(9, 1, 8), Fluid::Water);
+    sim.settle();
+
+    assert_eq!(sim.name(BlockPos::new(5, 1, 8)), "cobblestone");
+    assert_eq!(
+        sim.fluid(BlockPos::new(2, 1, 8)),
+        Some(FluidState::source(Fluid::Lava))
+    );
+
+    // And a lava source into obsidian
+    sim.source(BlockPos::new(1, 1, 8), Fluid::Water);
+    sim.settle();
+
+    assert_eq!(sim.name(BlockPos::new(2, 1, 8)), "obsidian");
+    assert!(sim.fluid(BlockPos::new(3, 1, 8)).is_none());
+}
+
+#[test]
+fn lava_falling_into_water_makes_stone() {
+    let mut sim = Sim::new(1, &[]);
+    let pool = BlockPos::new(8, 1, 8);
+
+    sim.source(pool, Fluid::Water);
+    sim.settle();
+    sim.source(pool.offset(0, 2, 0), Fluid::Lava);
+    sim.settle();
+
+    assert_eq!(sim.name(pool), "stone");
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use rubycave::{
+    gen::{
+        feature::{FeatureRegistry, MAX_REACH},
+        Error, Generator, BEDROCK_Y,
+    },
+    world::{Biome, BlockPos, BlockRegistry, Chunk, ChunkPos, LocalPos},
+};
+
+const SEED: u64 = 0x5eed;
+
+fn generator(seed: u64) -> (Generator, BlockRegistry) {
+    let registry = BlockRegistry::builtin().unwrap();
+    (Generator::new(seed, &registry).unwrap(), registry)
+}
+
+/// FNV-1a over every block of the chunk, in index order
+fn fingerprint(chunk: &Chunk) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for local in LocalPos::iter() {
+        let state = chunk.get(local);
+
+        for byte in state
+            .id
+            .0
+            .to_le_bytes()
+            .into_iter()
+            .chain(state.data.to_le_bytes())
+        {
+            h = (h ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    h
+}
+
+#[test]
+fn snapshot() {
+    let (generator, _) = generator(SEED);
+
+    let snapshots = [
+        (ChunkPos::new(0, 0, 0), 0xca588fcd66e89085),
+        (ChunkPos::new(0, -1, 0), 0x6bef6d5b2a9204e0),
+        (ChunkPos::new(12, 0, -40), 0x9cdcb2a7678b9cc7),
+        (ChunkPos::new(12, -2, -40), 0xce11e89086a1d93a),
+        (ChunkPos::new(0, -4, 0), 0x6e4b301981db8eb9),
+    ];
+
+    for (pos, expected) in snapshots {
+        let chunk = generator.generate(pos);
+        assert_eq!(fingerprint(&chunk), expected, "{pos:?}");
+    }
+}
+
+#[test]
+fn deterministic() {
+    let (a, _) = generator(SEED);
+    let (b, _) = generator(SEED);
+    let (other, _) = generator(SEED + 1);
+
+    let positions = [ChunkPos::new(2, -1, -3), ChunkPos::new(-8, 0, 1)];
+
+    for pos in positions.into_iter().rev() {
+        assert_eq!(fingerprint(&a.generate(pos)), fingerprint(&b.generate(pos)));
+    }
+
+    assert!(positions
+        .iter()
+        .any(|pos| fingerprint(&a.generate(*pos)) != fingerprint(&other.generate(*pos))));
+}
+
+#[test]
+fn layers() {
+    let (generator, registry) = generator(SEED);
+    let id = |name| registry.id(name).unwrap();
+
+    for (x, z) in [(0, 0), (17, -5), (-100, 250)] {
+        let surface = generator.surface(x, z);
+        let pos = BlockPos::new(x, surface, z);
+        let chunk = generator.generate(pos.chunk());
+
+        assert_eq!(chunk.get(pos.local()).id, id("grass"));
+
+        let above = pos.offset(0, 1, 0);
+        let below = pos.offset(0, -1, 0);
+
+        assert_eq!(
+            generator.generate(above.chunk()).get(above.local()).id,
+            id("air")
+        );
+        assert_eq!(
+            generator.generate(below.chunk()).get(below.local()).id,
+            id("dirt")
+        );
+    }
+
+    let floor = BlockPos::new(3, BEDROCK_Y, 9);
+    let chunk = generator.generate(floor.chunk());
+
+    assert_eq!(chunk.get(floor.local()).id, id("bedrock"));
+
+    let void = floor.offset(0, -1, 0);
+    assert_eq!(
+        generator.generate(void.chunk()).get(void.local()).id,
+        id("air")
+    );
+}
+
+#[test]
+fn caves_and_ores() {
+    let (generator, registry) = generator(SEED);
+    let air = registry.id("air").unwrap();
+    let diamond = registry.id("diamond_ore").unwrap();
+
+    let mut carved = 0;
+    let mut diamonds = 0;
+
+    for y in -4..=-2 {
+        for z in -1..=1 {
+            for x in -1..=1 {
+                let chunk = generator.generate(ChunkPos::new(x, y, z));
+
+                for local in LocalPos::iter() {
+                    let pos = chunk.pos.block(local);
+                    let id = chunk.get(local).id;
+
+                    if id == air && pos.y < generator.surface(pos.x, pos.z) - 8 {
+                        carved += 1;
+                    }
+
+                    if id == diamond {
+                        assert!(pos.y <= -42, "{pos:?}");
+                        diamonds += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    assert!(carved > 0);
+    assert!(diamonds > 0);
+}
+
+#[test]
+fn biomes() {
+    let (generator, registry) = generator(SEED);
+    let mut seen = Vec::new();
+
+    for i in 0..400 {
+        let (x, z) = (i * 97 - 20000, i * 61 - 12000);
+        let biome = generator.biome(x, z);
+        let pos = BlockPos::new(x, generator.surface(x, z), z);
+        let chunk = generator.generate(pos.chunk());
+        let surface = registry.get(chunk.get(pos.local()).id).unwrap();
+
+        assert_eq!(chunk.get_biome(pos.local()), biome);
+
+        let expected = match biome {
+            Biome::Desert => "sand",
+            Biome::Tundra => "snow",
+            _ => "grass",
+        };
+
+        // Caves and boulders can replace the surface
+        if !["air", "cobblestone", "mossy_cobblestone"].contains(&surface.name.as_str()) {
+            assert_eq!(surface.name, expected, "{pos:?} {biome:?}");
+        }
+
+        if !seen.contains(&biome) {
+            seen.push(biome);
+        }
+    }
+
+    assert!(seen.len() >= 4, "{seen:?}");
+}
+
+#[test]
+fn features_cross_chunk_borders() {
+    let (generator, registry) = generator(SEED);
+    let log = registry.id("log").unwrap();
+    let leaves = registry.id("leaves").unwrap();
+
+    let mut chunks = HashMap::new();
+
+    for y in -1..=0 {
+        for z in -1..=1 {
+            for x in -1..=1 {
+                let pos = ChunkPos::new(x, y, z);
+                chunks.insert(pos, generator.generate(pos));
+            }
+        }
+    }
+
+    let id = |pos: BlockPos| {
+        let (chunk, local) = pos.split();
+        chunks.get(&chunk).map(|chunk| chunk.get(local).id)
+    };
+
+    // Leaves grown by a tree in the chunk below
+    let top = &chunks[&ChunkPos::new(0, 0, 0)];
+    assert!(LocalPos::iter().any(|local| top.get(local).id == leaves));
+    assert!(LocalPos::iter().all(|local| top.get(local).id != log));
+
+    let mut checked = 0;
+
+    for y in -1..=0 {
+        let chunk = ChunkPos::new(0, y, 0);
+
+        for local in LocalPos::iter() {
+            let pos = chunk.block(local);
+
+            if id(pos) != Some(leaves) {
+                continue;
+            }
+
+            // Every leaf belongs to a whole tree, not a piece cut at a border.
+            // Trunks can be cut short by the leaves of another tree.
+            let near_log = (-7..=2).any(|dy| {
+                (-2..=2).any(|dz| (-2..=2).any(|dx| id(pos.offset(dx, dy, dz)) == Some(log)))
+            });
+
+            assert!(near_log, "{pos:?}");
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 0);
+}
+
+#[test]
+fn feature_definitions() {
+    let registry = BlockRegistry::builtin().unwrap();
+    let features = FeatureRegistry::builtin(&registry).unwrap();
+
+    assert!(features.get_by_name("tree").is_some());
+    assert!(features.get_by_name("dungeon").is_some());
+
+    let too_far = r#"
+        [[feature]]
+        name = "tower"
+        placement = { kind = "surface", chance = 0.1 }
+        parts = [{ shape = "column", block = "stone", height = [20, 20] }]
+    "#;
+
+    assert!(matches!(
+        FeatureRegistry::load(too_far, &registry),
+        Err(Error::Feature(name)) if name == "tower"
+    ));
+
+    let missing = r#"
+        [[feature]]
+        name = "crystal"
+        placement = { kind = "surface", chance = 0.1 }
+        parts = [{ shape = "sphere", block = "crystal", radius = [1, 2] }]
+    "#;
+
+    assert!(matches!(
+        FeatureRegistry::load(missing, &registry),
+        Err(Error::MissingBlock(name)) if name == "crystal"
+    ));
+}
+
+#[test]
+fn features_reach_up_from_higher_neighbors() {
+    let registry = BlockRegistry::builtin().unwrap();
+    let canopy = r#"
+        [[feature]]
+        name = "canopy"
+        placement = { kind = "surface", chance = 0.05 }
+        parts = [
+            { shape = "column", block = "log", height = [8, 8] },
+            { shape = "sphere", block = "leaves", radius = [8, 8], anchor = "top" },
+        ]
+    "#;
+    let features = FeatureRegistry::load(canopy, &registry).unwrap();
+    let generator = Generator::with_features(SEED, &registry, features).unwrap();
+
+    // Every column of this chunk is too low for a feature to reach it, but the
+    // ground next to it is higher
+    let pos = ChunkPos::new(-30, 2, -40);
+    let origin = pos.origin();
+
+    assert!(LocalPos::iter().all(|local| {
+        let block = pos.block(local);
+        generator.surface(block.x, block.z) + MAX_REACH < origin.y
+    }));
+
+    let chunk = generator.generate(pos);
+
+    assert!(LocalPos::iter().any(|local| chunk.get(local).id == registry.id("leaves").unwrap()));
+}
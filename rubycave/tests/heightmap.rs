@@ -0,0 +1,76 @@
+mod common;
+
+use rubycave::{
+    gen::Generator,
+    world::{light, BlockPos, BlockRegistry, Chunk, ChunkPos, HeightmapKind, LocalPos, World},
+};
+
+use common::state;
+
+fn heights(world: &World, x: i32, z: i32) -> [Option<i32>; 3] {
+    HeightmapKind::ALL.map(|kind| world.get_height(kind, x, z))
+}
+
+#[test]
+fn heightmaps_follow_block_changes() {
+    let registry = BlockRegistry::builtin().unwrap();
+    let chunks = (-1..=1).map(|y| Chunk::new(ChunkPos::new(0, y, 0)));
+    let mut world = common::lit_world(&registry, chunks);
+
+    assert_eq!(heights(&world, 3, 5), [None; 3]);
+
+    let set = |world: &mut World, y, name| {
+        assert!(light::set_block(
+            world,
+            &registry,
+            BlockPos::new(3, y, 5),
+            state(&registry, name)
+        ));
+    };
+
+    set(&mut world, -10, "stone");
+    set(&mut world, 2, "leaves");
+    set(&mut world, 20, "wheat");
+
+    // [opaque, motion blocking, non-air]
+    assert_eq!(heights(&world, 3, 5), [Some(-10), Some(2), Some(20)]);
+
+    set(&mut world, 21, "water");
+    assert_eq!(heights(&world, 3, 5), [Some(-10), Some(21), Some(21)]);
+
+    set(&mut world, 21, "air");
+    set(&mut world, 2, "air");
+    assert_eq!(heights(&world, 3, 5), [Some(-10), Some(-10), Some(20)]);
+
+    // Other columns are left alone
+    assert_eq!(heights(&world, 4, 5), [None; 3]);
+
+    world.remove_chunk(ChunkPos::new(0, 1, 0));
+    assert_eq!(heights(&world, 3, 5), [Some(-10), Some(-10), Some(-10)]);
+}
+
+#[test]
+fn generated_heightmaps_match_the_blocks() {
+    let registry = BlockRegistry::builtin().unwrap();
+    let generator = Generator::new(7, &registry).unwrap();
+    let pos = BlockPos::new(0, generator.surface(0, 0), 0).chunk();
+    let chunks = (-1..=1).map(|y| generator.generate(pos.offset(0, y, 0)));
+    let world = common::lit_world(&registry, chunks);
+
+    for local in LocalPos::iter().filter(|local| local.y() == 0) {
+        let column = pos.block(local);
+
+        for kind in HeightmapKind::ALL {
+            let scanned = (pos.y * 16 - 16..pos.y * 16 + 32).rev().find(|y| {
+                let block = world.get_block(BlockPos::new(column.x, *y, column.z));
+                kind.matches(&registry, block.unwrap())
+            });
+
+            assert_eq!(
+                world.get_height(kind, column.x, column.z),
+                scanned,
+                "{kind:?} {column:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use rubycave::world::{
+    light, BlockPos, BlockRegistry, BlockState, Chunk, ChunkPos, LightKind, World,
+};
+
+use common::state;
+
+fn setup(chunks: &[ChunkPos]) -> (World, BlockRegistry) {
+    let registry = BlockRegistry::builtin().unwrap();
+    let world = common::lit_world(&registry, chunks.iter().copied().map(Chunk::new));
+
+    (world, registry)
+}
+
+#[test]
+fn block_light_crosses_chunk_seam() {
+    let (mut world, registry) = setup(&[ChunkPos::new(0, 0, 0), ChunkPos::new(1, 0, 0)]);
+    let furnace = BlockPos::new(15, 8, 8);
+
+    assert!(light::set_block(
+        &mut world,
+        &registry,
+        furnace,
+        state(&registry, "lit_furnace")
+    ));
+
+    assert_eq!(world.get_light(LightKind::Block, furnace), Some(13));
+    assert_eq!(
+        world.get_light(LightKind::Block, BlockPos::new(16, 8, 8)),
+        Some(12)
+    );
+    assert_eq!(
+        world.get_light(LightKind::Block, BlockPos::new(20, 8, 8)),
+        Some(8)
+    );
+    assert_eq!(
+        world.get_light(LightKind::Block, BlockPos::new(14, 9, 8)),
+        Some(11)
+    );
+
+    assert!(light::set_block(
+        &mut world,
+        &registry,
+        furnace,
+        BlockState::AIR
+    ));
+
+    for x in 0..32 {
+        for y in 0..16 {
+            let pos = BlockPos::new(x, y, 8);
+            assert_eq!(world.get_light(LightKind::Block, pos), Some(0), "{pos:?}");
+        }
+    }
+}
+
+#[test]
+fn block_light_spreads_into_loaded_chunk() {
+    let (mut world, registry) = setup(&[ChunkPos::new(0, 0, 0)]);
+
+    light::set_block(
+        &mut world,
+        &registry,
+        BlockPos::new(0, 4, 4),
+        state(&registry, "lit_furnace"),
+    );
+
+    let neighbor = ChunkPos::new(-1, 0, 0);
+    world.insert_chunk(Box::new(Chunk::new(neighbor)));
+    light::init_chunk(&mut world, &registry, neighbor);
+
+    assert_eq!(
+        world.get_light(LightKind::Block, BlockPos::new(-1, 4, 4)),
+        Some(12)
+    );
+    assert_eq!(
+        world.get_light(LightKind::Block, BlockPos::new(-3, 4, 4)),
+        Some(10)
+    );
+}
+
+#[test]
+fn sky_light_returns_when_roof_is_removed() {
+    let (mut world, registry) = setup(&[ChunkPos::new(0, 0, 0), ChunkPos::new(0, 1, 0)]);
+    let stone = state(&registry, "stone");
+
+    for z in 0..16 {
+        for x in 0..16 {
+            light::set_block(&mut world, &registry, BlockPos::new(x, 20, z), stone);
+        }
+    }
+
+    assert_eq!(
+        world.get_light(LightKind::Sky, BlockPos::new(8, 21, 8)),
+        Some(15)
+    );
+    assert_eq!(
+        world.get_light(LightKind::Sky, BlockPos::new(8, 19, 8)),
+        Some(0)
+    );
+    assert_eq!(
+        world.get_light(LightKind::Sky, BlockPos::new(8, 2, 8)),
+        Some(0)
+    );
+
+    let hole = BlockPos::new(8, 20, 8);
+    light::set_block(&mut world, &registry, hole, BlockState::AIR);
+
+    assert_eq!(world.get_light(LightKind::Sky, hole), Some(15));
+    assert_eq!(
+        world.get_light(LightKind::Sky, BlockPos::new(8, 2, 8)),
+        Some(15)
+    );
+    assert_eq!(
+        world.get_light(LightKind::Sky, BlockPos::new(9, 2, 8)),
+        Some(14)
+    );
+    assert_eq!(
+        world.get_light(LightKind::Sky, BlockPos::new(10, 19, 10)),
+        Some(11)
+    );
+
+    light::set_block(&mut world, &registry, hole, stone);
+
+    assert_eq!(
+        world.get_light(LightKind::Sky, BlockPos::new(8, 2, 8)),
+        Some(0)
+    );
+    assert_eq!(
+        world.get_light(LightKind::Sky, BlockPos::new(10, 19, 10)),
+        Some(0)
+    );
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use rubycave::{
+    glam::Vec3,
+    physics::{self, Aabb, Movement},
+    world::{BlockPos, BlockRegistry, World},
+};
+
+use common::state;
+
+const WIDTH: f32 = 0.6;
+const HEIGHT: f32 = 1.8;
+const STEP: f32 = 0.5;
+
+/// A single chunk with a stone floor at y = 0, and stone at `stones`
+fn setup(stones: impl IntoIterator<Item = BlockPos>) -> (World, BlockRegistry) {
+    let registry = BlockRegistry::builtin().unwrap();
+    let mut world = common::lit_world(&registry, [common::floor_chunk(&registry, "stone", 1)]);
+
+    for pos in stones {
+        world.set_block(&registry, pos, state(&registry, "stone"));
+    }
+
+    (world, registry)
+}
+
+fn player(feet: Vec3) -> Aabb {
+    Aabb::from_feet(feet, WIDTH, HEIGHT)
+}
+
+fn assert_close(a: Vec3, b: Vec3) {
+    assert!(a.distance(b) < 1e-4, "{a} != {b}");
+}
+
+#[test]
+fn boxes_grow_towards_the_motion() {
+    let aabb = player(Vec3::new(8.5, 1.0, 8.5));
+
+    assert_close(aabb.min, Vec3::new(8.2, 1.0, 8.2));
+    assert_close(aabb.max, Vec3::new(8.8, 2.8, 8.8));
+
+    let grown = aabb.expand_towards(Vec3::new(1.0, -2.0, 0.0));
+
+    assert_close(grown.min, Vec3::new(8.2, -1.0, 8.2));
+    assert_close(grown.max, Vec3::new(9.8, 2.8, 8.8));
+
+    // Touching isn't overlapping
+    let next = aabb.offset(Vec3::X * WIDTH);
+
+    assert!(!aabb.intersects(&next));
+    assert!(aabb.intersects(&aabb.offset(Vec3::X * 0.5)));
+}
+
+#[test]
+fn falling_lands_on_the_floor() {
+    let (world, registry) = setup([]);
+    let aabb = player(Vec3::new(8.5, 1.2, 8.5));
+    let movement = physics::move_aabb(&world, &registry, aabb, -Vec3::Y * 0.5, false, STEP);
+
+    assert_close(movement.motion, -Vec3::Y * 0.2);
+    assert!(movement.on_ground);
+    assert!(movement.collided_y);
+    assert!(!movement.collided_x && !movement.collided_z);
+
+    // Still in the air
+    let aabb = player(Vec3::new(8.5, 3.0, 8.5));
+    let movement = physics::move_aabb(&world, &registry, aabb, -Vec3::Y * 0.5, false, STEP);
+
+    assert_close(movement.motion, -Vec3::Y * 0.5);
+    assert!(!movement.on_ground);
+    assert!(!movement.collided_y);
+}
+
+#[test]
+fn walls_stop_one_axis_only() {
+    // A wall along z at x = 10, too high to step up
+    let wall = (0..16).flat_map(|z| (1..3).map(move |y| BlockPos::new(10, y, z)));
+    let (world, registry) = setup(wall);
+    let aabb = player(Vec3::new(9.5, 1.0, 8.5));
+    let motion = Vec3::new(0.5, 0.0, 0.3);
+    let movement = physics::move_aabb(&world, &registry, aabb, motion, true, STEP);
+
+    assert_close(movement.motion, Vec3::new(0.2, 0.0, 0.3));
+    assert!(movement.collided_x);
+    assert!(!movement.collided_y);
+    assert!(!movement.collided_z);
+
+    // And the other way round
+    let wall = (0..16).flat_map(|x| (1..3).map(move |y| BlockPos::new(x, y, 10)));
+    let (world, registry) = setup(wall);
+    let aabb = player(Vec3::new(8.5, 1.0, 9.5));
+    let motion = Vec3::new(-0.3, 0.0, 0.5);
+    let movement = physics::move_aabb(&world, &registry, aabb, motion, true, STEP);
+
+    assert_close(movement.motion, Vec3::new(-0.3, 0.0, 0.2));
+    assert!(!movement.collided_x);
+    assert!(movement.collided_z);
+}
+
+#[test]
+fn half_block_ledges_are_stepped_up() {
+    let ledge = BlockPos::new(9, 1, 8);
+    let (world, registry) = setup([ledge]);
+    let motion = Vec3::new(0.3, -0.1, 0.0);
+
+    // Feet half a block below the top of the ledge
+    let aabb = player(Vec3::new(8.5, 1.5, 8.5));
+    let movement = physics::move_aabb(&world, &registry, aabb, motion, true, STEP);
+
+    assert_close(movement.motion, Vec3::new(0.3, 0.5, 0.0));
+    assert!(movement.on_ground);
+    assert!(!movement.collided_x);
+
+    // Not without ground to push off from
+    let movement = physics::move_aabb(&world, &registry, aabb, motion, false, STEP);
+
+    assert_close(movement.motion, Vec3::new(0.2, -0.1, 0.0));
+    assert!(movement.collided_x);
+}
+
+#[test]
+fn full_blocks_are_not_stepped_up() {
+    let (world, registry) = setup([BlockPos::new(9, 1, 8)]);
+    let aabb = player(Vec3::new(8.5, 1.0, 8.5));
+    let motion = Vec3::new(0.3, -0.1, 0.0);
+    let movement = physics::move_aabb(&world, &registry, aabb, motion, true, STEP);
+
+    assert_close(movement.motion, Vec3::new(0.2, 0.0, 0.0));
+    assert!(movement.on_ground);
+    assert!(movement.collided_x);
+    assert!(movement.collided_y);
+}
+
+#[test]
+fn fast_boxes_do_not_tunnel() {
+    let wall = (0..16).flat_map(|z| (1..3).map(move |y| BlockPos::new(10, y, z)));
+    let (world, registry) = setup(wall);
+
+    let aabb = player(Vec3::new(8.5, 1.0, 8.5));
+    let movement = physics::move_aabb(&world, &registry, aabb, Vec3::X * 50.0, true, STEP);
+
+    assert_close(movement.motion, Vec3::X * 1.2);
+    assert!(movement.collided_x);
+
+    let aabb = player(Vec3::new(4.5, 12.0, 4.5));
+    let movement = physics::move_aabb(&world, &registry, aabb, -Vec3::Y * 50.0, false, STEP);
+
+    assert_close(movement.motion, -Vec3::Y * 11.0);
+    assert!(movement.on_ground);
+}
+
+/// Moves the player from `feet` by `motion` a few times over
+fn walk(world: &World, registry: &BlockRegistry, feet: Vec3, motion: Vec3) -> Vec<Movement> {
+    let mut aabb = player(feet);
+
+    (0..10)
+        .map(|_| {
+            let movement = physics::move_aabb(world, registry, aabb, motion, true, STEP);
+            aabb = aabb.offset(movement.motion);
+            movement
+        })
+        .collect()
+}
+
+#[test]
+fn moves_are_deterministic() {
+    let (world, registry) = setup([BlockPos::new(9, 1, 8), BlockPos::new(8, 1, 10)]);
+    let feet = Vec3::new(8.3, 1.4, 8.6);
+    let motion = Vec3::new(0.37, -0.23, 0.41);
+
+    // Bit for bit, the server replays the client's moves
+    assert_eq!(
+        walk(&world, &registry, feet, motion),
+        walk(&world, &registry, feet, motion)
+    );
+}
@@ -0,0 +1,70 @@
+use rubycave::world::{BlockPos, ChunkPos, Face, LocalPos, CHUNK_VOLUME};
+
+#[test]
+fn negative_positions_split() {
+    let (chunk, local) = BlockPos::new(-1, -17, -16).split();
+
+    assert_eq!(chunk, ChunkPos::new(-1, -2, -1));
+    assert_eq!((local.x(), local.y(), local.z()), (15, 15, 0));
+}
+
+#[test]
+fn split_round_trips() {
+    for x in [-33, -17, -16, -15, -1, 0, 1, 15, 16, 31] {
+        for y in [-32, -17, -1, 0, 16, 17] {
+            for z in [-16, -9, 0, 7, 48] {
+                let pos = BlockPos::new(x, y, z);
+                let (chunk, local) = pos.split();
+
+                assert_eq!(chunk.block(local), pos);
+                assert_eq!(pos.chunk(), chunk);
+            }
+        }
+    }
+}
+
+#[test]
+fn chunk_origins() {
+    assert_eq!(
+        ChunkPos::new(-1, -2, 3).origin(),
+        BlockPos::new(-16, -32, 48)
+    );
+    assert_eq!(
+        BlockPos::new(-16, -32, 48).chunk(),
+        ChunkPos::new(-1, -2, 3)
+    );
+    assert_eq!(
+        BlockPos::new(-17, -33, 47).chunk(),
+        ChunkPos::new(-2, -3, 2)
+    );
+}
+
+#[test]
+fn local_indices_round_trip() {
+    for index in 0..CHUNK_VOLUME {
+        assert_eq!(LocalPos::from_index(index).index(), index);
+    }
+
+    assert_eq!(LocalPos::iter().count(), CHUNK_VOLUME);
+}
+
+#[test]
+fn local_neighbors_stay_in_the_chunk() {
+    let corner = LocalPos::new(0, 0, 0);
+
+    assert_eq!(corner.neighbor(Face::West), None);
+    assert_eq!(corner.neighbor(Face::Down), None);
+    assert_eq!(corner.neighbor(Face::East), Some(LocalPos::new(1, 0, 0)));
+    assert_eq!(LocalPos::new(15, 15, 15).neighbor(Face::Up), None);
+    assert_eq!(LocalPos::try_new(16, 0, 0), None);
+    assert_eq!(LocalPos::try_new(0, -1, 0), None);
+}
+
+#[test]
+fn block_neighbors_cross_chunks() {
+    let pos = BlockPos::new(0, 0, 0);
+
+    assert_eq!(pos.neighbor(Face::West), BlockPos::new(-1, 0, 0));
+    assert_eq!(pos.neighbor(Face::West).chunk(), ChunkPos::new(-1, 0, 0));
+    assert_eq!(pos.neighbor(Face::North).chunk(), ChunkPos::new(0, 0, -1));
+}
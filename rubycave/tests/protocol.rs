@@ -0,0 +1,175 @@
+use rubycave::{
+    entity::EntityId,
+    protocol::{
+        chat::{Color, Segment, MAX_MESSAGE_LENGTH},
+        client, feature, server, Negotiated, PacketValidator, MIN_PROTOCOL_VERSION,
+        PROTOCOL_VERSION,
+    },
+    world::{BlockPos, BlockState, BlockStorage, Chunk, ChunkPos, Face, CHUNK_VOLUME, MAX_COORD},
+};
+
+fn handshake(min_version: u32, version: u32) -> client::Packet {
+    client::Packet::Handshake {
+        version,
+        min_version,
+        features: Vec::new(),
+        username: "Player".to_owned(),
+        compression: None,
+    }
+}
+
+#[test]
+fn overlapping_versions_are_compatible() {
+    let validator = PacketValidator::new().unwrap();
+
+    assert!(validator
+        .check_client(&handshake(MIN_PROTOCOL_VERSION, PROTOCOL_VERSION))
+        .is_ok());
+
+    // A newer client that still speaks this version
+    assert!(validator
+        .check_client(&handshake(PROTOCOL_VERSION, PROTOCOL_VERSION + 5))
+        .is_ok());
+}
+
+#[test]
+fn incompatible_versions_name_the_supported_range() {
+    let validator = PacketValidator::new().unwrap();
+    let result = validator.check_client(&handshake(PROTOCOL_VERSION + 1, PROTOCOL_VERSION + 2));
+
+    let Err(error @ server::PacketError::Version { min, max }) = result else {
+        panic!("expected a version error, got {result:?}");
+    };
+
+    assert_eq!((min, max), (MIN_PROTOCOL_VERSION, PROTOCOL_VERSION));
+    assert!(error.to_string().contains(&format!("{min} to {max}")));
+}
+
+#[test]
+fn features_are_intersected() {
+    let negotiated = Negotiated::new(
+        PROTOCOL_VERSION + 1,
+        &[feature::CHAT.to_owned(), "teleportation".to_owned()],
+    );
+
+    assert_eq!(negotiated.version, PROTOCOL_VERSION);
+    assert!(negotiated.has(feature::CHAT));
+    assert!(!negotiated.has("teleportation"));
+    assert!(!Negotiated::new(PROTOCOL_VERSION, &[]).has(feature::CHAT));
+}
+
+#[test]
+fn non_finite_numbers_are_rejected() {
+    let validator = PacketValidator::new().unwrap();
+    let moved = |x| client::Packet::Move {
+        x,
+        y: 0.0,
+        z: 0.0,
+        yaw: 0.0,
+        pitch: 0.0,
+        on_ground: true,
+    };
+
+    assert!(validator.check_client(&moved(1.0)).is_ok());
+
+    for x in [f32::NAN, f32::INFINITY] {
+        assert!(matches!(
+            validator.check_client(&moved(x)),
+            Err(server::PacketError::NonFinite)
+        ));
+    }
+
+    assert!(matches!(
+        validator.check_server(&server::Packet::TeleportEntity {
+            id: EntityId(1),
+            position: [0.0, f32::NEG_INFINITY, 0.0],
+        }),
+        Err(client::PacketError::NonFinite)
+    ));
+}
+
+#[test]
+fn far_positions_are_rejected() {
+    let validator = PacketValidator::new().unwrap();
+    let place = |pos| client::Packet::Place {
+        pos,
+        face: Face::East,
+        state: BlockState::AIR,
+    };
+
+    assert!(validator
+        .check_client(&place(BlockPos::new(MAX_COORD, -MAX_COORD, 0)))
+        .is_ok());
+    // Its neighbor would overflow
+    assert!(matches!(
+        validator.check_client(&place(BlockPos::new(i32::MAX, 0, 0))),
+        Err(server::PacketError::Position)
+    ));
+
+    // The last chunk still fits, the one after doesn't
+    let last = ChunkPos::new(MAX_COORD / 16 - 1, -MAX_COORD / 16, 0);
+    let chunk = |pos| server::Packet::Chunk(Box::new(Chunk::new(pos)));
+
+    assert!(validator.check_server(&chunk(last)).is_ok());
+    assert!(matches!(
+        validator.check_server(&chunk(last.offset(1, 0, 0))),
+        Err(client::PacketError::Position)
+    ));
+    assert!(matches!(
+        validator.check_server(&server::Packet::MultiBlockChange {
+            chunk: ChunkPos::new(0, i32::MIN, 0),
+            changes: Vec::new(),
+        }),
+        Err(client::PacketError::Position)
+    ));
+    assert!(matches!(
+        validator.check_server(&server::Packet::BlockChange {
+            pos: BlockPos::new(0, 0, MAX_COORD + 1),
+            state: BlockState::AIR,
+        }),
+        Err(client::PacketError::Position)
+    ));
+}
+
+#[test]
+fn unbounded_strings_are_rejected() {
+    let validator = PacketValidator::new().unwrap();
+
+    let mut packet = handshake(MIN_PROTOCOL_VERSION, PROTOCOL_VERSION);
+    if let client::Packet::Handshake { features, .. } = &mut packet {
+        features.push("x".repeat(1 << 16));
+    }
+    assert!(matches!(
+        validator.check_client(&packet),
+        Err(server::PacketError::Features)
+    ));
+
+    let segments = vec![Segment::new(
+        "x".repeat(MAX_MESSAGE_LENGTH + 1),
+        Color::White,
+    )];
+    assert!(matches!(
+        validator.check_server(&server::Packet::Chat { segments }),
+        Err(client::PacketError::Chat)
+    ));
+}
+
+#[test]
+fn storage_indices_stay_in_the_palette() {
+    let storage = |data| BlockStorage::Paletted {
+        palette: vec![BlockState::AIR, BlockState::AIR],
+        bits: 1,
+        data,
+    };
+
+    assert!(storage(vec![u64::MAX; CHUNK_VOLUME / 64]).is_valid());
+    // Too short for every block
+    assert!(!storage(vec![0; 1]).is_valid());
+
+    let narrow = BlockStorage::Paletted {
+        palette: vec![BlockState::AIR],
+        bits: 1,
+        data: vec![u64::MAX; CHUNK_VOLUME / 64],
+    };
+    assert!(!narrow.is_valid());
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use rubycave::{
+    glam::Vec3,
+    world::{ray, raycast, BlockPos, BlockRegistry, BlockState, Chunk, ChunkPos, Face, World},
+};
+
+use common::state;
+
+/// Two empty chunks either side of x = 0, with stone at `stones`
+fn setup(stones: &[BlockPos]) -> (World, BlockRegistry) {
+    let registry = BlockRegistry::builtin().unwrap();
+    let mut world = World::new();
+
+    world.insert_chunk(Box::new(Chunk::new(ChunkPos::new(0, 0, 0))));
+    world.insert_chunk(Box::new(Chunk::new(ChunkPos::new(-1, 0, 0))));
+
+    for pos in stones {
+        world.set_block(&registry, *pos, state(&registry, "stone"));
+    }
+
+    (world, registry)
+}
+
+fn assert_close(a: Vec3, b: Vec3) {
+    assert!(a.distance(b) < 1e-4, "{a} != {b}");
+}
+
+#[test]
+fn hits_the_face_towards_the_ray() {
+    let stone = BlockPos::new(5, 8, 8);
+    let (world, registry) = setup(&[stone]);
+    let hit = raycast(&world, Vec3::new(1.5, 8.5, 8.5), Vec3::X, 10.0).unwrap();
+
+    assert_eq!(hit.pos, stone);
+    assert_eq!(hit.state, state(&registry, "stone"));
+    assert_eq!(hit.face, Face::West);
+    assert_close(hit.point, Vec3::new(5.0, 8.5, 8.5));
+    assert!((hit.distance - 3.5).abs() < 1e-4);
+}
+
+#[test]
+fn negative_directions_hit_the_far_faces() {
+    let (world, _) = setup(&[BlockPos::new(5, 8, 8), BlockPos::new(3, 2, 3)]);
+
+    let west = raycast(&world, Vec3::new(8.5, 8.5, 8.5), -Vec3::X, 10.0).unwrap();
+    assert_eq!(west.face, Face::East);
+    assert_close(west.point, Vec3::new(6.0, 8.5, 8.5));
+
+    let down = raycast(&world, Vec3::new(3.5, 6.5, 3.5), -Vec3::Y, 10.0).unwrap();
+    assert_eq!(down.pos, BlockPos::new(3, 2, 3));
+    assert_eq!(down.face, Face::Up);
+    assert_close(down.point, Vec3::new(3.5, 3.0, 3.5));
+    assert!((down.distance - 3.5).abs() < 1e-4);
+
+    let (world, _) = setup(&[BlockPos::new(8, 8, 2)]);
+    let north = raycast(&world, Vec3::new(8.5, 8.5, 8.5), -Vec3::Z, 10.0).unwrap();
+    assert_eq!(north.face, Face::South);
+}
+
+#[test]
+fn rays_cross_into_negative_chunks() {
+    let stone = BlockPos::new(-3, 8, 8);
+    let (world, _) = setup(&[stone]);
+    let hit = raycast(&world, Vec3::new(2.5, 8.5, 8.5), -Vec3::X, 10.0).unwrap();
+
+    assert_eq!(hit.pos, stone);
+    assert_eq!(hit.face, Face::East);
+    assert_close(hit.point, Vec3::new(-2.0, 8.5, 8.5));
+    assert!((hit.distance - 4.5).abs() < 1e-4);
+
+    // And back out of them
+    let hit = raycast(&world, Vec3::new(-7.5, 8.5, 8.5), Vec3::X, 10.0).unwrap();
+    assert_eq!(hit.face, Face::West);
+    assert_close(hit.point, Vec3::new(-3.0, 8.5, 8.5));
+}
+
+#[test]
+fn diagonal_rays_enter_through_the_crossed_face() {
+    let stone = BlockPos::new(3, 9, 8);
+    let (world, _) = setup(&[stone]);
+    let direction = Vec3::new(2.0, 1.0, 0.0);
+    let hit = raycast(&world, Vec3::new(0.5, 8.5, 8.5), direction, 10.0).unwrap();
+
+    assert_eq!(hit.pos, stone);
+    assert_eq!(hit.face, Face::West);
+    assert_close(hit.point, Vec3::new(3.0, 9.75, 8.5));
+    assert!((hit.distance - 2.5 * direction.length() / 2.0).abs() < 1e-4);
+}
+
+#[test]
+fn starting_inside_a_block_hits_it_at_once() {
+    let stone = BlockPos::new(5, 8, 8);
+    let (world, _) = setup(&[stone]);
+    let origin = Vec3::new(5.5, 8.5, 8.5);
+    let hit = raycast(&world, origin, Vec3::X, 10.0).unwrap();
+
+    assert_eq!(hit.pos, stone);
+    assert_eq!(hit.distance, 0.0);
+    assert_eq!(hit.point, origin);
+    // The face it's looking out of
+    assert_eq!(hit.face, Face::East);
+}
+
+#[test]
+fn max_distance_stops_short() {
+    let (world, _) = setup(&[BlockPos::new(5, 8, 8)]);
+    let origin = Vec3::new(1.5, 8.5, 8.5);
+
+    assert!(raycast(&world, origin, Vec3::X, 3.4).is_none());
+    assert!(raycast(&world, origin, Vec3::X, 3.5).is_some());
+
+    // Misses everything
+    assert!(raycast(&world, origin, Vec3::Y, 100.0).is_none());
+}
+
+#[test]
+fn degenerate_rays_hit_nothing() {
+    let (world, _) = setup(&[BlockPos::new(5, 8, 8)]);
+    let origin = Vec3::new(1.5, 8.5, 8.5);
+
+    assert!(raycast(&world, origin, Vec3::ZERO, 10.0).is_none());
+    assert!(raycast(&world, Vec3::NAN, Vec3::X, 10.0).is_none());
+    assert!(raycast(&world, origin, Vec3::X, f32::INFINITY).is_none());
+    assert!(raycast(&world, origin, Vec3::X, -1.0).is_none());
+}
+
+#[test]
+fn custom_hits_skip_other_blocks() {
+    let (mut world, registry) = setup(&[BlockPos::new(5, 8, 8)]);
+    let glass = state(&registry, "glass");
+    world.set_block(&registry, BlockPos::new(3, 8, 8), glass);
+
+    let origin = Vec3::new(1.5, 8.5, 8.5);
+
+    assert_eq!(
+        raycast(&world, origin, Vec3::X, 10.0).unwrap().pos,
+        BlockPos::new(3, 8, 8)
+    );
+
+    let hit = ray::raycast_by(&world, origin, Vec3::X, 10.0, |state| {
+        state != glass && state != BlockState::AIR
+    });
+    assert_eq!(hit.unwrap().pos, BlockPos::new(5, 8, 8));
+}
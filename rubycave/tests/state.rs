@@ -0,0 +1,83 @@
+use rubycave::{
+    protocol::{codec::PacketCodec, server, Packet},
+    tokio_util::{
+        bytes::BytesMut,
+        codec::{Decoder, Encoder},
+    },
+    world::{
+        state::Color, Axis, BlockRegistry, BlockState, Chunk, ChunkPos, LocalPos, PropertyValue,
+    },
+};
+
+fn with(registry: &BlockRegistry, name: &str, property: &str, value: PropertyValue) -> BlockState {
+    let def = registry.get_by_name(name).unwrap();
+    let state = registry.default_state(def.id).unwrap();
+
+    def.with(state, property, value).unwrap()
+}
+
+#[test]
+fn properties_have_defaults() {
+    let registry = BlockRegistry::builtin().unwrap();
+    let log = registry.get_by_name("log").unwrap();
+    let state = registry.default_state(log.id).unwrap();
+
+    assert_eq!(log.get(state, "axis"), Some(PropertyValue::Axis(Axis::Y)));
+    assert!(registry.is_valid(state));
+}
+
+#[test]
+fn properties_are_packed_independently() {
+    let registry = BlockRegistry::builtin().unwrap();
+    let log = registry.get_by_name("log").unwrap();
+    let state = with(&registry, "log", "axis", PropertyValue::Axis(Axis::X));
+
+    assert_eq!(log.get(state, "axis"), Some(PropertyValue::Axis(Axis::X)));
+    assert_eq!(log.get(state, "color"), None);
+    assert!(registry.is_valid(state));
+
+    // A value of the wrong kind can't be set
+    assert_eq!(log.with(state, "axis", PropertyValue::Bool(true)), None);
+}
+
+#[test]
+fn properties_survive_the_chunk_packet() {
+    let registry = BlockRegistry::builtin().unwrap();
+    let log = with(&registry, "log", "axis", PropertyValue::Axis(Axis::Z));
+    let wool = with(
+        &registry,
+        "wool",
+        "color",
+        PropertyValue::Color(Color::LightBlue),
+    );
+
+    let mut chunk = Chunk::new(ChunkPos::new(0, 0, 0));
+    chunk.set(LocalPos::new(1, 2, 3), log);
+    chunk.set(LocalPos::new(4, 5, 6), wool);
+
+    let mut codec = PacketCodec::default();
+    let mut buf = BytesMut::new();
+    codec
+        .encode(
+            Packet::Server(server::Packet::Chunk(Box::new(chunk))),
+            &mut buf,
+        )
+        .unwrap();
+
+    let Some(Packet::Server(server::Packet::Chunk(chunk))) = codec.decode(&mut buf).unwrap() else {
+        panic!("expected a chunk");
+    };
+
+    let log_def = registry.get(log.id).unwrap();
+    let wool_def = registry.get(wool.id).unwrap();
+
+    assert_eq!(chunk.get(LocalPos::new(1, 2, 3)), log);
+    assert_eq!(
+        log_def.get(chunk.get(LocalPos::new(1, 2, 3)), "axis"),
+        Some(PropertyValue::Axis(Axis::Z))
+    );
+    assert_eq!(
+        wool_def.get(chunk.get(LocalPos::new(4, 5, 6)), "color"),
+        Some(PropertyValue::Color(Color::LightBlue))
+    );
+}
@@ -0,0 +1,114 @@
+use rubycave::world::{BlockId, BlockState, BlockStorage, CHUNK_VOLUME};
+
+fn state(id: u16) -> BlockState {
+    BlockState::new(BlockId(id))
+}
+
+fn bits(storage: &BlockStorage) -> Option<u8> {
+    match storage {
+        BlockStorage::Single(_) => None,
+        BlockStorage::Paletted { bits, .. } => Some(*bits),
+    }
+}
+
+#[test]
+fn get_returns_what_was_set() {
+    let mut storage = BlockStorage::default();
+
+    assert!((0..CHUNK_VOLUME).all(|index| storage.get(index) == BlockState::AIR));
+
+    storage.set(0, state(1));
+    storage.set(CHUNK_VOLUME - 1, state(2));
+    storage.set(
+        1000,
+        BlockState {
+            id: BlockId(1),
+            data: 3,
+        },
+    );
+
+    assert_eq!(storage.get(0), state(1));
+    assert_eq!(storage.get(CHUNK_VOLUME - 1), state(2));
+    assert_eq!(
+        storage.get(1000),
+        BlockState {
+            id: BlockId(1),
+            data: 3
+        }
+    );
+    assert_eq!(storage.get(1), BlockState::AIR);
+    assert!(storage.is_valid());
+}
+
+#[test]
+fn setting_the_same_block_stays_single() {
+    let mut storage = BlockStorage::new(state(1));
+    storage.set(42, state(1));
+
+    assert!(matches!(storage, BlockStorage::Single(block) if block == state(1)));
+}
+
+#[test]
+fn palette_grows_across_bit_widths() {
+    let mut storage = BlockStorage::default();
+    let mut widths = Vec::new();
+
+    // Index i holds block i + 1, so every block is distinct
+    for i in 0..300 {
+        storage.set(i, state(i as u16 + 1));
+
+        if let Some(bits) = bits(&storage) {
+            if widths.last() != Some(&bits) {
+                widths.push(bits);
+            }
+        }
+    }
+
+    assert_eq!(widths, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    assert!((0..300).all(|i| storage.get(i) == state(i as u16 + 1)));
+    assert!((300..CHUNK_VOLUME).all(|i| storage.get(i) == BlockState::AIR));
+    assert!(storage.is_valid());
+}
+
+#[test]
+fn compact_shrinks_the_palette() {
+    let mut storage = BlockStorage::default();
+
+    for i in 0..20 {
+        storage.set(i, state(i as u16 + 1));
+    }
+    assert_eq!(bits(&storage), Some(5));
+
+    // Only air and two blocks are left
+    for i in 2..20 {
+        storage.set(i, BlockState::AIR);
+    }
+    storage.compact();
+
+    assert_eq!(bits(&storage), Some(2));
+    assert_eq!(storage.get(0), state(1));
+    assert_eq!(storage.get(1), state(2));
+    assert!((2..CHUNK_VOLUME).all(|i| storage.get(i) == BlockState::AIR));
+    assert!(storage.is_valid());
+}
+
+#[test]
+fn compact_collapses_to_single() {
+    let mut storage = BlockStorage::default();
+
+    storage.set(7, state(1));
+    storage.set(7, BlockState::AIR);
+    storage.compact();
+
+    assert!(matches!(storage, BlockStorage::Single(BlockState::AIR)));
+
+    // Filled with something else entirely
+    let mut storage = BlockStorage::default();
+
+    for i in 0..CHUNK_VOLUME {
+        storage.set(i, state(1));
+    }
+    storage.compact();
+
+    assert!(matches!(storage, BlockStorage::Single(block) if block == state(1)));
+}
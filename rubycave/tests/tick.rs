@@ -0,0 +1,201 @@
+mod common;
+
+use rubycave::world::{
+    light, Behavior, BlockPos, BlockRegistry, Chunk, ChunkPos, Fluid, FluidState, LocalPos,
+    PropertyValue, TickQueue, Ticker, World,
+};
+
+use common::{name, state};
+
+fn setup() -> (World, BlockRegistry, Ticker) {
+    common::ticked_floor("dirt", 4, 0x5eed)
+}
+
+#[test]
+fn scheduled_ticks_come_due() {
+    let mut queue = TickQueue::new();
+    let a = BlockPos::new(1, 2, 3);
+    let b = BlockPos::new(-4, 5, 6);
+
+    assert!(queue.schedule(a, 3));
+    assert!(queue.schedule(b, 1));
+    assert!(!queue.schedule(a, 1));
+    assert_eq!(queue.len(), 2);
+
+    assert_eq!(queue.advance(), [b]);
+    assert!(queue.advance().is_empty());
+    assert_eq!(queue.advance(), [a]);
+    assert!(queue.is_empty());
+    assert_eq!(queue.now(), 3);
+}
+
+#[test]
+fn scheduled_ticks_are_saved_with_their_chunk() {
+    let pos = ChunkPos::new(2, 0, 0);
+    let block = pos.block(LocalPos::new(3, 4, 5));
+
+    let mut world = World::new();
+    world.insert_chunk(Box::new(Chunk::new(pos)));
+    world.ticks_mut().schedule(block, 10);
+
+    for _ in 0..4 {
+        world.ticks_mut().advance();
+    }
+
+    assert_eq!(world.export_chunk(pos).unwrap().ticks().len(), 1);
+
+    let chunk = world.remove_chunk(pos).unwrap();
+
+    assert!(world.ticks().is_empty());
+    assert_eq!(chunk.ticks()[0].pos, block.local());
+    assert_eq!(chunk.ticks()[0].delay, 6);
+
+    let mut other = World::new();
+    other.insert_chunk(chunk);
+
+    assert!(other.get_chunk(pos).unwrap().ticks().is_empty());
+
+    for _ in 0..5 {
+        assert!(other.ticks_mut().advance().is_empty());
+    }
+
+    assert_eq!(other.ticks_mut().advance(), [block]);
+}
+
+#[test]
+fn blocks_declare_behaviors() {
+    let registry = BlockRegistry::builtin().unwrap();
+    let behavior = |name| registry.get_by_name(name).unwrap().behavior;
+
+    assert_eq!(behavior("grass"), Some(Behavior::Grass));
+    assert_eq!(behavior("sapling"), Some(Behavior::Sapling));
+    assert_eq!(behavior("ice"), Some(Behavior::Ice));
+    assert_eq!(behavior("water"), Some(Behavior::Fluid));
+    assert_eq!(behavior("stone"), None);
+}
+
+#[test]
+fn grass_spreads_and_dies_when_covered() {
+    let (mut world, registry, mut ticker) = setup();
+    let grass = BlockPos::new(8, 3, 8);
+    let mut changes = Vec::new();
+
+    light::set_block(&mut world, &registry, grass, state(&registry, "grass"));
+
+    for _ in 0..100 {
+        ticker.random_tick(&mut world, &registry, grass, &mut changes);
+    }
+
+    let spread = changes
+        .iter()
+        .filter(|(pos, _)| *pos != grass)
+        .collect::<Vec<_>>();
+
+    assert!(!spread.is_empty());
+
+    for (pos, _) in spread {
+        assert_eq!(name(&world, &registry, *pos), "grass");
+        assert_eq!(pos.y, 3, "{pos:?}");
+        assert!((pos.x - grass.x).abs() <= 1 && (pos.z - grass.z).abs() <= 1);
+    }
+
+    light::set_block(
+        &mut world,
+        &registry,
+        grass.offset(0, 1, 0),
+        state(&registry, "stone"),
+    );
+    ticker.random_tick(&mut world, &registry, grass, &mut changes);
+
+    assert_eq!(name(&world, &registry, grass), "dirt");
+}
+
+#[test]
+fn ticker_runs_random_ticks() {
+    let (mut world, registry, mut ticker) = setup();
+
+    // Grass on every other column
+    for z in 0..16 {
+        for x in 0..16 {
+            if (x + z) % 2 == 0 {
+                let pos = BlockPos::new(x, 3, z);
+                light::set_block(&mut world, &registry, pos, state(&registry, "grass"));
+            }
+        }
+    }
+
+    let changes = (0..2000)
+        .flat_map(|_| ticker.tick(&mut world, &registry))
+        .collect::<Vec<_>>();
+
+    assert!(!changes.is_empty());
+}
+
+#[test]
+fn saplings_grow_into_trees() {
+    let (mut world, registry, mut ticker) = setup();
+    let sapling = BlockPos::new(8, 4, 8);
+    let mut changes = Vec::new();
+
+    light::set_block(&mut world, &registry, sapling, state(&registry, "sapling"));
+
+    let mut stages = Vec::new();
+
+    for _ in 0..1000 {
+        ticker.random_tick(&mut world, &registry, sapling, &mut changes);
+
+        let current = world.get_block(sapling).unwrap();
+        let def = registry.get(current.id).unwrap();
+
+        if def.name != "sapling" {
+            break;
+        }
+
+        if let Some(PropertyValue::Int(stage)) = def.get(current, "stage") {
+            if !stages.contains(&stage) {
+                stages.push(stage);
+            }
+        }
+    }
+
+    assert_eq!(stages, [0, 1]);
+    assert_eq!(name(&world, &registry, sapling), "log");
+    assert_eq!(name(&world, &registry, sapling.offset(0, 3, 0)), "log");
+    assert!(changes
+        .iter()
+        .any(|(_, placed)| *placed == state(&registry, "leaves")));
+}
+
+#[test]
+fn ice_melts_near_bright_light() {
+    let (mut world, registry, mut ticker) = setup();
+    let ice = BlockPos::new(8, 4, 8);
+    let dark = BlockPos::new(2, 4, 2);
+    let mut changes = Vec::new();
+
+    for pos in [ice, dark] {
+        light::set_block(&mut world, &registry, pos, state(&registry, "ice"));
+    }
+
+    light::set_block(
+        &mut world,
+        &registry,
+        ice.offset(1, 0, 0),
+        state(&registry, "lava"),
+    );
+
+    for pos in [ice, dark] {
+        ticker.random_tick(&mut world, &registry, pos, &mut changes);
+    }
+
+    assert_eq!(
+        ticker
+            .fluids()
+            .get(&registry, world.get_block(ice).unwrap()),
+        Some(FluidState::source(Fluid::Water))
+    );
+    assert_eq!(name(&world, &registry, dark), "ice");
+
+    // The new water was scheduled to flow
+    assert!(world.ticks().is_scheduled(ice));
+}
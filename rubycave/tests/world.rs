@@ -0,0 +1,161 @@
+mod common;
+
+use rubycave::world::{
+    BlockPos, BlockRegistry, BlockState, Chunk, ChunkPos, Face, LightKind, LocalPos, World,
+};
+
+use common::state;
+
+/// Chunks around the origin on both sides of every axis, none of them dirty
+fn setup() -> (World, BlockRegistry) {
+    let registry = BlockRegistry::builtin().unwrap();
+    let mut world = World::new();
+
+    for pos in [
+        ChunkPos::new(0, 0, 0),
+        ChunkPos::new(-1, 0, 0),
+        ChunkPos::new(-1, -1, -1),
+    ] {
+        world.insert_chunk(Box::new(Chunk::new(pos)));
+    }
+
+    world.take_dirty();
+
+    (world, registry)
+}
+
+fn sorted(mut chunks: Vec<ChunkPos>) -> Vec<ChunkPos> {
+    chunks.sort_by_key(|pos| (pos.x, pos.y, pos.z));
+    chunks
+}
+
+#[test]
+fn blocks_land_in_the_right_chunk() {
+    let (mut world, registry) = setup();
+    let stone = state(&registry, "stone");
+
+    assert!(world.set_block(&registry, BlockPos::new(-1, 0, 0), stone));
+    assert!(world.set_block(&registry, BlockPos::new(-16, -1, -16), stone));
+
+    assert_eq!(world.get_block(BlockPos::new(-1, 0, 0)), Some(stone));
+    assert_eq!(
+        world.get_block(BlockPos::new(0, 0, 0)),
+        Some(BlockState::AIR)
+    );
+    assert_eq!(
+        world
+            .get_chunk(ChunkPos::new(-1, 0, 0))
+            .unwrap()
+            .get(LocalPos::new(15, 0, 0)),
+        stone
+    );
+    assert_eq!(
+        world
+            .get_chunk(ChunkPos::new(-1, -1, -1))
+            .unwrap()
+            .get(LocalPos::new(0, 15, 0)),
+        stone
+    );
+    assert_eq!(world.get_block(BlockPos::new(-16, -1, -16)), Some(stone));
+    assert_eq!(world.get_block(BlockPos::new(-17, -1, -16)), None);
+}
+
+#[test]
+fn unloaded_chunks_read_as_nothing() {
+    let (mut world, registry) = setup();
+    let far = BlockPos::new(0, -1, 0);
+
+    assert!(!world.is_loaded(far.chunk()));
+    assert_eq!(world.get_block(far), None);
+    assert_eq!(world.get_light(LightKind::Sky, far), None);
+    assert_eq!(world.get_biome(far), None);
+    assert!(!world.set_block(&registry, far, state(&registry, "stone")));
+    assert!(!world.set_light(LightKind::Block, far, 3));
+    assert!(world.get_chunk_mut(far.chunk()).is_none());
+    assert!(world.take_dirty().is_empty());
+}
+
+#[test]
+fn edits_mark_chunks_dirty() {
+    let (mut world, registry) = setup();
+    let stone = state(&registry, "stone");
+    let origin = ChunkPos::new(0, 0, 0);
+    let west = ChunkPos::new(-1, 0, 0);
+
+    // Inside the chunk, the neighbors don't see it
+    world.set_block(&registry, BlockPos::new(8, 8, 8), stone);
+    assert_eq!(world.take_dirty(), [origin]);
+    assert!(world.take_dirty().is_empty());
+
+    // On the border with a loaded neighbor
+    world.set_block(&registry, BlockPos::new(0, 8, 8), stone);
+    assert_eq!(sorted(world.take_dirty()), [west, origin]);
+
+    // Only changed light counts
+    assert!(world.set_light(LightKind::Block, BlockPos::new(8, 8, 8), 0));
+    assert!(!world.is_dirty(origin));
+    world.set_light(LightKind::Block, BlockPos::new(8, 8, 8), 5);
+    assert!(world.is_dirty(origin));
+
+    world.take_dirty();
+    world.get_chunk_mut(west).unwrap();
+    assert_eq!(world.take_dirty(), [west]);
+
+    world.mark_dirty(ChunkPos::new(5, 5, 5));
+    assert!(world.take_dirty().is_empty());
+}
+
+#[test]
+fn loading_and_unloading_marks_the_neighbors() {
+    let (mut world, _) = setup();
+    let east = ChunkPos::new(1, 0, 0);
+
+    world.insert_chunk(Box::new(Chunk::new(east)));
+    assert_eq!(sorted(world.take_dirty()), [ChunkPos::new(0, 0, 0), east]);
+
+    world.get_chunk_mut(east).unwrap();
+    world.remove_chunk(east).unwrap();
+
+    assert!(!world.is_dirty(east));
+    assert_eq!(world.take_dirty(), [ChunkPos::new(0, 0, 0)]);
+}
+
+#[test]
+fn neighbors_are_found_across_the_origin() {
+    let (world, _) = setup();
+    let origin = ChunkPos::new(0, 0, 0);
+
+    assert_eq!(
+        world.neighbor(origin, Face::West).map(|chunk| chunk.pos),
+        Some(ChunkPos::new(-1, 0, 0))
+    );
+    assert!(world.neighbor(origin, Face::East).is_none());
+    assert!(world.neighbor(origin, Face::Down).is_none());
+
+    let faces: Vec<_> = world.neighbors(origin).map(|(face, _)| face).collect();
+    assert_eq!(faces, [Face::West]);
+
+    // Diagonal chunks aren't neighbors
+    assert_eq!(world.neighbors(ChunkPos::new(-1, -1, -1)).count(), 0);
+    assert_eq!(world.len(), 3);
+}
+
+#[test]
+fn scheduled_ticks_stay_out_of_loaded_chunks() {
+    let (mut world, _) = setup();
+    let origin = ChunkPos::new(0, 0, 0);
+
+    world.ticks_mut().schedule(BlockPos::new(1, 2, 3), 5);
+
+    // What gets sent to clients
+    assert!(world.chunks().all(|chunk| chunk.ticks().is_empty()));
+    assert_eq!(world.export_chunk(origin).unwrap().ticks().len(), 1);
+
+    let mut chunk = world.remove_chunk(origin).unwrap();
+    assert_eq!(chunk.ticks().len(), 1);
+
+    chunk.clear_ticks();
+    world.insert_chunk(chunk);
+
+    assert!(world.export_chunk(origin).unwrap().ticks().is_empty());
+}
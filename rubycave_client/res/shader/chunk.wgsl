@@ -5,11 +5,15 @@
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
+    @location(2) light: f32,
+    @location(3) tint: vec3<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
+    @location(1) light: f32,
+    @location(2) tint: vec3<f32>,
 }
 
 @vertex
@@ -17,10 +21,13 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     out.position = vp * vec4<f32>(in.position, 1.0);
     out.tex_coords = in.tex_coords;
+    out.light = in.light;
+    out.tint = in.tint;
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(atlas, samp, in.tex_coords);
+    let color = textureSample(atlas, samp, in.tex_coords);
+    return vec4<f32>(color.rgb * in.tint * in.light, color.a);
 }
@@ -0,0 +1,34 @@
+@group(0) @binding(0) var samp: sampler;
+@group(0) @binding(1) var font: texture_2d<f32>;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(in.position, 0.0, 1.0);
+    out.tex_coords = in.tex_coords;
+    out.color = in.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let glyph = textureSample(font, samp, in.tex_coords);
+
+    // Negative texture coordinates draw a plain rectangle
+    let color = select(glyph * in.color, in.color, in.tex_coords.x < 0.0);
+
+    // The surface expects premultiplied alpha
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
@@ -1,34 +1,59 @@
-use rubycave::glam::Vec3;
+use std::rc::Rc;
+
+use rubycave::{
+    entity::EntityKind,
+    glam::Vec3,
+    physics::{self, Aabb},
+    world::{BlockRegistry, World},
+};
 
 pub trait Entity {
     fn get_name(&self) -> &str;
     fn get_position(&self) -> Vec3;
+    fn get_eye_position(&self) -> Vec3;
     fn teleport(&mut self, pos: Vec3);
     fn move_by(&mut self, motion: Vec3);
     fn get_head(&self) -> Vec3;
     fn set_head(&mut self, head: Vec3);
     fn move_head(&mut self, rot: Vec3);
-    fn update(&mut self, delta: f32);
+    fn update(&mut self, world: &World, delta: f32);
 }
 
 pub struct Player {
     username: String,
+    registry: Rc<BlockRegistry>,
     head: Vec3,
     friction: f32,
     motion: Vec3,
     position: Vec3,
+    on_ground: bool,
 }
 
 impl Player {
-    pub fn new(username: &str, head: Vec3) -> Self {
+    pub const WIDTH: f32 = 0.6;
+    pub const HEIGHT: f32 = 1.8;
+    pub const EYE_HEIGHT: f32 = 1.62;
+    pub const STEP_HEIGHT: f32 = 0.5;
+
+    pub fn new(username: &str, head: Vec3, registry: Rc<BlockRegistry>) -> Self {
         Self {
             username: username.to_owned(),
+            registry,
             head,
             friction: 1.0 - (1.0 / 64.0),
             motion: Vec3::ZERO,
             position: Vec3::ZERO,
+            on_ground: false,
         }
     }
+
+    pub fn get_aabb(&self) -> Aabb {
+        Aabb::from_feet(self.position, Self::WIDTH, Self::HEIGHT)
+    }
+
+    pub fn is_on_ground(&self) -> bool {
+        self.on_ground
+    }
 }
 
 impl Entity for Player {
@@ -40,8 +65,13 @@ impl Entity for Player {
         self.position
     }
 
+    fn get_eye_position(&self) -> Vec3 {
+        self.position + Vec3::Y * Self::EYE_HEIGHT
+    }
+
     fn teleport(&mut self, pos: Vec3) {
-        self.position = pos
+        self.position = pos;
+        self.motion = Vec3::ZERO;
     }
 
     fn move_by(&mut self, motion: Vec3) {
@@ -62,9 +92,30 @@ impl Entity for Player {
         self.clamp_head();
     }
 
-    fn update(&mut self, delta: f32) {
+    fn update(&mut self, world: &World, delta: f32) {
         self.motion *= self.friction;
-        self.position += self.motion * delta;
+
+        let movement = physics::move_aabb(
+            world,
+            &self.registry,
+            self.get_aabb(),
+            self.motion * delta,
+            self.on_ground,
+            Self::STEP_HEIGHT,
+        );
+
+        self.position += movement.motion;
+        self.on_ground = movement.on_ground;
+
+        if movement.collided_x {
+            self.motion.x = 0.0;
+        }
+        if movement.collided_y {
+            self.motion.y = 0.0;
+        }
+        if movement.collided_z {
+            self.motion.z = 0.0;
+        }
     }
 }
 
@@ -86,3 +137,83 @@ impl Player {
         }
     }
 }
+
+/// An entity the server tells about, only moved by its packets
+pub struct RemoteEntity {
+    kind: EntityKind,
+    name: String,
+    /// Where the server last put it
+    position: Vec3,
+    /// Where it's drawn, catching up with `position`
+    shown: Vec3,
+    head: Vec3,
+}
+
+impl RemoteEntity {
+    /// Share of the distance to the server position covered per second
+    const SMOOTHING: f32 = 15.0;
+    /// Teleports further than this are shown right away
+    const SNAP_DISTANCE: f32 = 8.0;
+
+    pub fn new(kind: EntityKind, name: Option<String>, position: Vec3, head: Vec3) -> Self {
+        Self {
+            kind,
+            name: name.unwrap_or_default(),
+            position,
+            shown: position,
+            head,
+        }
+    }
+
+    pub fn kind(&self) -> EntityKind {
+        self.kind
+    }
+}
+
+impl Entity for RemoteEntity {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_position(&self) -> Vec3 {
+        self.shown
+    }
+
+    fn get_eye_position(&self) -> Vec3 {
+        let eye = match self.kind {
+            EntityKind::Player => Player::EYE_HEIGHT,
+            kind => kind.height() / 2.0,
+        };
+
+        self.shown + Vec3::Y * eye
+    }
+
+    fn teleport(&mut self, pos: Vec3) {
+        if pos.distance(self.shown) > Self::SNAP_DISTANCE {
+            self.shown = pos;
+        }
+
+        self.position = pos;
+    }
+
+    fn move_by(&mut self, motion: Vec3) {
+        self.position += motion;
+    }
+
+    fn get_head(&self) -> Vec3 {
+        self.head
+    }
+
+    fn set_head(&mut self, head: Vec3) {
+        self.head = head;
+    }
+
+    fn move_head(&mut self, rot: Vec3) {
+        self.head += rot;
+    }
+
+    fn update(&mut self, _world: &World, delta: f32) {
+        let t = (Self::SMOOTHING * delta).min(1.0);
+        self.shown = self.shown.lerp(self.position, t);
+    }
+}
@@ -1,29 +1,44 @@
 use std::{
     cell::{Ref, RefCell},
+    collections::HashMap,
     env, io,
     rc::Rc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use crate::{
     config::Config,
-    entity::{Entity, Player},
-    math::FastPrng,
+    entity::{Entity, Player, RemoteEntity},
     render::{self, game::GameRenderer, view::Camera, Renderer, State},
     resource::ResourceManager,
     rpc::{self, tcp::TcpClient, Client},
 };
+use chat::Chat;
 use input::InputMovement;
+use interact::Interaction;
 use rubycave::{
+    entity::EntityId,
     epoch,
     glam::Vec3,
-    protocol::{client, server, Packet},
-    RangeIterator, KEEP_ALIVE_INTERVAL, TICK_RATE,
+    math::FastPrng,
+    protocol::{
+        client,
+        server::{self, MOVE_SCALE},
+        Packet,
+    },
+    world::{block, light, BlockRegistry, BlockState, LocalPos, World},
+    RangeIterator, TICK_RATE,
+};
+use tracing::{debug, error, info};
+use winit::{
+    dpi::PhysicalSize,
+    event::MouseButton,
+    keyboard::{Key, KeyCode},
 };
-use tracing::{error, info};
-use winit::{dpi::PhysicalSize, keyboard::KeyCode};
 
+pub mod chat;
 pub mod input;
+pub mod interact;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -35,6 +50,8 @@ pub enum Error {
     Join(#[from] tokio::task::JoinError),
     #[error("render error")]
     Render(#[from] render::Error),
+    #[error("block registry error")]
+    Block(#[from] block::Error),
 }
 
 pub struct Game<'a> {
@@ -42,13 +59,23 @@ pub struct Game<'a> {
     client: Option<TcpClient>,
     config: Rc<Config>,
     input: InputMovement,
+    interaction: Interaction,
+    chat: Chat,
+    registry: Rc<BlockRegistry>,
+    world: World,
+    /// Entities the server told about, other players included
+    entities: HashMap<EntityId, RemoteEntity>,
     player: Rc<RefCell<Player>>,
     state: Rc<State<'a>>,
     camera: Rc<RefCell<Camera>>,
     renderer: RefCell<GameRenderer<'a>>,
     last_update: Instant,
     last_tick: Instant,
-    last_keep_alive: Instant,
+    /// Round trip time to the server, as it last measured it
+    latency: Option<Duration>,
+    /// Position and head last sent to the server, `None` until it placed the
+    /// player
+    last_move: Option<(Vec3, Vec3)>,
 }
 
 impl<'a> Game<'a> {
@@ -68,13 +95,27 @@ impl<'a> Game<'a> {
         let client = if !client.start().await {
             error!("couldn't start rpc client");
             None
-        } else {
-            client.shake(&username).await?;
+        } else if let Some(negotiated) = client.shake(&username).await? {
+            info!(
+                "speaking protocol version {} with {:?}, chunks compressed with {:?}",
+                negotiated.version, negotiated.features, negotiated.compression
+            );
             Some(client)
+        } else {
+            error!("couldn't shake hands with the server");
+            client.stop();
+            None
         };
 
+        let registry = Rc::new(BlockRegistry::builtin()?);
         let input = InputMovement::new(config.clone());
-        let player = Rc::new(RefCell::new(Player::new(&username, Vec3::ZERO)));
+        let stone = registry.default_state_by_name("stone");
+        let interaction = Interaction::new(stone.unwrap_or(BlockState::AIR));
+        let player = Rc::new(RefCell::new(Player::new(
+            &username,
+            Vec3::ZERO,
+            registry.clone(),
+        )));
         let state = Rc::new(State::new(target, width, height).await?);
         let resource_man = Rc::new(ResourceManager::new(
             env::current_exe()?.parent().unwrap().join("res").as_path(),
@@ -91,22 +132,60 @@ impl<'a> Game<'a> {
             client,
             config: config.clone(),
             input,
+            interaction,
+            chat: Chat::new(),
+            registry: registry.clone(),
+            world: World::new(),
+            entities: HashMap::new(),
             player,
             state: state.clone(),
             camera: camera.clone(),
-            renderer: RefCell::new(GameRenderer::new(state, config, resource_man, camera)?),
+            renderer: RefCell::new(GameRenderer::new(
+                state,
+                config,
+                resource_man,
+                camera,
+                registry,
+            )?),
             last_update: last,
             last_tick: last,
-            last_keep_alive: last,
+            latency: None,
+            last_move: None,
         })
     }
 
     pub fn key(&mut self, key: KeyCode, down: bool) {
-        self.input.key(key, down)
+        // Releases still go through so no key stays held while typing
+        if self.chat.is_open() && down {
+            return;
+        }
+
+        if key == KeyCode::KeyT && down {
+            self.chat.open();
+        } else {
+            self.input.key(key, down)
+        }
+    }
+
+    pub fn is_chat_open(&self) -> bool {
+        self.chat.is_open()
+    }
+
+    /// Types into the open chat, `text` being what the key produced
+    pub fn chat_key(&mut self, key: &Key, text: Option<&str>) {
+        self.chat.key(key, text)
     }
 
     pub fn mouse(&mut self, delta: (f64, f64), window_size: PhysicalSize<u32>) {
-        self.input.mouse(delta, window_size)
+        if !self.chat.is_open() {
+            self.input.mouse(delta, window_size)
+        }
+    }
+
+    pub fn mouse_button(&mut self, button: MouseButton, down: bool) {
+        if !self.chat.is_open() || !down {
+            self.interaction.button(button, down)
+        }
     }
 
     pub async fn update_async(&mut self) -> Result<(), Error> {
@@ -114,7 +193,22 @@ impl<'a> Game<'a> {
             return Ok(());
         };
 
-        match client.poll()? {
+        let packet = client.poll()?;
+
+        if let Some(Packet::Server(packet)) = &packet {
+            if let Err(e) = client.get_packet_validator().check_server(packet) {
+                error!("{e}");
+                client
+                    .disconnect(client::DisconnectReason::Packet(e))
+                    .await?;
+                client.stop();
+                self.client = None;
+
+                return Ok(());
+            }
+        }
+
+        match packet {
             Some(Packet::Server(server::Packet::Kick { reason })) => {
                 info!("kicked for: {:?}", reason);
 
@@ -123,6 +217,14 @@ impl<'a> Game<'a> {
 
                 return Ok(());
             }
+            Some(Packet::Server(server::Packet::KeepAlive { id, latency })) => {
+                client.send(client::Packet::KeepAlive { id }).await?;
+
+                if let Some(latency) = latency {
+                    debug!("latency: {latency} ms");
+                    self.latency = Some(Duration::from_millis(latency as u64));
+                }
+            }
             Some(Packet::Server(server::Packet::Teleport {
                 x,
                 y,
@@ -132,46 +234,165 @@ impl<'a> Game<'a> {
             })) => {
                 info!("teleported to: {x:.1},{y:.1},{z:.1} {yaw:.1},{pitch:.1}");
 
-                let mut player = self.player.borrow_mut();
+                let (current, on_ground) = {
+                    let mut player = self.player.borrow_mut();
+
+                    player.teleport(Vec3::new(x, y, z));
+                    player.set_head(Vec3::new(yaw, pitch, 0.0));
+
+                    (
+                        (player.get_position(), player.get_head()),
+                        player.is_on_ground(),
+                    )
+                };
+
+                // The server ignores moves until one confirms the teleport
+                client
+                    .send(client::Packet::Move {
+                        x: current.0.x,
+                        y: current.0.y,
+                        z: current.0.z,
+                        yaw: current.1.x,
+                        pitch: current.1.y,
+                        on_ground,
+                    })
+                    .await?;
+
+                self.last_move = Some(current);
+            }
+            Some(Packet::Server(server::Packet::Chunk(mut chunk))) => {
+                // Only the server ticks, they'd pile up here
+                chunk.clear_ticks();
+                chunk.compute_heightmaps(&self.registry);
+                self.world.insert_chunk(chunk);
+            }
+            Some(Packet::Server(server::Packet::BlockChange { pos, state })) => {
+                light::set_block(&mut self.world, &self.registry, pos, state);
+            }
+            Some(Packet::Server(server::Packet::MultiBlockChange { chunk, changes })) => {
+                for change in changes {
+                    let pos = chunk.block(LocalPos::from_index(change.index as usize));
+                    light::set_block(&mut self.world, &self.registry, pos, change.state);
+                }
+            }
+            Some(Packet::Server(server::Packet::SpawnEntity {
+                id,
+                kind,
+                name,
+                position,
+                yaw,
+                pitch,
+            })) => {
+                self.entities.insert(
+                    id,
+                    RemoteEntity::new(kind, name, Vec3::from(position), Vec3::new(yaw, pitch, 0.0)),
+                );
+            }
+            Some(Packet::Server(server::Packet::MoveEntity { id, delta })) => {
+                if let Some(entity) = self.entities.get_mut(&id) {
+                    entity.move_by(Vec3::from(delta.map(f32::from)) / MOVE_SCALE);
+                }
+            }
+            Some(Packet::Server(server::Packet::TeleportEntity { id, position })) => {
+                if let Some(entity) = self.entities.get_mut(&id) {
+                    entity.teleport(Vec3::from(position));
+                }
+            }
+            Some(Packet::Server(server::Packet::RotateEntity { id, yaw, pitch })) => {
+                if let Some(entity) = self.entities.get_mut(&id) {
+                    entity.set_head(Vec3::new(yaw, pitch, 0.0));
+                }
+            }
+            Some(Packet::Server(server::Packet::DespawnEntity { id })) => {
+                self.entities.remove(&id);
+            }
+            Some(Packet::Server(server::Packet::Chat { segments })) => {
+                let text = segments.iter().map(|segment| segment.text.as_str());
+                info!("chat: {}", text.collect::<String>());
 
-                player.teleport(Vec3::new(x, y, z));
-                player.set_head(Vec3::new(yaw, pitch, 0.0));
+                self.chat.receive(segments);
             }
             _ => {}
         }
 
         if self.last_tick.elapsed().as_nanos() as u64 >= 1_000_000_000 / (TICK_RATE as u64) {
             // info!("tick elapsed");
+            let (current, on_ground, eye) = {
+                let player = self.player.borrow();
+                (
+                    (player.get_position(), player.get_head()),
+                    player.is_on_ground(),
+                    player.get_eye_position(),
+                )
+            };
+
+            if self.last_move.is_some_and(|last| last != current) {
+                client
+                    .send(client::Packet::Move {
+                        x: current.0.x,
+                        y: current.0.y,
+                        z: current.0.z,
+                        yaw: current.1.x,
+                        pitch: current.1.y,
+                        on_ground,
+                    })
+                    .await?;
+
+                self.last_move = Some(current);
+            }
+
+            // Block changes come back from the server
+            for packet in self
+                .interaction
+                .update(&self.world, &self.registry, eye, current.1)
+            {
+                client.send(packet).await?;
+            }
+
             self.last_tick = Instant::now();
         }
 
-        if self.last_keep_alive.elapsed().as_millis() as u32 >= KEEP_ALIVE_INTERVAL {
-            client
-                .send(client::Packet::KeepAlive {
-                    epoch: epoch().as_millis() as u64,
-                })
-                .await?;
-
-            self.last_keep_alive = Instant::now();
+        for message in self.chat.take_outgoing() {
+            client.send(client::Packet::Chat { message }).await?;
         }
 
         Ok(())
     }
 
     pub fn update(&mut self) -> Result<(), Error> {
+        let delta = self.last_update.elapsed().as_secs_f32();
+
         {
             self.input.update(self.player.borrow_mut());
         }
 
         {
-            self.player
-                .borrow_mut()
-                .update(self.last_update.elapsed().as_secs_f32());
+            self.player.borrow_mut().update(&self.world, delta);
+        }
+
+        for entity in self.entities.values_mut() {
+            entity.update(&self.world, delta);
         }
 
         self.last_update = Instant::now();
 
-        self.renderer.borrow_mut().update();
+        {
+            let mut renderer = self.renderer.borrow_mut();
+
+            for pos in self.world.take_dirty() {
+                renderer.load_chunk(&self.world, pos);
+            }
+
+            renderer.set_entities(&self.world, self.entities.values());
+            let status = match self.latency {
+                Some(latency) => format!("{} ms", latency.as_millis()),
+                None => String::new(),
+            };
+
+            renderer.set_text(self.chat.visible(), self.chat.input(), &status);
+
+            renderer.update();
+        }
 
         Ok(())
     }
@@ -180,7 +401,7 @@ impl<'a> Game<'a> {
         {
             let mut camera = self.camera.borrow_mut();
             let player = self.player.borrow();
-            let pos = player.get_position();
+            let pos = player.get_eye_position();
             let ang = player.get_head();
 
             if camera.pos != pos || camera.ang != ang {
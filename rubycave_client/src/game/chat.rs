@@ -0,0 +1,111 @@
+use std::{collections::VecDeque, time::Instant};
+
+use rubycave::protocol::chat::{self, Segment, MAX_MESSAGE_LENGTH};
+use winit::keyboard::{Key, NamedKey};
+
+/// Messages kept for scrolling back
+const HISTORY: usize = 100;
+
+/// Messages shown at once, more while typing
+const SHOWN: usize = 10;
+const SHOWN_OPEN: usize = 20;
+
+/// Seconds a message stays on screen, the last one spent fading out
+const SHOW_TIME: f32 = 10.0;
+const FADE_TIME: f32 = 1.0;
+
+/// Received messages and the line being typed
+pub struct Chat {
+    history: VecDeque<(Vec<Segment>, Instant)>,
+    input: Option<String>,
+    /// Messages typed but not sent to the server yet
+    outgoing: Vec<String>,
+}
+
+impl Chat {
+    pub fn new() -> Self {
+        Self {
+            history: VecDeque::new(),
+            input: None,
+            outgoing: Vec::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn open(&mut self) {
+        self.input = Some(String::new());
+    }
+
+    pub fn input(&self) -> Option<&str> {
+        self.input.as_deref()
+    }
+
+    /// Handles a key pressed while typing
+    pub fn key(&mut self, key: &Key, text: Option<&str>) {
+        let Some(input) = &mut self.input else {
+            return;
+        };
+
+        match key {
+            Key::Named(NamedKey::Escape) => self.input = None,
+            Key::Named(NamedKey::Enter) => {
+                let message = input.trim().to_owned();
+
+                if chat::is_valid_message(&message) {
+                    self.outgoing.push(message);
+                }
+
+                self.input = None;
+            }
+            Key::Named(NamedKey::Backspace) => {
+                input.pop();
+            }
+            _ => {
+                let text = text.unwrap_or_default();
+
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    if input.chars().count() >= MAX_MESSAGE_LENGTH {
+                        break;
+                    }
+
+                    input.push(c);
+                }
+            }
+        }
+    }
+
+    pub fn receive(&mut self, segments: Vec<Segment>) {
+        if self.history.len() >= HISTORY {
+            self.history.pop_back();
+        }
+
+        self.history.push_front((segments, Instant::now()));
+    }
+
+    pub fn take_outgoing(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    /// Messages to draw with their opacity, newest first
+    pub fn visible(&self) -> impl Iterator<Item = (&[Segment], f32)> {
+        let open = self.is_open();
+
+        self.history
+            .iter()
+            .take(if open { SHOWN_OPEN } else { SHOWN })
+            .map(move |(segments, received)| {
+                let age = received.elapsed().as_secs_f32();
+                let alpha = if open {
+                    1.0
+                } else {
+                    ((SHOW_TIME - age) / FADE_TIME).clamp(0.0, 1.0)
+                };
+
+                (segments.as_slice(), alpha)
+            })
+            .filter(|(_, alpha)| *alpha > 0.0)
+    }
+}
@@ -0,0 +1,119 @@
+use rubycave::{
+    glam::{EulerRot, Quat, Vec3},
+    protocol::client::{self, DigAction},
+    world::{raycast, BlockPos, BlockRegistry, BlockState, Face, World},
+    REACH,
+};
+use winit::event::MouseButton;
+
+struct Dig {
+    pos: BlockPos,
+    face: Face,
+    ticks: u32,
+}
+
+/// Digs with the left button, places with the right one and picks the block to
+/// place with the middle one
+pub struct Interaction {
+    held: [bool; 3],
+    clicked: [bool; 3],
+    dig: Option<Dig>,
+    selected: BlockState,
+}
+
+impl Interaction {
+    pub fn new(selected: BlockState) -> Self {
+        Self {
+            held: [false; 3],
+            clicked: [false; 3],
+            dig: None,
+            selected,
+        }
+    }
+
+    pub fn button(&mut self, button: MouseButton, down: bool) {
+        let index = match button {
+            MouseButton::Left => 0,
+            MouseButton::Right => 1,
+            MouseButton::Middle => 2,
+            _ => return,
+        };
+
+        self.held[index] = down;
+        self.clicked[index] |= down;
+    }
+
+    /// Runs one tick against the block looked at from `eye`, returning the
+    /// packets to send
+    pub fn update(
+        &mut self,
+        world: &World,
+        registry: &BlockRegistry,
+        eye: Vec3,
+        head: Vec3,
+    ) -> Vec<client::Packet> {
+        let direction = Quat::from_euler(EulerRot::YXZ, head.x, head.y, 0.0) * Vec3::NEG_Z;
+        let hit = raycast(world, eye, direction, REACH);
+        let clicked = std::mem::take(&mut self.clicked);
+        let mut packets = Vec::new();
+
+        let target = hit.filter(|_| self.held[0]);
+
+        // Stopped digging or looked at another block
+        if let Some(dig) = &self.dig {
+            if target.is_none_or(|hit| hit.pos != dig.pos) {
+                packets.push(client::Packet::Dig {
+                    action: DigAction::Cancel,
+                    pos: dig.pos,
+                    face: dig.face,
+                });
+                self.dig = None;
+            }
+        }
+
+        if let Some(hit) = target {
+            let dig = self.dig.get_or_insert_with(|| {
+                packets.push(client::Packet::Dig {
+                    action: DigAction::Start,
+                    pos: hit.pos,
+                    face: hit.face,
+                });
+
+                Dig {
+                    pos: hit.pos,
+                    face: hit.face,
+                    ticks: 0,
+                }
+            });
+
+            let needed = registry.get(hit.state.id).and_then(|def| def.dig_ticks());
+
+            if needed.is_some_and(|needed| dig.ticks >= needed) {
+                packets.push(client::Packet::Dig {
+                    action: DigAction::Finish,
+                    pos: dig.pos,
+                    face: dig.face,
+                });
+                self.dig = None;
+            } else {
+                dig.ticks += 1;
+            }
+        }
+
+        if let Some(hit) = hit {
+            if clicked[1] {
+                packets.push(client::Packet::Place {
+                    pos: hit.pos,
+                    face: hit.face,
+                    state: self.selected,
+                });
+            }
+
+            if clicked[2] {
+                self.selected = hit.state;
+            }
+        }
+
+        packets
+    }
+}
@@ -9,7 +9,6 @@ use winit::event_loop::{ControlFlow, EventLoop};
 mod config;
 mod entity;
 mod game;
-mod math;
 mod render;
 mod resource;
 mod rpc;
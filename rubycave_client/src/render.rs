@@ -9,7 +9,9 @@ use image::ImageReader;
 
 use crate::resource;
 
+pub mod entity;
 pub mod game;
+pub mod overlay;
 pub mod test;
 pub mod view;
 pub mod world;
@@ -0,0 +1,128 @@
+use rubycave::{
+    entity::EntityKind,
+    glam::{EulerRot, Quat, Vec3},
+    world::{BlockPos, BlockRegistry, Face, World},
+};
+
+use crate::entity::{Entity, Player, RemoteEntity};
+
+use super::world::{face_corners, face_light, tile_coords, ChunkVertex, Colormaps};
+
+/// Players are a body with a head on top, both in wool tinted by their name
+const BODY_HEIGHT: f32 = 1.3;
+const HEAD_SIZE: f32 = 0.5;
+
+/// A box around `center`, turned by `rotation`
+struct Cuboid {
+    center: Vec3,
+    size: Vec3,
+    rotation: Quat,
+}
+
+/// Builds the vertices of every entity, drawn with the chunk pipeline
+pub(super) fn mesh_entities<'e>(
+    world: &World,
+    registry: &BlockRegistry,
+    colormaps: &Colormaps,
+    entities: impl IntoIterator<Item = &'e RemoteEntity>,
+) -> Vec<ChunkVertex> {
+    let mut vertices = Vec::new();
+
+    for entity in entities {
+        let position = entity.get_position();
+        let kind = entity.kind();
+        let center = position + Vec3::Y * (kind.height() / 2.0);
+        let block = BlockPos::new(
+            center.x.floor() as i32,
+            center.y.floor() as i32,
+            center.z.floor() as i32,
+        );
+        let light = face_light(world, block);
+
+        match kind {
+            EntityKind::FallingBlock(state) | EntityKind::Item(state) => {
+                let Some(def) = registry.get(state.id) else {
+                    continue;
+                };
+
+                let biome = world.get_biome(block).unwrap_or_default();
+                let cuboid = Cuboid {
+                    center,
+                    size: Vec3::splat(kind.width()),
+                    rotation: Quat::IDENTITY,
+                };
+
+                mesh_cuboid(&mut vertices, &cuboid, light, |face| {
+                    let tint = def
+                        .tint(face)
+                        .map_or(Vec3::ONE, |colormap| colormaps.color(colormap, biome));
+
+                    (def.texture_for(state, face), tint)
+                });
+            }
+            EntityKind::Player => {
+                let Some(wool) = registry.get_by_name("wool") else {
+                    continue;
+                };
+                let Some(state) = registry.default_state(wool.id) else {
+                    continue;
+                };
+
+                let tint = name_color(entity.get_name());
+                let head = entity.get_head();
+                let body = Cuboid {
+                    center: position + Vec3::Y * (BODY_HEIGHT / 2.0),
+                    size: Vec3::new(Player::WIDTH, BODY_HEIGHT, Player::WIDTH / 2.0),
+                    rotation: Quat::from_rotation_y(head.x),
+                };
+                let head = Cuboid {
+                    center: position + Vec3::Y * (BODY_HEIGHT + HEAD_SIZE / 2.0),
+                    size: Vec3::splat(HEAD_SIZE),
+                    rotation: Quat::from_euler(EulerRot::YXZ, head.x, head.y, 0.0),
+                };
+
+                for cuboid in [body, head] {
+                    mesh_cuboid(&mut vertices, &cuboid, light, |face| {
+                        (wool.texture_for(state, face), tint)
+                    });
+                }
+            }
+        }
+    }
+
+    vertices
+}
+
+fn mesh_cuboid(
+    vertices: &mut Vec<ChunkVertex>,
+    cuboid: &Cuboid,
+    light: f32,
+    texture: impl Fn(Face) -> (u16, Vec3),
+) {
+    for face in Face::ALL {
+        let corners = face_corners(face);
+        let (tile, tint) = texture(face);
+        let tex_coords = tile_coords(tile);
+
+        for i in [0, 1, 2, 0, 2, 3] {
+            let corner = (corners[i] - 0.5) * cuboid.size;
+
+            vertices.push(ChunkVertex {
+                position: cuboid.center + cuboid.rotation * corner,
+                tex_coords: tex_coords[i],
+                light,
+                tint,
+            });
+        }
+    }
+}
+
+/// A light color that stays the same for a name, so players can be told apart
+fn name_color(name: &str) -> Vec3 {
+    let hash = name.bytes().fold(0x811c_9dc5u32, |h, b| {
+        (h ^ b as u32).wrapping_mul(0x0100_0193)
+    });
+    let [r, g, b, _] = hash.to_le_bytes();
+
+    Vec3::new(r as f32, g as f32, b as f32) / 255.0 * 0.6 + 0.4
+}
@@ -1,11 +1,18 @@
 use std::{cell::RefCell, rc::Rc};
 
-use crate::{config::Config, render, resource::ResourceManager};
+use rubycave::{
+    protocol::chat::Segment,
+    world::{BlockRegistry, ChunkPos, World},
+};
 
-use super::{view::Camera, world::ChunkRenderer, Renderer, State};
+use crate::{config::Config, entity::RemoteEntity, render, resource::ResourceManager};
+
+use super::{overlay::OverlayRenderer, view::Camera, world::ChunkRenderer, Renderer, State};
 
 pub struct GameRenderer<'a> {
+    state: Rc<State<'a>>,
     world: ChunkRenderer<'a>,
+    overlay: OverlayRenderer<'a>,
 }
 
 impl<'a> GameRenderer<'a> {
@@ -14,23 +21,59 @@ impl<'a> GameRenderer<'a> {
         config: Rc<Config>,
         resource_man: Rc<ResourceManager>,
         camera: Rc<RefCell<Camera>>,
+        registry: Rc<BlockRegistry>,
     ) -> Result<Self, render::Error> {
         Ok(Self {
-            world: ChunkRenderer::new(state, config, resource_man, camera)?,
+            state: state.clone(),
+            world: ChunkRenderer::new(
+                state.clone(),
+                config,
+                resource_man.clone(),
+                camera,
+                registry,
+            )?,
+            overlay: OverlayRenderer::new(state, resource_man)?,
         })
     }
+
+    pub fn load_chunk(&mut self, world: &World, pos: ChunkPos) {
+        self.world.load_chunk(world, pos)
+    }
+
+    /// Meshes the entities to draw next frame
+    pub fn set_entities<'e>(
+        &mut self,
+        world: &World,
+        entities: impl IntoIterator<Item = &'e RemoteEntity>,
+    ) {
+        self.world.load_entities(world, entities)
+    }
+
+    /// Lays out the text to draw next frame, see [`OverlayRenderer::set_text`]
+    pub fn set_text<'m>(
+        &mut self,
+        messages: impl IntoIterator<Item = (&'m [Segment], f32)>,
+        input: Option<&str>,
+        status: &str,
+    ) {
+        self.overlay.set_text(messages, input, status)
+    }
 }
 
 impl Renderer for GameRenderer<'_> {
     fn update(&mut self) {
-        self.world.update()
+        self.world.update();
+        self.overlay.update();
     }
 
     fn render<'p, 'a: 'p>(&'a mut self, frame_view: &wgpu::TextureView) -> wgpu::CommandBuffer {
-        self.world.render(frame_view)
+        // Submitted first so the overlay lands on top
+        self.state.submit(self.world.render(frame_view));
+        self.overlay.render(frame_view)
     }
 
     fn resize(&mut self, width: u32, height: u32) {
-        self.world.resize(width, height)
+        self.world.resize(width, height);
+        self.overlay.resize(width, height);
     }
 }
@@ -0,0 +1,428 @@
+use std::{io::BufReader, mem, path::Path, rc::Rc};
+
+use bytemuck::{Pod, Zeroable};
+use image::{ImageReader, RgbaImage};
+use rubycave::{
+    glam::{Vec2, Vec4},
+    protocol::chat::{Color, Segment},
+};
+
+use crate::{render, resource::ResourceManager, SHADER_DIR, TEXTURE_DIR};
+
+use super::{Renderer, SizedSurface, State, Vertex};
+
+const LABEL: &str = "Overlay renderer";
+
+/// Screen pixels per font pixel
+const SCALE: f32 = 2.0;
+
+/// In font pixels
+const GLYPH_SIZE: f32 = 8.0;
+const LINE_HEIGHT: f32 = 9.0;
+const CHAT_WIDTH: f32 = 320.0;
+const MARGIN: f32 = 2.0;
+const INPUT_HEIGHT: f32 = 12.0;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct OverlayVertex {
+    /// In normalized device coordinates
+    position: Vec2,
+    /// Negative for a plain rectangle
+    tex_coords: Vec2,
+    color: Vec4,
+}
+
+/// Draws the chat and a status line on top of the world
+pub struct OverlayRenderer<'a> {
+    state: Rc<State<'a>>,
+
+    render_pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+
+    font: Font,
+    vertex_buffer: Option<wgpu::Buffer>,
+    vertex_count: u32,
+}
+
+/// Widths of the glyphs in the font atlas, indexed by character code
+struct Font {
+    widths: [f32; 256],
+}
+
+/// A character of a message with its style
+#[derive(Clone, Copy)]
+struct Glyph {
+    c: char,
+    color: Color,
+    bold: bool,
+}
+
+/// Vertices being laid out, in font pixels from the top left of the screen
+struct Mesh<'f> {
+    font: &'f Font,
+    size: Vec2,
+    vertices: Vec<OverlayVertex>,
+}
+
+impl OverlayVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+        0 => Float32x2, 1 => Float32x2, 2 => Float32x4
+    ];
+}
+
+impl<'a> Vertex<'a> for OverlayVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+impl<'a> OverlayRenderer<'a> {
+    pub fn new(
+        state: Rc<State<'a>>,
+        resource_man: Rc<ResourceManager>,
+    ) -> Result<Self, render::Error> {
+        let device = &state.device;
+
+        let mut res = resource_man.get_from_path(&Path::new(SHADER_DIR).join("overlay.wgsl"))?;
+        let source = res.read_to_str()?;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(LABEL),
+            source: wgpu::ShaderSource::Wgsl(source.as_str().into()),
+        });
+
+        let (sampler, sampler_entry) = state.create_sampler(
+            Some(LABEL),
+            0,
+            &wgpu::SamplerDescriptor {
+                label: None,
+                mag_filter: wgpu::FilterMode::Nearest,
+                min_filter: wgpu::FilterMode::Nearest,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                ..wgpu::SamplerDescriptor::default()
+            },
+            false,
+        );
+
+        let font_path = Path::new(TEXTURE_DIR).join("font.png");
+        let mut res = resource_man.get_from_path(&font_path)?;
+        let (font_texture, font_entry) = state.load_texture(
+            Some((LABEL.to_owned() + " font").as_str()),
+            1,
+            res.open()?,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            false,
+        )?;
+        let font_view = font_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut res = resource_man.get_from_path(&font_path)?;
+        let font = Font::new(
+            &ImageReader::new(BufReader::new(res.open()?))
+                .with_guessed_format()?
+                .decode()?
+                .into_rgba8(),
+        );
+
+        let (bind_group_layout, bind_group) = state.create_bind_group(
+            Some(LABEL),
+            &[sampler_entry, font_entry],
+            &[
+                wgpu::BindGroupEntry {
+                    binding: sampler_entry.binding,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: font_entry.binding,
+                    resource: wgpu::BindingResource::TextureView(&font_view),
+                },
+            ],
+        );
+
+        let (_, render_pipeline) = state.create_render_pipeline(
+            Some(LABEL),
+            &[&bind_group_layout],
+            super::get_vert_state(&shader, &[OverlayVertex::desc()]),
+            super::get_frag_state(
+                &shader,
+                &[Some(state.get_target_state(
+                    wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+                ))],
+            ),
+            super::get_raster_state(false),
+            None,
+        );
+
+        Ok(Self {
+            state,
+
+            render_pipeline,
+            bind_group,
+
+            font,
+            vertex_buffer: None,
+            vertex_count: 0,
+        })
+    }
+
+    /// Lays out the chat, `messages` newest first with their opacity, above
+    /// the line being typed if any, and `status` in the top left corner
+    pub fn set_text<'m>(
+        &mut self,
+        messages: impl IntoIterator<Item = (&'m [Segment], f32)>,
+        input: Option<&str>,
+        status: &str,
+    ) {
+        let (width, height) = self.state.surface_config.get_size();
+        let mut mesh = Mesh {
+            font: &self.font,
+            size: Vec2::new(width as f32, height as f32) / SCALE,
+            vertices: Vec::new(),
+        };
+
+        let status = status
+            .chars()
+            .map(|c| Glyph {
+                c,
+                color: Color::Gray,
+                bold: false,
+            })
+            .collect::<Vec<_>>();
+
+        mesh.text(&status, Vec2::splat(MARGIN), 1.0);
+
+        let mut y = mesh.size.y - MARGIN - INPUT_HEIGHT;
+
+        if let Some(input) = input {
+            let line = input
+                .chars()
+                .chain(['_'])
+                .map(|c| Glyph {
+                    c,
+                    color: Color::White,
+                    bold: false,
+                })
+                .collect::<Vec<_>>();
+
+            mesh.rect(
+                Vec2::new(MARGIN, y),
+                Vec2::new(mesh.size.x - MARGIN, y + INPUT_HEIGHT),
+                Vec4::new(0.0, 0.0, 0.0, 0.5),
+            );
+
+            // Keeps the end of long lines in view
+            let mut start = 0;
+            while mesh.font.line_width(&line[start..]) > mesh.size.x - MARGIN * 4.0 {
+                start += 1;
+            }
+
+            mesh.text(&line[start..], Vec2::new(MARGIN * 2.0, y + 2.0), 1.0);
+        }
+
+        y -= MARGIN;
+
+        for (segments, alpha) in messages {
+            let glyphs = segments
+                .iter()
+                .flat_map(|segment| {
+                    segment.text.chars().map(|c| Glyph {
+                        c,
+                        color: segment.color,
+                        bold: segment.bold,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            for line in self.font.wrap(&glyphs, CHAT_WIDTH).into_iter().rev() {
+                y -= LINE_HEIGHT;
+
+                mesh.rect(
+                    Vec2::new(MARGIN, y),
+                    Vec2::new(MARGIN + CHAT_WIDTH + MARGIN * 2.0, y + LINE_HEIGHT),
+                    Vec4::new(0.0, 0.0, 0.0, 0.5 * alpha),
+                );
+                mesh.text(line, Vec2::new(MARGIN * 2.0, y + 1.0), alpha);
+            }
+
+            if y < 0.0 {
+                break;
+            }
+        }
+
+        let vertices = mesh.vertices;
+        let size = mem::size_of_val(vertices.as_slice());
+
+        // Grown only, the chat changes every frame while it fades
+        if self
+            .vertex_buffer
+            .as_ref()
+            .is_none_or(|buffer| buffer.size() < size as wgpu::BufferAddress)
+        {
+            self.vertex_buffer = Some(self.state.create_buffer(
+                Some((LABEL.to_owned() + " vertex").as_str()),
+                size,
+                wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            ));
+        }
+
+        if let Some(buffer) = self.vertex_buffer.as_ref().filter(|_| size > 0) {
+            self.state
+                .queue
+                .write_buffer(buffer, 0, bytemuck::cast_slice(&vertices));
+        }
+
+        self.vertex_count = vertices.len() as u32;
+    }
+}
+
+impl Renderer for OverlayRenderer<'_> {
+    fn update(&mut self) {}
+
+    fn render<'p, 'a: 'p>(&'a mut self, frame_view: &wgpu::TextureView) -> wgpu::CommandBuffer {
+        let mut encoder = self.state.create_command_encoder(Some(LABEL));
+
+        if let Some(buffer) = self
+            .vertex_buffer
+            .as_ref()
+            .filter(|_| self.vertex_count > 0)
+        {
+            let mut pass = super::begin_render_pass(
+                &mut encoder,
+                &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                None,
+            );
+
+            pass.set_pipeline(&self.render_pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.set_vertex_buffer(0, buffer.slice(..));
+            pass.draw(0..self.vertex_count, 0..1);
+        }
+
+        encoder.finish()
+    }
+
+    fn resize(&mut self, _width: u32, _height: u32) {}
+}
+
+impl Font {
+    /// Measures each glyph by its rightmost opaque column
+    fn new(image: &RgbaImage) -> Self {
+        let cell = image.width() / 16;
+        let mut widths = [0.0; 256];
+
+        for (index, width) in widths.iter_mut().enumerate() {
+            let (x, y) = (index as u32 % 16 * cell, index as u32 / 16 * cell);
+            let columns = (0..cell)
+                .rev()
+                .find(|column| (0..cell).any(|row| image.get_pixel(x + column, y + row).0[3] > 0))
+                .map_or(0, |column| column + 1);
+
+            *width = columns as f32 * GLYPH_SIZE / cell as f32;
+        }
+
+        // Space has no pixels to measure
+        widths[' ' as usize] = GLYPH_SIZE / 2.0;
+
+        Self { widths }
+    }
+
+    /// Characters outside the atlas are drawn as question marks
+    fn index(c: char) -> usize {
+        match c {
+            ' '..='~' => c as usize,
+            _ => '?' as usize,
+        }
+    }
+
+    fn advance(&self, glyph: Glyph) -> f32 {
+        self.widths[Self::index(glyph.c)] + if glyph.bold { 2.0 } else { 1.0 }
+    }
+
+    fn line_width(&self, line: &[Glyph]) -> f32 {
+        line.iter().map(|glyph| self.advance(*glyph)).sum()
+    }
+
+    /// Splits `glyphs` into lines no wider than `width`
+    fn wrap<'g>(&self, glyphs: &'g [Glyph], width: f32) -> Vec<&'g [Glyph]> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut x = 0.0;
+
+        for (i, glyph) in glyphs.iter().enumerate() {
+            let advance = self.advance(*glyph);
+
+            if x + advance > width && i > start {
+                lines.push(&glyphs[start..i]);
+                start = i;
+                x = 0.0;
+            }
+
+            x += advance;
+        }
+
+        lines.push(&glyphs[start..]);
+        lines
+    }
+}
+
+impl Mesh<'_> {
+    fn quad(&mut self, min: Vec2, max: Vec2, tex_min: Vec2, tex_max: Vec2, color: Vec4) {
+        let corners = [
+            (min, tex_min),
+            (Vec2::new(max.x, min.y), Vec2::new(tex_max.x, tex_min.y)),
+            (max, tex_max),
+            (Vec2::new(min.x, max.y), Vec2::new(tex_min.x, tex_max.y)),
+        ];
+
+        for i in [0, 1, 2, 0, 2, 3] {
+            let (position, tex_coords) = corners[i];
+            let ndc = position / self.size * Vec2::new(2.0, -2.0) + Vec2::new(-1.0, 1.0);
+
+            self.vertices.push(OverlayVertex {
+                position: ndc,
+                tex_coords,
+                color,
+            });
+        }
+    }
+
+    fn rect(&mut self, min: Vec2, max: Vec2, color: Vec4) {
+        self.quad(min, max, Vec2::NEG_ONE, Vec2::NEG_ONE, color);
+    }
+
+    /// Draws a line of text with a shadow, bold glyphs twice
+    fn text(&mut self, line: &[Glyph], origin: Vec2, alpha: f32) {
+        for (offset, shade) in [(Vec2::ONE, 0.25), (Vec2::ZERO, 1.0)] {
+            let mut x = origin.x;
+
+            for glyph in line {
+                let index = Font::index(glyph.c);
+                let tile = Vec2::new((index % 16) as f32, (index / 16) as f32) / 16.0;
+                let [r, g, b] = glyph.color.rgb().map(|c| c as f32 / 255.0 * shade);
+                let color = Vec4::new(r, g, b, alpha);
+
+                let min = Vec2::new(x, origin.y) + offset;
+                let size = Vec2::splat(GLYPH_SIZE);
+
+                for bold in 0..=glyph.bold as u32 {
+                    let min = min + Vec2::X * bold as f32;
+                    self.quad(min, min + size, tile, tile + 1.0 / 16.0, color);
+                }
+
+                x += self.font.advance(*glyph);
+            }
+        }
+    }
+}
@@ -1,37 +1,36 @@
-use std::{cell::RefCell, mem, path::Path, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, io::BufReader, mem, path::Path, rc::Rc};
 
 use bytemuck::{Pod, Zeroable};
-use rubycave::glam::{Mat4, Vec2, Vec3};
+use image::{ImageReader, RgbaImage};
+use rubycave::{
+    glam::{Mat4, Vec2, Vec3},
+    world::{
+        block::ATLAS_TILES, light::MAX_LIGHT, Biome, BlockId, BlockPos, BlockRegistry, ChunkPos,
+        Colormap, Face, LightKind, LocalPos, World,
+    },
+};
 
-use crate::{config::Config, render, resource::ResourceManager, SHADER_DIR, TEXTURE_DIR};
+use crate::{
+    config::Config, entity::RemoteEntity, render, resource::ResourceManager, SHADER_DIR,
+    TEXTURE_DIR,
+};
 
 use super::{
+    entity,
     view::{self, Camera},
     Renderer, SizedSurface, State, Vertex,
 };
 
 const LABEL: &str = "Chunk renderer";
 const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24Plus;
-const TEST_VERTICES: &[ChunkVertex] = &[
-    ChunkVertex {
-        position: Vec3::new(0.0, 0.5, -3.0),
-        tex_coords: Vec2::new(0.0, 0.0),
-    },
-    ChunkVertex {
-        position: Vec3::new(-0.5, -0.5, -3.0),
-        tex_coords: Vec2::new(0.25, 0.0),
-    },
-    ChunkVertex {
-        position: Vec3::new(0.5, -0.5, -3.0),
-        tex_coords: Vec2::new(0.0, 0.25),
-    },
-];
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Pod, Zeroable)]
 pub struct ChunkVertex {
-    position: Vec3,
-    tex_coords: Vec2,
+    pub(super) position: Vec3,
+    pub(super) tex_coords: Vec2,
+    pub(super) light: f32,
+    pub(super) tint: Vec3,
 }
 
 pub struct ChunkRenderer<'a> {
@@ -43,15 +42,32 @@ pub struct ChunkRenderer<'a> {
     depth_view: wgpu::TextureView,
 
     vp: (wgpu::Buffer, Option<Mat4>),
-    vertex_buffer: wgpu::Buffer,
 
     camera: Rc<RefCell<Camera>>,
     fov: f32,
+
+    registry: Rc<BlockRegistry>,
+    colormaps: Colormaps,
+    meshes: HashMap<ChunkPos, ChunkMesh>,
+    /// Rebuilt every frame, its buffer only grows
+    entity_mesh: Option<ChunkMesh>,
+}
+
+/// Biome colormaps, sampled on the CPU when meshing
+pub(super) struct Colormaps {
+    grass: RgbaImage,
+    foliage: RgbaImage,
+}
+
+struct ChunkMesh {
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
 }
 
 impl ChunkVertex {
-    const ATTRIBS: [wgpu::VertexAttribute; 2] =
-        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2];
+    const ATTRIBS: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+        0 => Float32x3, 1 => Float32x2, 2 => Float32, 3 => Float32x3
+    ];
 }
 
 impl<'a> Vertex<'a> for ChunkVertex {
@@ -70,9 +86,9 @@ impl<'a> ChunkRenderer<'a> {
         config: Rc<Config>,
         resource_man: Rc<ResourceManager>,
         camera: Rc<RefCell<Camera>>,
+        registry: Rc<BlockRegistry>,
     ) -> Result<Self, render::Error> {
         let device = &state.device;
-        let queue = &state.queue;
 
         let mut res = resource_man.get_from_path(&Path::new(SHADER_DIR).join("chunk.wgsl"))?;
         let source = res.read_to_str()?;
@@ -101,6 +117,7 @@ impl<'a> ChunkRenderer<'a> {
             false,
         );
 
+        let colormaps = Colormaps::load(&resource_man)?;
         let (terrain_atlas, terrain_atlas_entry) =
             Self::create_terrain_texture(2, &state, resource_man)?;
         let terrain_atlas_view = terrain_atlas.create_view(&wgpu::TextureViewDescriptor::default());
@@ -145,14 +162,6 @@ impl<'a> ChunkRenderer<'a> {
         let (width, height) = state.surface_config.get_size();
         let depth_view = Self::create_depth_view(&state, width, height);
 
-        let vertex_buffer = state.create_buffer(
-            Some((LABEL.to_owned() + " vertex").as_str()),
-            mem::size_of::<[ChunkVertex; 3]>(),
-            wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-        );
-
-        queue.write_buffer(&vertex_buffer, 0, bytemuck::cast_slice(TEST_VERTICES));
-
         Ok(Self {
             state,
             config,
@@ -162,13 +171,79 @@ impl<'a> ChunkRenderer<'a> {
             depth_view,
 
             vp,
-            vertex_buffer,
 
             camera,
             fov: 0.0,
+
+            registry,
+            colormaps,
+            meshes: HashMap::new(),
+            entity_mesh: None,
         })
     }
 
+    pub fn load_chunk(&mut self, world: &World, pos: ChunkPos) {
+        let vertices = mesh_chunk(world, pos, &self.registry, &self.colormaps);
+
+        if vertices.is_empty() {
+            self.meshes.remove(&pos);
+            return;
+        }
+
+        let vertex_buffer = self.state.create_buffer(
+            Some((LABEL.to_owned() + " vertex").as_str()),
+            mem::size_of_val(vertices.as_slice()),
+            wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        );
+
+        self.state
+            .queue
+            .write_buffer(&vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+
+        self.meshes.insert(
+            pos,
+            ChunkMesh {
+                vertex_buffer,
+                vertex_count: vertices.len() as u32,
+            },
+        );
+    }
+
+    pub fn load_entities<'e>(
+        &mut self,
+        world: &World,
+        entities: impl IntoIterator<Item = &'e RemoteEntity>,
+    ) {
+        let vertices = entity::mesh_entities(world, &self.registry, &self.colormaps, entities);
+        let size = mem::size_of_val(vertices.as_slice());
+
+        let mesh = match &mut self.entity_mesh {
+            Some(mesh) if mesh.vertex_buffer.size() >= size as wgpu::BufferAddress => mesh,
+            mesh => {
+                if vertices.is_empty() {
+                    return;
+                }
+
+                mesh.insert(ChunkMesh {
+                    vertex_buffer: self.state.create_buffer(
+                        Some((LABEL.to_owned() + " entity vertex").as_str()),
+                        size,
+                        wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    ),
+                    vertex_count: 0,
+                })
+            }
+        };
+
+        if !vertices.is_empty() {
+            self.state
+                .queue
+                .write_buffer(&mesh.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        }
+
+        mesh.vertex_count = vertices.len() as u32;
+    }
+
     fn create_depth_view(state: &State, width: u32, height: u32) -> wgpu::TextureView {
         state
             .create_depth_texture(Some(LABEL), width, height, DEPTH_FORMAT)
@@ -240,8 +315,20 @@ impl Renderer for ChunkRenderer<'_> {
 
             pass.set_pipeline(&self.render_pipeline);
             pass.set_bind_group(0, &self.bind_group, &[]);
-            pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            pass.draw(0..3, 0..1);
+
+            for mesh in self.meshes.values() {
+                pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                pass.draw(0..mesh.vertex_count, 0..1);
+            }
+
+            if let Some(mesh) = self
+                .entity_mesh
+                .as_ref()
+                .filter(|mesh| mesh.vertex_count > 0)
+            {
+                pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                pass.draw(0..mesh.vertex_count, 0..1);
+            }
         }
 
         encoder.finish()
@@ -251,3 +338,156 @@ impl Renderer for ChunkRenderer<'_> {
         self.depth_view = Self::create_depth_view(&self.state, width, height)
     }
 }
+
+impl Colormaps {
+    fn load(resource_man: &ResourceManager) -> Result<Self, render::Error> {
+        let load = |name: &str| -> Result<RgbaImage, render::Error> {
+            let mut res = resource_man.get_from_path(&Path::new(TEXTURE_DIR).join(name))?;
+            let image = ImageReader::new(BufReader::new(res.open()?))
+                .with_guessed_format()?
+                .decode()?;
+
+            Ok(image.into_rgba8())
+        };
+
+        Ok(Self {
+            grass: load("grasscolor.png")?,
+            foliage: load("foliagecolor.png")?,
+        })
+    }
+
+    pub(super) fn color(&self, colormap: Colormap, biome: Biome) -> Vec3 {
+        let image = match colormap {
+            Colormap::Grass => &self.grass,
+            Colormap::Foliage => &self.foliage,
+        };
+
+        let (u, v) = biome.colormap_coords();
+        let x = (u * (image.width() - 1) as f32) as u32;
+        let y = (v * (image.height() - 1) as f32) as u32;
+        let [r, g, b, _] = image.get_pixel(x, y).0;
+
+        Vec3::new(r as f32, g as f32, b as f32) / 255.0
+    }
+}
+
+pub(super) fn face_corners(face: Face) -> [Vec3; 4] {
+    match face {
+        Face::Down => [
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 1.0),
+        ],
+        Face::Up => [
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(1.0, 1.0, 0.0),
+        ],
+        Face::North => [
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        ],
+        Face::South => [
+            Vec3::new(0.0, 1.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ],
+        Face::West => [
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 0.0),
+        ],
+        Face::East => [
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 1.0),
+        ],
+    }
+}
+
+pub(super) fn tile_coords(tile: u16) -> [Vec2; 4] {
+    let size = 1.0 / ATLAS_TILES as f32;
+    let origin = Vec2::new((tile % ATLAS_TILES) as f32, (tile / ATLAS_TILES) as f32) * size;
+
+    [
+        origin,
+        origin + Vec2::new(size, 0.0),
+        origin + Vec2::new(size, size),
+        origin + Vec2::new(0.0, size),
+    ]
+}
+
+/// Brightness of a face, from the light of the block in front of it
+pub(super) fn face_light(world: &World, pos: BlockPos) -> f32 {
+    let level = [LightKind::Block, LightKind::Sky]
+        .into_iter()
+        .filter_map(|kind| world.get_light(kind, pos))
+        .max()
+        .unwrap_or(MAX_LIGHT);
+
+    0.8f32.powi((MAX_LIGHT - level) as i32)
+}
+
+fn mesh_chunk(
+    world: &World,
+    pos: ChunkPos,
+    registry: &BlockRegistry,
+    colormaps: &Colormaps,
+) -> Vec<ChunkVertex> {
+    let Some(chunk) = world.get_chunk(pos) else {
+        return Vec::new();
+    };
+
+    let mut vertices = Vec::new();
+
+    for local in LocalPos::iter() {
+        let state = chunk.get(local);
+
+        if state.id == BlockId::AIR {
+            continue;
+        }
+
+        let Some(def) = registry.get(state.id) else {
+            continue;
+        };
+
+        let block = pos.block(local);
+        let offset = Vec3::new(block.x as f32, block.y as f32, block.z as f32);
+
+        for face in Face::ALL {
+            let neighbor = match local.neighbor(face) {
+                Some(neighbor) => Some(chunk.get(neighbor)),
+                None => world.get_block(block.neighbor(face)),
+            };
+
+            if neighbor.is_some_and(|neighbor| registry.is_opaque(neighbor.id)) {
+                continue;
+            }
+
+            let corners = face_corners(face);
+            let tex_coords = tile_coords(def.texture_for(state, face));
+            let light = face_light(world, block.neighbor(face));
+            let tint = def.tint(face).map_or(Vec3::ONE, |colormap| {
+                colormaps.color(colormap, chunk.get_biome(local))
+            });
+
+            for i in [0, 1, 2, 0, 2, 3] {
+                vertices.push(ChunkVertex {
+                    position: offset + corners[i],
+                    tex_coords: tex_coords[i],
+                    light,
+                    tint,
+                });
+            }
+        }
+    }
+
+    vertices
+}
@@ -1,11 +1,16 @@
 use std::io;
 
 use rubycave::{
-    protocol::{client, Packet, PacketValidator},
+    protocol::{
+        client, codec,
+        compress::{self, ALGORITHMS},
+        server, Negotiated, Packet, PacketValidator, FEATURES, MIN_PROTOCOL_VERSION,
+        PROTOCOL_VERSION,
+    },
     regex,
-    rkyv_codec::RkyvCodecError,
 };
 use tokio::sync::mpsc;
+use tracing::error;
 
 pub mod tcp;
 
@@ -15,14 +20,16 @@ pub enum Error {
     Io(#[from] io::Error),
     #[error("regex error")]
     Regex(#[from] regex::Error),
-    #[error("rkyv_codec error")]
-    RkyvCodec(#[from] RkyvCodecError),
+    #[error("codec error")]
+    Codec(#[from] codec::Error),
     #[error("mpsc send error")]
     MpscSend(#[from] mpsc::error::SendError<Packet>),
     #[error("mpsc channel closed")]
     MpscClosed(),
     #[error("mpsc try_recv error")]
     MpscTryRecv(#[from] mpsc::error::TryRecvError),
+    #[error("compression error")]
+    Compress(#[from] compress::Error),
 }
 
 pub trait Client {
@@ -34,24 +41,53 @@ pub trait Client {
     async fn start(&mut self) -> bool;
     fn stop(&mut self) -> bool;
 
-    async fn shake(&mut self, username: &str) -> Result<bool, Error> {
+    /// Returns what was agreed on with the server, or `None` if it's
+    /// incompatible
+    async fn shake(&mut self, username: &str) -> Result<Option<Negotiated>, Error> {
         let packet = self.receive().await?;
         let validator = self.get_packet_validator();
 
-        if let Packet::Server(server_packet) = packet {
-            if let Err(e) = validator.check_server(&server_packet) {
-                self.disconnect(client::DisconnectReason::Packet(e)).await?;
-                return Ok(false);
-            }
+        let Packet::Server(server_packet) = packet else {
+            return Ok(None);
+        };
+
+        if let Err(e) = validator.check_server(&server_packet) {
+            error!("{e}");
+            self.disconnect(client::DisconnectReason::Packet(e)).await?;
+            return Ok(None);
         }
 
+        let server::Packet::Handshake {
+            version,
+            features,
+            compression,
+            ..
+        } = server_packet
+        else {
+            self.disconnect(client::DisconnectReason::Packet(
+                client::PacketError::Handshake,
+            ))
+            .await?;
+            return Ok(None);
+        };
+
+        // The server's first offer this build can decompress
+        let picked = compression
+            .into_iter()
+            .find(|offer| ALGORITHMS.contains(&offer.algorithm));
+
         self.send(client::Packet::Handshake {
-            version: env!("CARGO_PKG_VERSION").to_owned(),
+            version: PROTOCOL_VERSION,
+            min_version: MIN_PROTOCOL_VERSION,
+            features: FEATURES.iter().map(|feature| feature.to_string()).collect(),
             username: username.to_owned(),
+            compression: picked,
         })
         .await?;
 
-        Ok(true)
+        Ok(Some(
+            Negotiated::new(version, &features).with_compression(picked),
+        ))
     }
 
     async fn disconnect(&mut self, reason: client::DisconnectReason) -> Result<(), Error> {
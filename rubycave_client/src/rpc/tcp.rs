@@ -2,8 +2,7 @@ use std::sync::Arc;
 
 use futures::SinkExt;
 use rubycave::{
-    protocol::{client, Packet, PacketValidator},
-    rkyv_codec::{futures_stream::RkyvCodec, VarintLength},
+    protocol::{client, codec::PacketCodec, compress, Packet, PacketValidator},
     tokio_util::codec::Framed,
 };
 use tokio::{
@@ -20,7 +19,7 @@ use tracing::info;
 
 use super::{Client, Error};
 
-type TcpFramed = Framed<TcpStream, RkyvCodec<Packet, VarintLength>>;
+type TcpFramed = Framed<TcpStream, PacketCodec>;
 
 struct TaskData {
     framed: RwLock<TcpFramed>,
@@ -41,12 +40,9 @@ impl TcpClient {
         let stream = TcpStream::connect(addr).await?;
         stream.set_nodelay(true)?;
 
-        let framed = RwLock::new(Framed::new(
-            stream,
-            RkyvCodec::<Packet, VarintLength>::default(),
-        ));
+        let framed = RwLock::new(Framed::new(stream, PacketCodec::default()));
 
-        let validator = PacketValidator::new(env!("CARGO_PKG_VERSION"))?;
+        let validator = PacketValidator::new()?;
 
         let send = mpsc::channel(32);
         let recv = mpsc::channel(32);
@@ -74,7 +70,10 @@ impl TcpClient {
         loop {
             select! {
                 received = framed.next() => if let Some(p) = received {
-                    let p = p?;
+                    let p = match p? {
+                        Packet::Server(p) => Packet::Server(compress::decompress_packet(p)?),
+                        p => p,
+                    };
                     info!("received: {:?}", p);
                     recv.send(p).await?;
                 },
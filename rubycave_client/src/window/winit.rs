@@ -82,7 +82,9 @@ impl ApplicationHandler for App<'_> {
                 event,
                 is_synthetic: _,
             } => {
-                if let PhysicalKey::Code(code) = event.physical_key {
+                if game.is_chat_open() && event.state.is_pressed() {
+                    game.chat_key(&event.logical_key, event.text.as_deref());
+                } else if let PhysicalKey::Code(code) = event.physical_key {
                     match code {
                         KeyCode::Escape => {
                             if event.state.is_pressed() {
@@ -118,6 +120,13 @@ impl ApplicationHandler for App<'_> {
                     }
                 }
             }
+            WindowEvent::MouseInput {
+                device_id: _,
+                state,
+                button,
+            } if self.focused => {
+                game.mouse_button(button, state.is_pressed());
+            }
             WindowEvent::RedrawRequested => {
                 self.rt.block_on(game.update_async()).unwrap();
                 game.update().unwrap();
@@ -1,16 +1,23 @@
-use std::{env, error::Error, fs::File, io::BufReader, path::Path};
+use std::{env, error::Error, fs, fs::File, io::BufReader, path::Path};
 
-use image::{codecs::png::PngDecoder, DynamicImage, GenericImage, ImageResult};
+use image::{codecs::png::PngDecoder, DynamicImage, GenericImage, ImageResult, Rgba, RgbaImage};
 
 pub const CLIENT_PATH: &str = env!("CLIENT_PATH");
 
+/// Biome colormaps, indexed by temperature and humidity
+pub const COLORMAPS: [&str; 2] = ["grasscolor.png", "foliagecolor.png"];
+
+/// 16x16 grid of glyphs, indexed by character code
+pub const FONT: &str = "font.png";
+
 fn convert_terrain(terrain_png: File) -> ImageResult<DynamicImage> {
     let mc_terrain = PngDecoder::new(BufReader::new(terrain_png))?;
     let mc_terrain = DynamicImage::from_decoder(mc_terrain)?;
 
-    let mut terrain = DynamicImage::new_rgba8(64, 64);
+    let mut terrain = DynamicImage::new_rgba8(256, 256);
 
-    terrain.copy_from(&mc_terrain.crop_imm(0, 0, 16, 16), 0, 0)?; // grass
+    // Keep the vanilla 16x16 tile layout, block texture indices refer to it
+    terrain.copy_from(&mc_terrain.crop_imm(0, 0, 256, 256), 0, 0)?;
 
     Ok(terrain)
 }
@@ -21,5 +28,22 @@ pub fn create_textures(output_path: &Path) -> Result<(), Box<dyn Error>> {
     convert_terrain(File::open(client_path.join("terrain.png"))?)?
         .save(output_path.join("terrain.png"))?;
 
+    fs::copy(
+        client_path.join("font").join("default.png"),
+        output_path.join(FONT),
+    )?;
+
+    for colormap in COLORMAPS {
+        let path = client_path.join("misc").join(colormap);
+
+        if path.is_file() {
+            fs::copy(path, output_path.join(colormap))?;
+        } else {
+            // Versions before biomes have no colormaps and ship their grass and
+            // leaves textures already colored, so they must be left untinted
+            RgbaImage::from_pixel(256, 256, Rgba([255; 4])).save(output_path.join(colormap))?;
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,30 @@
+use rubycave::{
+    protocol::delta::{self, ChunkDelta},
+    world::{BlockPos, BlockState, World},
+};
+
+/// Blocks set by players since the last tick, sent along with the tick's own
+/// changes so a chunk gets a single delta per tick
+#[derive(Default)]
+pub struct PendingEdits {
+    edits: Vec<(BlockPos, BlockState)>,
+}
+
+impl PendingEdits {
+    pub fn push(&mut self, pos: BlockPos, state: BlockState) {
+        self.edits.push((pos, state));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Batches the queued edits, followed by the tick's `changes`, per chunk
+    pub fn flush(
+        &mut self,
+        world: &World,
+        changes: impl IntoIterator<Item = (BlockPos, BlockState)>,
+    ) -> Vec<ChunkDelta> {
+        delta::batch(world, self.edits.drain(..).chain(changes))
+    }
+}
@@ -1,15 +1,45 @@
-use std::{io, sync::Arc};
+use std::{
+    collections::HashSet,
+    env, io,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
 
 use rubycave::{
-    protocol::{Packet, PacketValidator},
+    entity::{Entity, EntityId, EntityKind},
+    epoch,
+    gen::{self, Generator},
+    glam::Vec3,
+    protocol::{
+        chat::{Color, Segment},
+        client::{self, DigAction},
+        codec::{self, PacketCodec},
+        compress::{self, Compression},
+        delta::ChunkDelta,
+        feature, server, Negotiated, Packet, PacketValidator,
+    },
     regex,
-    rkyv_codec::{futures_stream::RkyvCodec, RkyvCodecError, VarintLength},
     tokio_util::codec::Framed,
+    world::{
+        block, light, tick, BlockPos, BlockRegistry, BlockState, Chunk, ChunkPos, HeightmapKind,
+        Ticker, World,
+    },
+    KEEP_ALIVE_INTERVAL, TICK_RATE,
+};
+use tokio::{
+    net::TcpStream,
+    sync::broadcast::{self, error::RecvError},
+    time::{self, MissedTickBehavior},
 };
-use tokio::net::TcpStream;
-use tracing::info;
+use tracing::{debug, info, warn};
 
-use crate::rpc::{self, tcp::TcpServer, Client, Server};
+use crate::{
+    edits::PendingEdits,
+    keep_alive::KeepAlive,
+    player::{Move, Player},
+    rpc::{self, tcp::TcpServer, Client, Server},
+    tracker::Tracker,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -19,43 +49,508 @@ pub enum Error {
     Regex(#[from] regex::Error),
     #[error("rpc error")]
     Rpc(#[from] rpc::Error),
-    #[error("rkyv_codec error")]
-    RkyvCodec(#[from] RkyvCodecError),
+    #[error("codec error")]
+    Codec(#[from] codec::Error),
+    #[error("block registry error")]
+    Block(#[from] block::Error),
+    #[error("world generation error")]
+    Gen(#[from] gen::Error),
+    #[error("block tick error")]
+    Tick(#[from] tick::Error),
+    #[error("compression error")]
+    Compress(#[from] compress::Error),
+}
+
+/// Chunks generated around the spawn point on startup
+const SPAWN_RADIUS: i32 = 3;
+
+/// Updates a client can fall behind on before getting its chunks again
+const UPDATE_BACKLOG: usize = 4096;
+
+/// Keep-alives a client can leave unanswered in a row before it's kicked,
+/// unless `RUBYCAVE_KEEP_ALIVE_MISSES` says otherwise
+const KEEP_ALIVE_MISSES: u32 = 3;
+
+/// Longest frame a client may send, unless `RUBYCAVE_MAX_FRAME_LENGTH` says
+/// otherwise. Nothing a client sends comes close.
+const MAX_FRAME_LENGTH: usize = 1 << 16;
+
+/// Chunk compressions offered to clients, unless `RUBYCAVE_COMPRESSION` lists
+/// others or is `none`
+const COMPRESSION: &str = "zstd:3,lz4";
+
+/// Settings for every client, read from the environment on startup
+struct Settings {
+    keep_alive_misses: u32,
+    compression: Vec<Compression>,
+}
+
+/// Something that happened in a tick, for every client to hear about
+#[derive(Clone)]
+enum Update {
+    /// Changes to a chunk, batched per tick
+    Chunk(Arc<ChunkDelta>),
+    /// Every entity at the end of a tick, for each client to diff
+    Entities(Arc<Vec<(EntityId, Entity)>>),
+    Chat(Vec<Segment>),
 }
 
 pub struct Game {
     server: TcpServer,
     validator: Arc<PacketValidator>,
+    registry: Arc<BlockRegistry>,
+    world: Arc<RwLock<World>>,
+    ticker: Arc<Mutex<Ticker>>,
+    edits: Arc<Mutex<PendingEdits>>,
+    updates: broadcast::Sender<Update>,
+    spawn: Vec3,
+    settings: Arc<Settings>,
 }
 
 impl Game {
     pub async fn new() -> Result<Self, Error> {
-        let server = TcpServer::new("0.0.0.0:1616").await?;
-        let validator = Arc::new(PacketValidator::new(env!("CARGO_PKG_VERSION"))?);
+        let max_frame_length = env::var("RUBYCAVE_MAX_FRAME_LENGTH")
+            .ok()
+            .and_then(|length| length.parse().ok())
+            .unwrap_or(MAX_FRAME_LENGTH);
+        let server = TcpServer::new("0.0.0.0:1616", max_frame_length).await?;
+        let validator = Arc::new(PacketValidator::new()?);
+        let registry = BlockRegistry::builtin()?;
+
+        info!("loaded {} blocks", registry.iter().count());
+
+        let seed = match env::var("RUBYCAVE_SEED") {
+            Ok(seed) => seed.parse().unwrap_or_else(|_| hash_seed(&seed)),
+            Err(_) => epoch().as_nanos() as u64,
+        };
+        let generator = Generator::new(seed, &registry)?;
+        let keep_alive_misses = env::var("RUBYCAVE_KEEP_ALIVE_MISSES")
+            .ok()
+            .and_then(|misses| misses.parse().ok())
+            .unwrap_or(KEEP_ALIVE_MISSES);
+        let compression = match env::var("RUBYCAVE_COMPRESSION") {
+            Ok(compression) if compression == "none" => Vec::new(),
+            Ok(compression) => parse_compression(&compression)?,
+            Err(_) => parse_compression(COMPRESSION)?,
+        };
+
+        info!("generating world with seed {seed}");
+
+        let mut world = World::new();
+        let ticker = Ticker::new(&registry, seed)?;
+        let surface = generator.surface(0, 0);
+        let center = BlockPos::new(0, surface, 0).chunk();
+
+        for y in -SPAWN_RADIUS..=SPAWN_RADIUS {
+            for z in -SPAWN_RADIUS..=SPAWN_RADIUS {
+                for x in -SPAWN_RADIUS..=SPAWN_RADIUS {
+                    let pos = center.offset(x, y, z);
+
+                    world.insert_chunk(Box::new(generator.generate(pos)));
+                    light::init_chunk(&mut world, &registry, pos);
+                    ticker.fluids().notify_chunk(&mut world, &registry, pos);
+                }
+            }
+        }
 
-        Ok(Self { server, validator })
+        // Trees and fluids can stand above the generated surface
+        let ground = world
+            .get_height(HeightmapKind::MotionBlocking, 0, 0)
+            .unwrap_or(surface);
+
+        Ok(Self {
+            server,
+            validator,
+            registry: Arc::new(registry),
+            world: Arc::new(RwLock::new(world)),
+            ticker: Arc::new(Mutex::new(ticker)),
+            edits: Arc::new(Mutex::new(PendingEdits::default())),
+            updates: broadcast::channel(UPDATE_BACKLOG).0,
+            spawn: Vec3::new(0.5, ground as f32 + 1.0, 0.5),
+            settings: Arc::new(Settings {
+                keep_alive_misses,
+                compression,
+            }),
+        })
     }
 
     pub async fn run(&self) -> Option<()> {
+        tokio::spawn(Self::tick_task(
+            self.registry.clone(),
+            self.world.clone(),
+            self.ticker.clone(),
+            self.edits.clone(),
+            self.updates.clone(),
+        ));
+
         loop {
             let framed = self.server.accept().await?;
             let client = Client::new(framed, self.validator.clone());
+            let registry = self.registry.clone();
+            let world = self.world.clone();
+            let ticker = self.ticker.clone();
+            let edits = self.edits.clone();
+            let updates = self.updates.clone();
+            let spawn = self.spawn;
+            let settings = self.settings.clone();
 
-            tokio::spawn(async move { Self::client_task(client).await });
+            tokio::spawn(async move {
+                Self::client_task(
+                    client, registry, world, ticker, edits, updates, spawn, settings,
+                )
+                .await
+            });
         }
     }
 
+    async fn tick_task(
+        registry: Arc<BlockRegistry>,
+        world: Arc<RwLock<World>>,
+        ticker: Arc<Mutex<Ticker>>,
+        edits: Arc<Mutex<PendingEdits>>,
+        updates: broadcast::Sender<Update>,
+    ) {
+        let mut interval = time::interval(Duration::from_secs(1) / TICK_RATE);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            let tick = {
+                let mut world = world.write().unwrap();
+                let mut ticker = ticker.lock().unwrap();
+                let changes = ticker.tick(&mut world, &registry);
+
+                let entities = ticker
+                    .entities()
+                    .iter()
+                    .map(|(id, entity)| (id, entity.clone()))
+                    .collect();
+
+                // Player edits since the last tick go out with the tick's changes
+                edits
+                    .lock()
+                    .unwrap()
+                    .flush(&world, changes)
+                    .into_iter()
+                    .map(|delta| Update::Chunk(Arc::new(delta)))
+                    .chain([Update::Entities(Arc::new(entities))])
+                    .collect::<Vec<_>>()
+            };
+
+            for update in tick {
+                // Only fails when no client is connected
+                let _ = updates.send(update);
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn client_task(
-        mut client: Client<Framed<TcpStream, RkyvCodec<Packet, VarintLength>>>,
+        mut client: Client<Framed<TcpStream, PacketCodec>>,
+        registry: Arc<BlockRegistry>,
+        world: Arc<RwLock<World>>,
+        ticker: Arc<Mutex<Ticker>>,
+        edits: Arc<Mutex<PendingEdits>>,
+        updates: broadcast::Sender<Update>,
+        spawn: Vec3,
+        settings: Arc<Settings>,
     ) -> Result<(), Error> {
         info!("new client");
 
-        if !client.shake().await? {
+        let Some((username, negotiated)) = client.shake(&settings.compression).await? else {
             return Ok(());
-        }
+        };
+
+        info!(
+            "{username} speaks protocol version {} with {:?}, compressing chunks with {:?}",
+            negotiated.version, negotiated.features, negotiated.compression
+        );
+
+        // Before sending the chunks, so no update in between is missed
+        let incoming = updates.subscribe();
+
+        let mut entity = Entity::new(EntityKind::Player, spawn, Vec3::ZERO);
+        entity.name = Some(username.clone());
+
+        let id = ticker.lock().unwrap().entities_mut().spawn(entity);
+        let mut player = Player::new(username, spawn);
+
+        Self::announce(&updates, format!("{} joined the game", player.username));
+
+        let result = Self::play(
+            &mut client,
+            &mut player,
+            id,
+            &registry,
+            &world,
+            &ticker,
+            &edits,
+            &updates,
+            incoming,
+            &negotiated,
+            settings.keep_alive_misses,
+        )
+        .await;
+
+        // Also when the connection broke
+        ticker.lock().unwrap().entities_mut().remove(id);
+        Self::announce(&updates, format!("{} left the game", player.username));
+
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn play(
+        client: &mut Client<Framed<TcpStream, PacketCodec>>,
+        player: &mut Player,
+        id: EntityId,
+        registry: &BlockRegistry,
+        world: &RwLock<World>,
+        ticker: &Mutex<Ticker>,
+        edits: &Mutex<PendingEdits>,
+        updates: &broadcast::Sender<Update>,
+        mut incoming: broadcast::Receiver<Update>,
+        negotiated: &Negotiated,
+        keep_alive_misses: u32,
+    ) -> Result<(), Error> {
+        let mut tracker = Tracker::new(id);
+        let mut keep_alive = KeepAlive::default();
+        let mut keep_alive_interval =
+            time::interval(Duration::from_millis(KEEP_ALIVE_INTERVAL as u64));
+        keep_alive_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut loaded = Self::send_chunks(client, world).await?;
+        Self::send_teleport(client, player).await?;
 
         loop {
-            let _ = client.receive().await?;
+            tokio::select! {
+                _ = keep_alive_interval.tick() => {
+                    let Some(keep_alive_id) = keep_alive.start(keep_alive_misses, Instant::now()) else {
+                        warn!("{} stopped answering keep-alives", player.username);
+                        client.kick(server::KickReason::Timeout).await?;
+                        return Ok(());
+                    };
+
+                    client
+                        .send(server::Packet::KeepAlive {
+                            id: keep_alive_id,
+                            latency: keep_alive.latency().map(|latency| latency.as_millis() as u32),
+                        })
+                        .await?;
+                }
+                packet = client.receive() => {
+                    if let Packet::Client(packet) = packet? {
+                        if !client.check(&packet).await? {
+                            return Ok(());
+                        }
+
+                        if let client::Packet::KeepAlive { id } = packet {
+                            if let Some(latency) = keep_alive.finish(id, Instant::now()) {
+                                debug!("{} latency: {} ms", player.username, latency.as_millis());
+                            }
+
+                            continue;
+                        }
+
+                        Self::handle_packet(
+                            client,
+                            player,
+                            id,
+                            packet,
+                            registry,
+                            world,
+                            ticker,
+                            edits,
+                            updates,
+                        )
+                        .await?;
+                    }
+                }
+                update = incoming.recv() => match update {
+                    Ok(Update::Chunk(delta)) => {
+                        if loaded.contains(&delta.chunk()) {
+                            client.send(delta.to_packet()).await?;
+                        }
+                    }
+                    Ok(Update::Entities(entities)) => {
+                        for packet in tracker.update(&entities, player.position) {
+                            client.send(packet).await?;
+                        }
+                    }
+                    Ok(Update::Chat(segments)) => {
+                        if negotiated.has(feature::CHAT) {
+                            client.send(server::Packet::Chat { segments }).await?;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("client missed {skipped} updates, resending chunks");
+                        loaded = Self::send_chunks(client, world).await?;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_packet(
+        client: &mut Client<Framed<TcpStream, PacketCodec>>,
+        player: &mut Player,
+        id: EntityId,
+        packet: client::Packet,
+        registry: &BlockRegistry,
+        world: &RwLock<World>,
+        ticker: &Mutex<Ticker>,
+        edits: &Mutex<PendingEdits>,
+        updates: &broadcast::Sender<Update>,
+    ) -> Result<(), Error> {
+        match packet {
+            client::Packet::Move {
+                x,
+                y,
+                z,
+                yaw,
+                pitch,
+                on_ground,
+            } => {
+                let position = Vec3::new(x, y, z);
+                let result = player.handle_move(
+                    &world.read().unwrap(),
+                    registry,
+                    position,
+                    yaw,
+                    pitch,
+                    on_ground,
+                    Instant::now(),
+                );
+
+                match result {
+                    Move::Accepted => {
+                        if let Some(entity) = ticker.lock().unwrap().entities_mut().get_mut(id) {
+                            entity.position = player.position;
+                            entity.yaw = player.yaw;
+                            entity.pitch = player.pitch;
+                            entity.on_ground = player.on_ground;
+                        }
+                    }
+                    Move::Ignored => {}
+                    Move::Rejected => {
+                        warn!("{} moved wrongly to {position}", player.username);
+                        Self::send_teleport(client, player).await?;
+                    }
+                }
+            }
+            client::Packet::Dig { action, pos, .. } => match action {
+                DigAction::Start => player.start_dig(pos, Instant::now()),
+                DigAction::Cancel => player.cancel_dig(),
+                DigAction::Finish => {
+                    let dug =
+                        player.finish_dig(&world.read().unwrap(), registry, pos, Instant::now());
+
+                    if dug {
+                        Self::edit(registry, world, ticker, edits, pos, BlockState::AIR);
+                    } else {
+                        warn!("{} couldn't dig {pos:?}", player.username);
+                    }
+                }
+            },
+            client::Packet::Place { pos, face, state } => {
+                let target = player.check_place(&world.read().unwrap(), registry, pos, face, state);
+
+                match target {
+                    Some(target) => Self::edit(registry, world, ticker, edits, target, state),
+                    None => warn!("{} couldn't place against {pos:?}", player.username),
+                }
+            }
+            client::Packet::Chat { message } => {
+                info!("<{}> {message}", player.username);
+
+                // Only fails when no client is connected
+                let _ = updates.send(Update::Chat(vec![
+                    Segment::new("<", Color::White),
+                    Segment::new(player.username.clone(), Color::Aqua).bold(),
+                    Segment::new("> ", Color::White),
+                    Segment::new(message.trim(), Color::White),
+                ]));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Sends a system message to every client
+    fn announce(updates: &broadcast::Sender<Update>, message: String) {
+        info!("{message}");
+
+        // Only fails when no client is connected
+        let _ = updates.send(Update::Chat(vec![Segment::new(message, Color::Yellow)]));
+    }
+
+    /// Sets a block changed by a player, every client hears about it with the
+    /// next tick
+    fn edit(
+        registry: &BlockRegistry,
+        world: &RwLock<World>,
+        ticker: &Mutex<Ticker>,
+        edits: &Mutex<PendingEdits>,
+        pos: BlockPos,
+        state: BlockState,
+    ) {
+        let mut world = world.write().unwrap();
+
+        if light::set_block(&mut world, registry, pos, state) {
+            ticker.lock().unwrap().notify(&mut world, registry, pos);
+            edits.lock().unwrap().push(pos, state);
         }
     }
+
+    /// Sends the player back to its authoritative position
+    async fn send_teleport(
+        client: &mut Client<Framed<TcpStream, PacketCodec>>,
+        player: &Player,
+    ) -> Result<(), Error> {
+        client
+            .send(server::Packet::Teleport {
+                x: player.position.x,
+                y: player.position.y,
+                z: player.position.z,
+                yaw: player.yaw,
+                pitch: player.pitch,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Sends every chunk, returning the chunks the client now has loaded
+    async fn send_chunks(
+        client: &mut Client<Framed<TcpStream, PacketCodec>>,
+        world: &RwLock<World>,
+    ) -> Result<HashSet<ChunkPos>, Error> {
+        // Loaded chunks hand their scheduled ticks to the world, none are sent
+        let chunks: Vec<Chunk> = world.read().unwrap().chunks().cloned().collect();
+        let loaded = chunks.iter().map(|chunk| chunk.pos).collect();
+
+        for chunk in chunks {
+            client.send(server::Packet::Chunk(Box::new(chunk))).await?;
+        }
+
+        Ok(loaded)
+    }
+}
+
+/// Parses a comma separated list of compressions, like `zstd:3,lz4`
+fn parse_compression(list: &str) -> Result<Vec<Compression>, compress::Error> {
+    list.split(',')
+        .map(|compression| compression.trim().parse())
+        .collect()
+}
+
+/// Turns a non-numeric seed into a number, like typing a word as a seed
+fn hash_seed(seed: &str) -> u64 {
+    seed.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
 }
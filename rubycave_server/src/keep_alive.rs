@@ -0,0 +1,56 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Server side of the keep-alive round trips of one client
+#[derive(Default)]
+pub struct KeepAlive {
+    next_id: u64,
+    /// Round trips waiting for their answer and when they started, oldest
+    /// first, so a slow answer still counts after the next one was sent
+    pending: VecDeque<(u64, Instant)>,
+    /// Intervals in a row that went unanswered
+    missed: u32,
+    latency: Option<Duration>,
+}
+
+impl KeepAlive {
+    /// Starts a round trip, returning its id, or `None` once the client missed
+    /// more than `max_missed` of them in a row
+    pub fn start(&mut self, max_missed: u32, now: Instant) -> Option<u64> {
+        if !self.pending.is_empty() {
+            self.missed += 1;
+
+            if self.missed > max_missed {
+                return None;
+            }
+        }
+
+        let id = self.next_id;
+
+        self.next_id += 1;
+        self.pending.push_back((id, now));
+
+        Some(id)
+    }
+
+    /// Finishes the pending round trip `id` answers, returning the measured
+    /// latency
+    pub fn finish(&mut self, id: u64, now: Instant) -> Option<Duration> {
+        let index = self.pending.iter().position(|(pending, _)| *pending == id)?;
+        let (_, started) = self.pending[index];
+
+        // Answers come in order, the ones before it are never coming
+        self.pending.drain(..=index);
+        self.missed = 0;
+        self.latency = Some(now.saturating_duration_since(started));
+
+        self.latency
+    }
+
+    /// Round trip time of the last answered keep-alive
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+}
@@ -0,0 +1,6 @@
+pub mod edits;
+pub mod game;
+pub mod keep_alive;
+pub mod player;
+mod rpc;
+pub mod tracker;
@@ -1,10 +1,7 @@
 use color_eyre::eyre::{self, eyre};
-use game::Game;
+use rubycave_server::game::Game;
 use tracing::info;
 
-mod game;
-mod rpc;
-
 #[tokio::main(flavor = "multi_thread", worker_threads = 10)]
 async fn main() -> eyre::Result<()> {
     color_eyre::install()?;
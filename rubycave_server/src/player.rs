@@ -0,0 +1,235 @@
+use std::time::Instant;
+
+use rubycave::{
+    glam::Vec3,
+    physics::{self, Aabb},
+    world::{Behavior, BlockPos, BlockRegistry, BlockState, Face, World},
+    REACH, TICK_RATE,
+};
+
+/// Fastest a player may move, in blocks per second
+const MAX_SPEED: f32 = 20.0;
+
+/// Most distance that can be saved up, for packets arriving in bursts
+const MOVE_SLACK: f32 = 2.0;
+
+/// How far a move may end from where the server's sweep of it stops
+const SWEEP_EPSILON: f32 = 1e-2;
+
+/// How far a box may sink into blocks, for rounding errors
+const OVERLAP_EPSILON: f32 = 1e-3;
+
+/// How close a move has to be to a teleport to confirm it
+const TELEPORT_EPSILON: f32 = 1e-2;
+
+/// Share of a block's dig time a client has to actually wait, for lag
+const DIG_TOLERANCE: f32 = 0.7;
+
+pub const WIDTH: f32 = 0.6;
+pub const HEIGHT: f32 = 1.8;
+pub const EYE_HEIGHT: f32 = 1.62;
+pub const STEP_HEIGHT: f32 = 0.5;
+
+/// Server side state of a connected player, the authority on where it is
+pub struct Player {
+    pub username: String,
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+    last_move: Instant,
+    /// Distance the player may still move, refilled at `MAX_SPEED`
+    budget: f32,
+    /// Where the player was sent to, moves are ignored until it gets there
+    teleport: Option<Vec3>,
+    /// Block being dug and when it started
+    digging: Option<(BlockPos, Instant)>,
+}
+
+pub enum Move {
+    Accepted,
+    /// Sent before the client got the last teleport
+    Ignored,
+    /// The client needs to be sent back to its position
+    Rejected,
+}
+
+impl Player {
+    pub fn new(username: String, position: Vec3) -> Self {
+        Self {
+            username,
+            position,
+            yaw: 0.0,
+            pitch: 0.0,
+            on_ground: false,
+            last_move: Instant::now(),
+            budget: MOVE_SLACK,
+            teleport: Some(position),
+            digging: None,
+        }
+    }
+
+    pub fn eye(&self) -> Vec3 {
+        self.position + Vec3::Y * EYE_HEIGHT
+    }
+
+    pub fn can_reach(&self, pos: BlockPos) -> bool {
+        let center = Vec3::new(pos.x as f32, pos.y as f32, pos.z as f32) + 0.5;
+
+        // The ray can hit a block by its corner
+        self.eye().distance(center) <= REACH + 1.0
+    }
+
+    pub fn start_dig(&mut self, pos: BlockPos, now: Instant) {
+        self.digging = Some((pos, now));
+    }
+
+    pub fn cancel_dig(&mut self) {
+        self.digging = None;
+    }
+
+    /// Checks that the block at `pos` was dug for long enough to break it
+    pub fn finish_dig(
+        &mut self,
+        world: &World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        now: Instant,
+    ) -> bool {
+        let Some((dug, started)) = self.digging.take() else {
+            return false;
+        };
+        let Some(state) = world.get_block(pos) else {
+            return false;
+        };
+        let Some(def) = registry.get(state.id) else {
+            return false;
+        };
+        let Some(ticks) = def.dig_ticks() else {
+            return false;
+        };
+
+        let needed = ticks as f32 / TICK_RATE as f32 * DIG_TOLERANCE;
+
+        dug == pos
+            && state != BlockState::AIR
+            && def.behavior != Some(Behavior::Fluid)
+            && self.can_reach(pos)
+            && now.saturating_duration_since(started).as_secs_f32() >= needed
+    }
+
+    /// Returns where `state` goes when placed against the `face` of `pos`, if
+    /// it can be placed there
+    pub fn check_place(
+        &self,
+        world: &World,
+        registry: &BlockRegistry,
+        pos: BlockPos,
+        face: Face,
+        state: BlockState,
+    ) -> Option<BlockPos> {
+        let target = pos.neighbor(face);
+        let against = world.get_block(pos)?;
+        let current = world.get_block(target)?;
+
+        let is_fluid = |state: BlockState| {
+            registry.get(state.id).and_then(|def| def.behavior) == Some(Behavior::Fluid)
+        };
+
+        let min = Vec3::new(target.x as f32, target.y as f32, target.z as f32);
+        let inside = registry.is_solid(state.id)
+            && Aabb::from_feet(self.position, WIDTH, HEIGHT).intersects(&Aabb::new(min, min + 1.0));
+
+        let valid = state != BlockState::AIR
+            && registry.is_valid(state)
+            && against != BlockState::AIR
+            && !is_fluid(against)
+            && (current == BlockState::AIR || is_fluid(current))
+            && !inside
+            && self.can_reach(target);
+
+        valid.then_some(target)
+    }
+
+    /// Checks a move reported by the client, applying it if it's allowed
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_move(
+        &mut self,
+        world: &World,
+        registry: &BlockRegistry,
+        position: Vec3,
+        yaw: f32,
+        pitch: f32,
+        on_ground: bool,
+        now: Instant,
+    ) -> Move {
+        if let Some(target) = self.teleport {
+            if position.distance(target) > TELEPORT_EPSILON {
+                return Move::Ignored;
+            }
+
+            self.teleport = None;
+        }
+
+        let elapsed = now.saturating_duration_since(self.last_move).as_secs_f32();
+        let budget = (self.budget + MAX_SPEED * elapsed).min(MOVE_SLACK);
+        let motion = position - self.position;
+
+        let valid = position.is_finite()
+            && yaw.is_finite()
+            && pitch.abs() <= std::f32::consts::FRAC_PI_2
+            && motion.length() <= budget
+            && self.sweep(world, registry, motion)
+            && is_free(world, registry, position);
+
+        if !valid {
+            self.teleport = Some(self.position);
+            return Move::Rejected;
+        }
+
+        self.position = position;
+        self.yaw = yaw;
+        self.pitch = pitch;
+        self.on_ground = on_ground;
+        self.last_move = now;
+        self.budget = budget - motion.length();
+
+        Move::Accepted
+    }
+
+    /// Whether `motion` can be made from the current position without going
+    /// through blocks
+    fn sweep(&self, world: &World, registry: &BlockRegistry, motion: Vec3) -> bool {
+        let aabb = Aabb::from_feet(self.position, WIDTH, HEIGHT);
+        let movement =
+            physics::move_aabb(world, registry, aabb, motion, self.on_ground, STEP_HEIGHT);
+
+        movement.motion.distance(motion) <= SWEEP_EPSILON
+    }
+}
+
+/// Whether a player standing at `feet` is in loaded chunks and clear of
+/// solid blocks
+fn is_free(world: &World, registry: &BlockRegistry, feet: Vec3) -> bool {
+    let aabb = Aabb::from_feet(feet, WIDTH, HEIGHT);
+    let shrunk = Aabb::new(
+        aabb.min + Vec3::splat(OVERLAP_EPSILON),
+        aabb.max - Vec3::splat(OVERLAP_EPSILON),
+    );
+
+    let loaded = [shrunk.min, shrunk.max].into_iter().all(|corner| {
+        world.is_loaded(
+            BlockPos::new(
+                corner.x.floor() as i32,
+                corner.y.floor() as i32,
+                corner.z.floor() as i32,
+            )
+            .chunk(),
+        )
+    });
+
+    loaded
+        && physics::block_boxes(world, registry, shrunk)
+            .iter()
+            .all(|block| !block.intersects(&shrunk))
+}
@@ -2,24 +2,31 @@ use std::sync::Arc;
 
 use futures::{SinkExt, Stream, StreamExt};
 use rubycave::{
-    protocol::{client, server, Packet, PacketValidator},
-    rkyv_codec::{futures_stream::RkyvCodec, RkyvCodecError, VarintLength},
+    protocol::{
+        client,
+        codec::{self, PacketCodec},
+        compress::{self, Compression},
+        server, Negotiated, Packet, PacketValidator, FEATURES, MIN_PROTOCOL_VERSION,
+        PROTOCOL_VERSION,
+    },
     tokio_util::codec::Framed,
 };
-use tracing::info;
+use tracing::{info, warn};
 
 pub mod tcp;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    #[error("rkyv_codec error")]
-    RkyvCodec(#[from] RkyvCodecError),
+    #[error("codec error")]
+    Codec(#[from] codec::Error),
     #[error("failed to receive data from stream")]
     Receive,
+    #[error("compression error")]
+    Compress(#[from] compress::Error),
 }
 
 pub trait Server<T> {
-    async fn accept(&self) -> Option<Framed<T, RkyvCodec<Packet, VarintLength>>>;
+    async fn accept(&self) -> Option<Framed<T, PacketCodec>>;
 }
 
 pub trait StreamClientExt<T> {
@@ -29,27 +36,55 @@ pub trait StreamClientExt<T> {
 pub struct Client<T: SinkExt<Packet> + StreamClientExt<Packet> + Unpin> {
     framed: T,
     validator: Arc<PacketValidator>,
+    /// Picked by the client in the handshake
+    compression: Option<Compression>,
 }
 
-impl<T: SinkExt<Packet, Error = RkyvCodecError> + StreamClientExt<Packet> + Unpin> Client<T> {
+impl<T: SinkExt<Packet, Error = codec::Error> + StreamClientExt<Packet> + Unpin> Client<T> {
     pub fn new(framed: T, validator: Arc<PacketValidator>) -> Self {
-        Self { framed, validator }
+        Self {
+            framed,
+            validator,
+            compression: None,
+        }
     }
 
+    /// Kicks the client if what it sent couldn't be decoded
     pub async fn receive(&mut self) -> Result<Packet, Error> {
-        let packet = self.framed.receive().await?;
+        let packet = match self.framed.receive().await {
+            Err(Error::Codec(e)) => {
+                if let Some(reason) = e.packet_error() {
+                    warn!("{e}");
+                    self.kick(server::KickReason::Packet(reason)).await?;
+                }
+
+                return Err(Error::Codec(e));
+            }
+            packet => packet?,
+        };
+
         info!("received: {:?}", packet);
         Ok(packet)
     }
 
+    /// Sends `packet`, compressing chunks if the client agreed to it
     pub async fn send(&mut self, packet: server::Packet) -> Result<(), Error> {
         info!("sending: {:?}", packet);
+        let packet = compress::compress_packet(packet, self.compression)?;
         Ok(self.framed.send(Packet::Server(packet)).await?)
     }
 
-    pub async fn shake(&mut self) -> Result<bool, Error> {
+    /// Offers the `compression`s, returning the username of the player and
+    /// what it agreed on, or `None` if it got kicked
+    pub async fn shake(
+        &mut self,
+        compression: &[Compression],
+    ) -> Result<Option<(String, Negotiated)>, Error> {
         self.send(server::Packet::Handshake {
-            version: env!("CARGO_PKG_VERSION").to_owned(),
+            version: PROTOCOL_VERSION,
+            min_version: MIN_PROTOCOL_VERSION,
+            features: FEATURES.iter().map(|feature| feature.to_string()).collect(),
+            compression: compression.to_vec(),
         })
         .await?;
 
@@ -58,19 +93,42 @@ impl<T: SinkExt<Packet, Error = RkyvCodecError> + StreamClientExt<Packet> + Unpi
         if let Packet::Client(client_packet) = packet {
             if let Err(e) = self.validator.check_client(&client_packet) {
                 self.kick(server::KickReason::Packet(e)).await?;
-                return Ok(false);
+                return Ok(None);
             } else if let client::Packet::Handshake {
-                version: _,
-                username: _,
+                version,
+                features,
+                compression: picked,
+                username,
+                ..
             } = client_packet
             {
-                return Ok(true);
+                if picked.is_some_and(|picked| !compression.contains(&picked)) {
+                    self.kick(server::KickReason::Packet(server::PacketError::Compression))
+                        .await?;
+                    return Ok(None);
+                }
+
+                self.compression = picked;
+
+                let negotiated = Negotiated::new(version, &features).with_compression(picked);
+                return Ok(Some((username, negotiated)));
             }
         }
 
         self.kick(server::KickReason::Packet(server::PacketError::Handshake))
             .await?;
-        Ok(false)
+        Ok(None)
+    }
+
+    /// Kicks the client if `packet` is invalid, returning whether it was valid
+    pub async fn check(&mut self, packet: &client::Packet) -> Result<bool, Error> {
+        match self.validator.check_client(packet) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                self.kick(server::KickReason::Packet(e)).await?;
+                Ok(false)
+            }
+        }
     }
 
     pub async fn kick(&mut self, reason: server::KickReason) -> Result<(), Error> {
@@ -78,7 +136,7 @@ impl<T: SinkExt<Packet, Error = RkyvCodecError> + StreamClientExt<Packet> + Unpi
     }
 }
 
-impl<T, S: Stream<Item = Result<T, RkyvCodecError>> + Unpin> StreamClientExt<T> for S {
+impl<T, S: Stream<Item = Result<T, codec::Error>> + Unpin> StreamClientExt<T> for S {
     async fn receive(&mut self) -> Result<T, Error> {
         Ok(self.next().await.ok_or(Error::Receive)??)
     }
@@ -1,35 +1,32 @@
 use std::io;
 
-use rubycave::{
-    protocol::Packet,
-    rkyv_codec::{futures_stream::RkyvCodec, VarintLength},
-    tokio_util::codec::Framed,
-};
+use rubycave::{protocol::codec::PacketCodec, tokio_util::codec::Framed};
 use tokio::net::{TcpListener, TcpStream};
 
 use super::Server;
 
 pub struct TcpServer {
     listener: TcpListener,
+    max_frame_length: usize,
 }
 
 impl TcpServer {
-    pub async fn new(addr: &str) -> io::Result<Self> {
+    pub async fn new(addr: &str, max_frame_length: usize) -> io::Result<Self> {
         let listener = TcpListener::bind(addr).await?;
 
-        Ok(Self { listener })
+        Ok(Self {
+            listener,
+            max_frame_length,
+        })
     }
 }
 
 impl Server<TcpStream> for TcpServer {
-    async fn accept(&self) -> Option<Framed<TcpStream, RkyvCodec<Packet, VarintLength>>> {
+    async fn accept(&self) -> Option<Framed<TcpStream, PacketCodec>> {
         let Ok((stream, _)) = self.listener.accept().await else {
             return None;
         };
 
-        Some(Framed::new(
-            stream,
-            RkyvCodec::<Packet, VarintLength>::default(),
-        ))
+        Some(Framed::new(stream, PacketCodec::new(self.max_frame_length)))
     }
 }
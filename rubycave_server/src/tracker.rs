@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use rubycave::{
+    entity::{Entity, EntityId},
+    glam::{I64Vec3, Vec3},
+    protocol::server::{self, MOVE_SCALE},
+    TICK_RATE,
+};
+
+/// How far away a client sees entities, in blocks
+pub const TRACKING_RANGE: f32 = 96.0;
+
+/// Ticks between absolute positions, so rounding never adds up
+pub const SYNC_INTERVAL: u32 = 5 * TICK_RATE;
+
+/// What a client was last told about an entity
+struct Tracked {
+    /// In [`MOVE_SCALE`] steps, like the moves sent
+    position: I64Vec3,
+    yaw: f32,
+    pitch: f32,
+    since_sync: u32,
+}
+
+/// Entities one client knows about, turning snapshots of the world's entities
+/// into the packets that bring the client up to date
+pub struct Tracker {
+    /// The client's own player, which it moves itself
+    own: EntityId,
+    tracked: HashMap<EntityId, Tracked>,
+}
+
+impl Tracker {
+    pub fn new(own: EntityId) -> Self {
+        Self {
+            own,
+            tracked: HashMap::new(),
+        }
+    }
+
+    /// Diffs `entities` against what the client knows, seen from `center`
+    pub fn update(&mut self, entities: &[(EntityId, Entity)], center: Vec3) -> Vec<server::Packet> {
+        let mut packets = Vec::new();
+        let mut seen = HashSet::new();
+
+        for (id, entity) in entities {
+            if *id == self.own || entity.position.distance(center) > TRACKING_RANGE {
+                continue;
+            }
+
+            seen.insert(*id);
+
+            let position = to_steps(entity.position);
+            let Some(tracked) = self.tracked.get_mut(id) else {
+                packets.push(server::Packet::SpawnEntity {
+                    id: *id,
+                    kind: entity.kind,
+                    name: entity.name.clone(),
+                    position: entity.position.to_array(),
+                    yaw: entity.yaw,
+                    pitch: entity.pitch,
+                });
+                self.tracked.insert(
+                    *id,
+                    Tracked {
+                        position,
+                        yaw: entity.yaw,
+                        pitch: entity.pitch,
+                        since_sync: 0,
+                    },
+                );
+                continue;
+            };
+
+            tracked.since_sync += 1;
+
+            let delta = position - tracked.position;
+            let small = delta.to_array().map(|axis| i16::try_from(axis).ok());
+
+            if tracked.since_sync >= SYNC_INTERVAL {
+                packets.push(server::Packet::TeleportEntity {
+                    id: *id,
+                    position: entity.position.to_array(),
+                });
+                tracked.since_sync = 0;
+            } else if delta != I64Vec3::ZERO {
+                match small {
+                    [Some(x), Some(y), Some(z)] => packets.push(server::Packet::MoveEntity {
+                        id: *id,
+                        delta: [x, y, z],
+                    }),
+                    _ => {
+                        packets.push(server::Packet::TeleportEntity {
+                            id: *id,
+                            position: entity.position.to_array(),
+                        });
+                        tracked.since_sync = 0;
+                    }
+                }
+            }
+
+            tracked.position = position;
+
+            if (entity.yaw, entity.pitch) != (tracked.yaw, tracked.pitch) {
+                packets.push(server::Packet::RotateEntity {
+                    id: *id,
+                    yaw: entity.yaw,
+                    pitch: entity.pitch,
+                });
+                tracked.yaw = entity.yaw;
+                tracked.pitch = entity.pitch;
+            }
+        }
+
+        // Gone from the world or out of range
+        self.tracked.retain(|id, _| {
+            let keep = seen.contains(id);
+
+            if !keep {
+                packets.push(server::Packet::DespawnEntity { id: *id });
+            }
+
+            keep
+        });
+
+        packets
+    }
+}
+
+fn to_steps(position: Vec3) -> I64Vec3 {
+    (position.as_dvec3() * MOVE_SCALE as f64)
+        .round()
+        .as_i64vec3()
+}
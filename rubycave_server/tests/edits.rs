@@ -0,0 +1,82 @@
+#[path = "../../rubycave/tests/common/mod.rs"]
+mod common;
+
+use rubycave::{
+    protocol::{delta::ChunkDelta, server},
+    world::{BlockPos, BlockRegistry, ChunkPos, LocalPos, World},
+};
+use rubycave_server::edits::PendingEdits;
+
+use common::state;
+
+fn setup() -> (World, BlockRegistry) {
+    let registry = BlockRegistry::builtin().unwrap();
+    let world = common::lit_world(&registry, [common::floor_chunk(&registry, "stone", 1)]);
+
+    (world, registry)
+}
+
+#[test]
+fn edits_in_a_tick_share_one_packet() {
+    let (world, registry) = setup();
+    let stone = state(&registry, "stone");
+    let mut edits = PendingEdits::default();
+
+    for x in 0..4 {
+        edits.push(BlockPos::new(x, 3, 8), stone);
+    }
+
+    let deltas = edits.flush(&world, []);
+
+    assert!(edits.is_empty());
+    assert!(matches!(
+        &deltas[..],
+        [delta] if matches!(
+            delta.to_packet(),
+            server::Packet::MultiBlockChange { chunk, ref changes }
+                if chunk == ChunkPos::new(0, 0, 0) && changes.len() == 4
+        )
+    ));
+
+    // Nothing left for the next tick
+    assert!(edits.flush(&world, []).is_empty());
+}
+
+#[test]
+fn edits_go_out_with_the_tick_changes() {
+    let (world, registry) = setup();
+    let stone = state(&registry, "stone");
+    let sand = state(&registry, "sand");
+    let pos = BlockPos::new(1, 3, 8);
+    let mut edits = PendingEdits::default();
+
+    edits.push(pos, stone);
+    edits.push(BlockPos::new(2, 3, 8), stone);
+
+    // The tick ran after the edits, so its change to `pos` wins
+    let deltas = edits.flush(&world, [(pos, sand), (BlockPos::new(3, 3, 8), sand)]);
+
+    let [ChunkDelta::Blocks(_, changes)] = &deltas[..] else {
+        panic!("expected a single delta, got {deltas:?}");
+    };
+
+    assert_eq!(changes.len(), 3);
+    assert!(changes
+        .iter()
+        .any(|change| change.index == pos.local().index() as u16 && change.state == sand));
+}
+
+#[test]
+fn whole_chunk_edits_resend_the_chunk() {
+    let (world, registry) = setup();
+    let glass = state(&registry, "glass");
+    let mut edits = PendingEdits::default();
+
+    for local in LocalPos::iter() {
+        edits.push(ChunkPos::new(0, 0, 0).block(local), glass);
+    }
+
+    let deltas = edits.flush(&world, []);
+
+    assert!(matches!(&deltas[..], [ChunkDelta::Chunk(_)]));
+}
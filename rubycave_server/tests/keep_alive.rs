@@ -0,0 +1,86 @@
+use std::time::{Duration, Instant};
+
+use rubycave_server::keep_alive::KeepAlive;
+
+const INTERVAL: Duration = Duration::from_secs(5);
+
+#[test]
+fn answers_measure_latency() {
+    let mut keep_alive = KeepAlive::default();
+    let now = Instant::now();
+    let id = keep_alive.start(3, now).unwrap();
+
+    assert_eq!(keep_alive.latency(), None);
+    assert_eq!(keep_alive.finish(id + 1, now), None);
+    assert_eq!(
+        keep_alive.finish(id, now + Duration::from_millis(120)),
+        Some(Duration::from_millis(120))
+    );
+    assert_eq!(keep_alive.latency(), Some(Duration::from_millis(120)));
+
+    // Only answered once
+    assert_eq!(keep_alive.finish(id, now + INTERVAL), None);
+}
+
+#[test]
+fn unanswered_intervals_add_up() {
+    let mut keep_alive = KeepAlive::default();
+    let now = Instant::now();
+
+    for i in 0..3 {
+        assert!(keep_alive.start(2, now + INTERVAL * i).is_some());
+    }
+    assert_eq!(keep_alive.start(2, now + INTERVAL * 3), None);
+}
+
+#[test]
+fn answers_reset_the_misses() {
+    let mut keep_alive = KeepAlive::default();
+    let now = Instant::now();
+
+    for i in 0..10 {
+        keep_alive.start(1, now + INTERVAL * 2 * i).unwrap();
+        let id = keep_alive.start(1, now + INTERVAL * (2 * i + 1)).unwrap();
+
+        keep_alive.finish(id, now + INTERVAL * (2 * i + 1)).unwrap();
+    }
+}
+
+#[test]
+fn late_answers_still_count() {
+    let mut keep_alive = KeepAlive::default();
+    let now = Instant::now();
+    let round_trip = Duration::from_secs(7);
+
+    // Every answer arrives after the next keep-alive was sent
+    let mut ids = Vec::new();
+
+    for i in 0..10 {
+        ids.push(keep_alive.start(1, now + INTERVAL * i).unwrap());
+
+        if i > 0 {
+            let answered = ids[i as usize - 1];
+            let latency = keep_alive.finish(answered, now + INTERVAL * (i - 1) + round_trip);
+
+            assert_eq!(latency, Some(round_trip));
+        }
+    }
+
+    // Earlier ids are dropped once a later one is answered
+    let last = keep_alive.start(1, now + INTERVAL * 10).unwrap();
+
+    assert!(keep_alive.finish(last, now + INTERVAL * 10).is_some());
+    assert_eq!(keep_alive.finish(ids[9], now + INTERVAL * 11), None);
+}
+
+#[test]
+fn zero_misses_kicks_on_the_first() {
+    let mut keep_alive = KeepAlive::default();
+    let now = Instant::now();
+    let id = keep_alive.start(0, now).unwrap();
+
+    keep_alive.finish(id, now).unwrap();
+    keep_alive.start(0, now + INTERVAL).unwrap();
+
+    assert_eq!(keep_alive.start(0, now + INTERVAL * 2), None);
+}
@@ -0,0 +1,324 @@
+#[path = "../../rubycave/tests/common/mod.rs"]
+mod common;
+
+use std::time::{Duration, Instant};
+
+use rubycave::{
+    glam::Vec3,
+    world::{light, BlockPos, BlockRegistry, BlockState, Face, World},
+    TICK_RATE,
+};
+use rubycave_server::player::{Move, Player};
+
+use common::state;
+
+const TICK: Duration = Duration::from_millis(50);
+
+/// A single chunk with a stone floor at y = 0
+fn setup() -> (World, BlockRegistry) {
+    let registry = BlockRegistry::builtin().unwrap();
+    let world = common::lit_world(&registry, [common::floor_chunk(&registry, "stone", 1)]);
+
+    (world, registry)
+}
+
+fn step(
+    player: &mut Player,
+    world: &World,
+    registry: &BlockRegistry,
+    position: Vec3,
+    now: Instant,
+) -> Move {
+    player.handle_move(world, registry, position, 0.0, 0.0, true, now)
+}
+
+#[test]
+fn moves_wait_for_the_teleport() {
+    let (world, registry) = setup();
+    let spawn = Vec3::new(8.5, 1.0, 8.5);
+    let mut player = Player::new("steve".to_owned(), spawn);
+    let now = Instant::now();
+
+    // Sent before the client got the spawn position
+    let early = step(&mut player, &world, &registry, spawn + Vec3::X, now + TICK);
+
+    assert!(matches!(early, Move::Ignored));
+    assert_eq!(player.position, spawn);
+
+    let confirm = step(&mut player, &world, &registry, spawn, now + TICK * 2);
+    let walk = step(
+        &mut player,
+        &world,
+        &registry,
+        spawn + Vec3::X * 0.2,
+        now + TICK * 3,
+    );
+
+    assert!(matches!(confirm, Move::Accepted));
+    assert!(matches!(walk, Move::Accepted));
+    assert_eq!(player.position, spawn + Vec3::X * 0.2);
+}
+
+#[test]
+fn rejected_moves_teleport_back() {
+    let (world, registry) = setup();
+    let spawn = Vec3::new(8.5, 1.0, 8.5);
+    let mut player = Player::new("steve".to_owned(), spawn);
+    let now = Instant::now();
+
+    step(&mut player, &world, &registry, spawn, now);
+
+    // Into the floor
+    let sunk = step(
+        &mut player,
+        &world,
+        &registry,
+        spawn - Vec3::Y * 0.5,
+        now + TICK,
+    );
+
+    assert!(matches!(sunk, Move::Rejected));
+    assert_eq!(player.position, spawn);
+
+    // Until the client is back where it was sent
+    let stale = step(
+        &mut player,
+        &world,
+        &registry,
+        spawn + Vec3::X * 0.2,
+        now + TICK * 2,
+    );
+    let confirm = step(&mut player, &world, &registry, spawn, now + TICK * 3);
+
+    assert!(matches!(stale, Move::Ignored));
+    assert!(matches!(confirm, Move::Accepted));
+}
+
+#[test]
+fn invalid_moves_are_rejected() {
+    let (world, registry) = setup();
+    let spawn = Vec3::new(8.5, 1.0, 8.5);
+    let now = Instant::now();
+
+    let mut player = Player::new("steve".to_owned(), spawn);
+    step(&mut player, &world, &registry, spawn, now);
+
+    let nan = step(&mut player, &world, &registry, Vec3::NAN, now + TICK);
+    assert!(matches!(nan, Move::Rejected));
+
+    let mut player = Player::new("steve".to_owned(), spawn);
+    step(&mut player, &world, &registry, spawn, now);
+
+    let pitch = player.handle_move(&world, &registry, spawn, 0.0, 2.0, true, now + TICK);
+    assert!(matches!(pitch, Move::Rejected));
+
+    // Out of the loaded chunk
+    let edge = Vec3::new(15.5, 1.0, 8.5);
+    let mut player = Player::new("steve".to_owned(), edge);
+    step(&mut player, &world, &registry, edge, now);
+
+    let unloaded = step(
+        &mut player,
+        &world,
+        &registry,
+        edge + Vec3::X * 0.5,
+        now + TICK,
+    );
+    assert!(matches!(unloaded, Move::Rejected));
+}
+
+#[test]
+fn flooding_moves_runs_out_of_budget() {
+    let (world, registry) = setup();
+    let spawn = Vec3::new(2.5, 1.0, 8.5);
+    let mut player = Player::new("steve".to_owned(), spawn);
+    let now = Instant::now();
+
+    step(&mut player, &world, &registry, spawn, now);
+
+    // All at once, only the slack is there to spend
+    let moves: Vec<_> = (1..=3)
+        .map(|i| {
+            let position = spawn + Vec3::X * 0.9 * i as f32;
+            step(&mut player, &world, &registry, position, now)
+        })
+        .collect();
+
+    assert!(matches!(
+        moves[..],
+        [Move::Accepted, Move::Accepted, Move::Rejected]
+    ));
+
+    // Refilled after waiting
+    let later = now + Duration::from_secs(1);
+    let stopped = player.position;
+    step(&mut player, &world, &registry, stopped, later);
+
+    let walk = step(
+        &mut player,
+        &world,
+        &registry,
+        stopped + Vec3::X * 1.5,
+        later + TICK,
+    );
+    assert!(matches!(walk, Move::Accepted));
+}
+
+#[test]
+fn moves_cannot_cut_through_blocks() {
+    let (mut world, registry) = setup();
+    let stone = state(&registry, "stone");
+    light::set_block(&mut world, &registry, BlockPos::new(10, 1, 10), stone);
+
+    let start = Vec3::new(9.65, 1.0, 9.65);
+    let now = Instant::now();
+
+    // Both ends are clear, but the way between goes through the corner
+    let mut player = Player::new("steve".to_owned(), start);
+    step(&mut player, &world, &registry, start, now);
+
+    let corner = step(
+        &mut player,
+        &world,
+        &registry,
+        Vec3::new(10.35, 1.0, 11.35),
+        now + TICK,
+    );
+    assert!(matches!(corner, Move::Rejected));
+
+    // Going past it is fine
+    let mut player = Player::new("steve".to_owned(), start);
+    step(&mut player, &world, &registry, start, now);
+
+    let past = step(
+        &mut player,
+        &world,
+        &registry,
+        Vec3::new(9.6, 1.0, 11.35),
+        now + TICK,
+    );
+    assert!(matches!(past, Move::Accepted));
+}
+
+#[test]
+fn digging_takes_time() {
+    let (world, registry) = setup();
+    let mut player = Player::new("steve".to_owned(), Vec3::new(8.5, 1.0, 8.5));
+    let floor = BlockPos::new(8, 0, 8);
+    let ticks = registry.get_by_name("stone").unwrap().dig_ticks().unwrap();
+    let needed = Duration::from_secs_f32(ticks as f32 / TICK_RATE as f32);
+    let now = Instant::now();
+
+    player.start_dig(floor, now);
+    assert!(!player.finish_dig(&world, &registry, floor, now + TICK));
+
+    // The failed finish ended the dig
+    assert!(!player.finish_dig(&world, &registry, floor, now + needed));
+
+    player.start_dig(floor, now);
+    assert!(player.finish_dig(&world, &registry, floor, now + needed));
+
+    // Not the block that was started on
+    player.start_dig(floor, now);
+    assert!(!player.finish_dig(&world, &registry, floor.neighbor(Face::East), now + needed));
+}
+
+#[test]
+fn digging_needs_a_breakable_block_in_reach() {
+    let (mut world, registry) = setup();
+    let mut player = Player::new("steve".to_owned(), Vec3::new(8.5, 1.0, 8.5));
+    let bedrock = BlockPos::new(9, 0, 8);
+    let far = BlockPos::new(8, 0, 15);
+    let air = BlockPos::new(8, 3, 8);
+    let later = Instant::now() + Duration::from_secs(60);
+
+    light::set_block(&mut world, &registry, bedrock, state(&registry, "bedrock"));
+
+    for pos in [bedrock, far, air] {
+        player.start_dig(pos, Instant::now());
+        assert!(!player.finish_dig(&world, &registry, pos, later), "{pos:?}");
+    }
+}
+
+#[test]
+fn placing_goes_against_a_face() {
+    let (mut world, registry) = setup();
+    let player = Player::new("steve".to_owned(), Vec3::new(8.5, 1.0, 8.5));
+    let stone = state(&registry, "stone");
+    let floor = BlockPos::new(8, 0, 10);
+
+    assert_eq!(
+        player.check_place(&world, &registry, floor, Face::Up, stone),
+        Some(floor.neighbor(Face::Up))
+    );
+
+    // Against air, into the floor and out of reach
+    let air = BlockPos::new(8, 3, 10);
+    let far = BlockPos::new(8, 0, 15);
+
+    assert_eq!(
+        player.check_place(&world, &registry, air, Face::Up, stone),
+        None
+    );
+    assert_eq!(
+        player.check_place(&world, &registry, floor, Face::North, stone),
+        None
+    );
+    assert_eq!(
+        player.check_place(&world, &registry, far, Face::Up, stone),
+        None
+    );
+    assert_eq!(
+        player.check_place(&world, &registry, floor, Face::Up, BlockState::AIR),
+        None
+    );
+
+    // Fluids are replaced but can't be built on
+    let water = BlockPos::new(10, 1, 8);
+    light::set_block(&mut world, &registry, water, state(&registry, "water"));
+
+    assert_eq!(
+        player.check_place(
+            &world,
+            &registry,
+            water.neighbor(Face::Down),
+            Face::Up,
+            stone
+        ),
+        Some(water)
+    );
+    assert_eq!(
+        player.check_place(&world, &registry, water, Face::Up, stone),
+        None
+    );
+}
+
+#[test]
+fn placing_keeps_clear_of_the_player() {
+    let (world, registry) = setup();
+    let player = Player::new("steve".to_owned(), Vec3::new(8.5, 1.0, 8.5));
+    let floor = BlockPos::new(8, 0, 8);
+
+    assert_eq!(
+        player.check_place(
+            &world,
+            &registry,
+            floor,
+            Face::Up,
+            state(&registry, "stone")
+        ),
+        None
+    );
+
+    // Anything that can be walked through is fine
+    assert_eq!(
+        player.check_place(
+            &world,
+            &registry,
+            floor,
+            Face::Up,
+            state(&registry, "wheat")
+        ),
+        Some(floor.neighbor(Face::Up))
+    );
+}
@@ -0,0 +1,112 @@
+use rubycave::{
+    entity::{Entity, EntityId, EntityKind},
+    glam::Vec3,
+    protocol::server::{Packet, MOVE_SCALE},
+};
+use rubycave_server::tracker::{Tracker, SYNC_INTERVAL, TRACKING_RANGE};
+
+const OWN: EntityId = EntityId(1);
+const OTHER: EntityId = EntityId(2);
+
+fn player(position: Vec3) -> Entity {
+    Entity::new(EntityKind::Player, position, Vec3::ZERO)
+}
+
+#[test]
+fn entities_spawn_move_and_despawn() {
+    let mut tracker = Tracker::new(OWN);
+    let mut other = player(Vec3::ZERO);
+
+    let spawn = tracker.update(&[(OTHER, other.clone())], Vec3::ZERO);
+    assert!(matches!(spawn[..], [Packet::SpawnEntity { id: OTHER, .. }]));
+
+    // Nothing changed
+    assert!(tracker
+        .update(&[(OTHER, other.clone())], Vec3::ZERO)
+        .is_empty());
+
+    other.position.x += 1.0;
+    other.yaw = 1.0;
+
+    let moved = tracker.update(&[(OTHER, other.clone())], Vec3::ZERO);
+    let step = MOVE_SCALE as i16;
+    assert!(matches!(
+        moved[..],
+        [
+            Packet::MoveEntity {
+                id: OTHER,
+                delta: [x, 0, 0]
+            },
+            Packet::RotateEntity { id: OTHER, .. }
+        ] if x == step
+    ));
+
+    // Gone from the world
+    let gone = tracker.update(&[], Vec3::ZERO);
+    assert!(matches!(gone[..], [Packet::DespawnEntity { id: OTHER }]));
+}
+
+#[test]
+fn entities_leave_and_come_back_into_range() {
+    let mut tracker = Tracker::new(OWN);
+    let other = player(Vec3::ZERO);
+    let far = Vec3::X * (TRACKING_RANGE + 1.0);
+
+    tracker.update(&[(OTHER, other.clone())], Vec3::ZERO);
+
+    let left = tracker.update(&[(OTHER, other.clone())], far);
+    assert!(matches!(left[..], [Packet::DespawnEntity { id: OTHER }]));
+    assert!(tracker.update(&[(OTHER, other.clone())], far).is_empty());
+
+    let back = tracker.update(&[(OTHER, other)], Vec3::ZERO);
+    assert!(matches!(back[..], [Packet::SpawnEntity { id: OTHER, .. }]));
+}
+
+#[test]
+fn long_moves_teleport() {
+    let mut tracker = Tracker::new(OWN);
+    let mut other = player(Vec3::ZERO);
+
+    tracker.update(&[(OTHER, other.clone())], Vec3::ZERO);
+
+    // Further than an i16 of steps
+    other.position.z -= i16::MAX as f32 / MOVE_SCALE + 1.0;
+
+    let moved = tracker.update(&[(OTHER, other.clone())], Vec3::ZERO);
+    assert!(matches!(
+        moved[..],
+        [Packet::TeleportEntity { id: OTHER, position }] if position == other.position.to_array()
+    ));
+}
+
+#[test]
+fn positions_resync_every_interval() {
+    let mut tracker = Tracker::new(OWN);
+    let other = player(Vec3::new(0.3, 64.0, -0.7));
+
+    tracker.update(&[(OTHER, other.clone())], Vec3::ZERO);
+
+    for _ in 1..SYNC_INTERVAL {
+        assert!(tracker
+            .update(&[(OTHER, other.clone())], Vec3::ZERO)
+            .is_empty());
+    }
+
+    let sync = tracker.update(&[(OTHER, other.clone())], Vec3::ZERO);
+    assert!(matches!(
+        sync[..],
+        [Packet::TeleportEntity { id: OTHER, .. }]
+    ));
+    assert!(tracker.update(&[(OTHER, other)], Vec3::ZERO).is_empty());
+}
+
+#[test]
+fn own_entity_is_skipped() {
+    let mut tracker = Tracker::new(OWN);
+    let mut own = player(Vec3::ZERO);
+
+    assert!(tracker.update(&[(OWN, own.clone())], Vec3::ZERO).is_empty());
+
+    own.position.x += 1.0;
+    assert!(tracker.update(&[(OWN, own)], Vec3::ZERO).is_empty());
+}